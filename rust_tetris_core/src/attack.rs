@@ -0,0 +1,65 @@
+use crate::score::{ComboState, ScoreType};
+
+/// Extra lines sent by each combo step, the last one repeats for longer combos.
+const COMBO_TABLE: [u32; 10] = [1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+/// Lines sent by a clear, before back-to-back and combo bonuses.
+pub fn base_attack(score_type: ScoreType) -> u32 {
+    use ScoreType::*;
+    match score_type {
+        Single | TSpin | TSpinMini | TSpinMiniSingle => 0,
+        Double | TSpinMiniDouble => 1,
+        Triple | TSpinSingle => 2,
+        Tetris | TSpinDouble => 4,
+        TSpinTriple => 6,
+        AllClear => 10,
+    }
+}
+
+/// Lines sent to the opponent by a clear, with the combo and back-to-back
+/// level of the `ScoreUpdater` after scoring it.
+pub fn lines_sent(score_type: ScoreType, combo: ComboState, back_to_back: u32) -> u32 {
+    let combo_bonus = combo.value().map_or(0, |n| {
+        COMBO_TABLE[(n as usize - 1).min(COMBO_TABLE.len() - 1)]
+    });
+    let b2b_bonus = u32::from(back_to_back > 0);
+
+    base_attack(score_type) + combo_bonus + b2b_bonus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(ScoreType::Single, 0)]
+    #[test_case(ScoreType::Double, 1)]
+    #[test_case(ScoreType::Triple, 2)]
+    #[test_case(ScoreType::Tetris, 4)]
+    #[test_case(ScoreType::TSpinMiniDouble, 1)]
+    #[test_case(ScoreType::TSpinDouble, 4)]
+    #[test_case(ScoreType::TSpinTriple, 6)]
+    #[test_case(ScoreType::AllClear, 10)]
+    fn test_base_attack(score_type: ScoreType, lines: u32) {
+        assert_eq!(lines_sent(score_type, ComboState::Started, 0), lines);
+    }
+
+    #[test_case(ComboState::Empty, 0)]
+    #[test_case(ComboState::Continuing(1), 1)]
+    #[test_case(ComboState::Continuing(3), 2)]
+    #[test_case(ComboState::Continuing(5), 3)]
+    #[test_case(ComboState::Continuing(10), 5)]
+    #[test_case(ComboState::Continuing(30), 5)]
+    fn test_combo_bonus(combo: ComboState, bonus: u32) {
+        assert_eq!(lines_sent(ScoreType::Single, combo, 0), bonus);
+    }
+
+    #[test]
+    fn test_back_to_back_bonus() {
+        assert_eq!(lines_sent(ScoreType::Tetris, ComboState::Started, 1), 5);
+        assert_eq!(
+            lines_sent(ScoreType::TSpinDouble, ComboState::Started, 4),
+            5
+        );
+    }
+}
@@ -24,6 +24,44 @@ pub fn not_playable_piece_to_cell() -> Cell {
     Cell::Filled(PieceTypeColor::NotPlayable)
 }
 
+/// Dimensions of the playfield.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoardSize {
+    pub cols: isize,
+    /// Rows shown to the player
+    pub visible_rows: isize,
+    /// Rows above the visible ones, where pieces spawn
+    pub buffer_rows: isize,
+}
+
+impl BoardSize {
+    pub fn rows(&self) -> isize {
+        self.visible_rows + self.buffer_rows
+    }
+
+    /// Row of the bottom of a spawning piece box, just below the top
+    /// of the visible rows.
+    pub fn spawn_row(&self) -> isize {
+        self.visible_rows - 1
+    }
+
+    /// Column of the left of a spawning piece box, three wide pieces are
+    /// centered, rounding to the left.
+    pub fn spawn_col(&self) -> isize {
+        (self.cols - 3) / 2
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        Self {
+            cols: 10,
+            visible_rows: 20,
+            buffer_rows: 20,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Board {
     pub rows: isize,
@@ -48,6 +86,10 @@ impl Board {
         board
     }
 
+    pub fn with_size(size: BoardSize) -> Self {
+        Self::new(size.rows(), size.cols)
+    }
+
     pub fn get(&self, i: isize, j: isize) -> Cell {
         self.data[i as usize][j as usize]
     }
@@ -84,13 +126,27 @@ impl Board {
         self.data[i as usize].iter().all(|&cell| cell.is_filled())
     }
 
+    /// Whether the row has cells of garbage.
+    pub fn has_garbage(&self, i: isize) -> bool {
+        self.data[i as usize]
+            .iter()
+            .any(|&cell| cell == not_playable_piece_to_cell())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.data
             .iter()
             .all(|row| row.iter().all(|&cell| !cell.is_filled()))
     }
 
-    pub fn completed_rows(&mut self) -> Vec<(isize, isize)> {
+    /// Whether nothing is left once the completed rows are removed.
+    pub fn is_all_clear(&self) -> bool {
+        self.data.iter().all(|row| {
+            row.iter().all(|cell| cell.is_filled()) || row.iter().all(|cell| !cell.is_filled())
+        })
+    }
+
+    pub fn completed_rows(&self) -> Vec<(isize, isize)> {
         let mut ranges = vec![];
 
         let mut from = None;
@@ -160,6 +216,25 @@ impl Board {
         }
     }
 
+    /// Pushes `rows` garbage rows in from the bottom, filled but for
+    /// `hole_col`. Returns whether filled cells were pushed out of the top.
+    pub fn insert_garbage(&mut self, rows: usize, hole_col: isize) -> bool {
+        let rows = rows.min(self.data.len());
+        let kept = self.data.len() - rows;
+        let overflow = self.data[kept..]
+            .iter()
+            .any(|row| row.iter().any(Cell::is_filled));
+        self.data.truncate(kept);
+
+        let mut garbage = vec![not_playable_piece_to_cell(); self.cols as usize];
+        if let Some(hole) = garbage.get_mut(hole_col as usize) {
+            *hole = Cell::Empty;
+        }
+        self.data.splice(0..0, std::iter::repeat_n(garbage, rows));
+
+        overflow
+    }
+
     pub fn get_first_set_col(&self) -> Option<isize> {
         (0..self.cols)
             .flat_map(|j| (0..self.rows).map(move |i| (i, j)))
@@ -207,6 +282,18 @@ mod tests {
         assert_eq!(cell.is_filled(), expected)
     }
 
+    #[test_case(10, 3; "standard")]
+    #[test_case(4, 0; "four wide")]
+    #[test_case(12, 4; "twelve wide")]
+    #[test_case(9, 3; "odd width")]
+    fn test_spawn_col(cols: isize, expected: isize) {
+        let size = BoardSize {
+            cols,
+            ..Default::default()
+        };
+        assert_eq!(size.spawn_col(), expected);
+    }
+
     #[test]
     fn test_playable_piece_to_cell() {
         let pt = PieceType::I;
@@ -292,6 +379,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_insert_garbage() {
+        let mut board = Board::new(5, 3);
+        load_board(&mut board, "T  ");
+
+        assert!(!board.insert_garbage(2, 1));
+
+        for row in 0..2 {
+            assert!(board.is_set(row, 0));
+            assert!(!board.is_set(row, 1));
+            assert!(board.is_set(row, 2));
+        }
+        assert_eq!(
+            board.get(2, 0),
+            Cell::Filled(PieceTypeColor::Playable(PieceType::T))
+        );
+        assert_eq!(board.data.len(), 5);
+    }
+
+    #[test]
+    fn test_insert_garbage_overflows() {
+        let mut board = Board::new(3, 3);
+        load_board(&mut board, "      *  ");
+
+        assert!(!board.insert_garbage(0, 0));
+        assert!(board.insert_garbage(1, 0));
+        assert!(!board.is_set(0, 0));
+    }
+
     #[test]
     fn test_board_remove_rows3() {
         let mut board = Board::new(5, 3);
@@ -307,4 +423,14 @@ mod tests {
             }
         }
     }
+
+    #[test_case("", true; "when board is empty")]
+    #[test_case("******", true; "when only completed rows are left")]
+    #[test_case("***      ***", true; "when empty rows are in between")]
+    #[test_case("*** *    ", false; "when an incomplete row is left")]
+    fn test_is_all_clear(s: &str, expected: bool) {
+        let mut board = Board::new(5, 3);
+        load_board(&mut board, s);
+        assert_eq!(board.is_all_clear(), expected);
+    }
 }
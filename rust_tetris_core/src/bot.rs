@@ -0,0 +1,508 @@
+//! A bot playing the game through the same inputs as a player. It searches
+//! the placements the current piece can reach, scores the resulting boards
+//! and presses the actions leading to the best one.
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    board::{Board, BoardSize},
+    enums::Rotation,
+    game::{Game, FRAMES_PER_SECOND},
+    holder::Swappable,
+    input::{Action, Input},
+    pieces::{Piece, PieceWithPosition},
+};
+
+/// Placements looked further into with the next pieces, the best ones on their own
+const BEAM_WIDTH: usize = 4;
+
+/// Weights of the board features, positive ones are sought after.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weights {
+    /// Sum of the column heights
+    pub aggregate_height: f32,
+    /// Rows cleared by the placement
+    pub lines: f32,
+    /// Empty cells under the top of their column
+    pub holes: f32,
+    /// Sum of the height differences between neighbour columns
+    pub bumpiness: f32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            aggregate_height: -0.51,
+            lines: 0.76,
+            holes: -0.36,
+            bumpiness: -0.18,
+        }
+    }
+}
+
+/// A reachable resting place of a piece, with the actions leading to it from
+/// where the piece spawned. `SoftDrop` stands for dropping down to the ground,
+/// the last drop is left to the hard drop.
+#[derive(Clone)]
+pub struct Placement {
+    pub piece: PieceWithPosition,
+    pub actions: Vec<Action>,
+}
+
+/// The placement chosen for the current piece.
+#[derive(Clone)]
+pub struct Plan {
+    /// Whether to swap with the hold piece first
+    pub hold: bool,
+    pub placement: Placement,
+    pub score: f32,
+}
+
+/// Every resting place of `piece` reachable with moves, rotations and soft
+/// drops, the shortest way to each of them first.
+pub fn placements(board: &Board, piece: &PieceWithPosition) -> Vec<Placement> {
+    let mut visited = HashSet::new();
+    let mut landed = HashSet::new();
+    let mut queue = VecDeque::from([(piece.clone(), Vec::new())]);
+    let mut placements = Vec::new();
+    visited.insert(position_key(piece));
+
+    while let Some((piece, actions)) = queue.pop_front() {
+        if piece.collides_down(board) && landed.insert(cells_key(&piece)) {
+            let mut actions = actions.clone();
+            if actions.last() == Some(&Action::SoftDrop) {
+                actions.pop();
+            }
+            placements.push(Placement {
+                piece: piece.clone(),
+                actions,
+            });
+        }
+
+        for action in [
+            Action::Left,
+            Action::Right,
+            Action::RotateCw,
+            Action::RotateCcw,
+            Action::Rotate180,
+            Action::SoftDrop,
+        ] {
+            let mut next = piece.clone();
+            let moved = match action {
+                Action::Left => next.try_move_left(board),
+                Action::Right => next.try_move_right(board),
+                Action::RotateCw => next.try_rotate(board),
+                Action::RotateCcw => next.try_rotate_prev(board),
+                Action::Rotate180 => next.try_rotate_180(board),
+                _ => {
+                    while !next.collides_down(board) {
+                        next.move_down();
+                    }
+                    next.row() != piece.row()
+                }
+            };
+
+            if moved && visited.insert(position_key(&next)) {
+                let mut actions = actions.clone();
+                actions.push(action);
+                queue.push_back((next, actions));
+            }
+        }
+    }
+
+    placements
+}
+
+/// Locks `piece` on a copy of `board` and removes the completed rows,
+/// returns the new board and the rows cleared.
+pub fn lock(board: &Board, piece: &PieceWithPosition) -> (Board, u32) {
+    let mut board = board.clone();
+    piece.finalize_on(&mut board);
+
+    let completed = board.completed_rows();
+    let lines = completed.iter().map(|(from, to)| (from - to) as u32).sum();
+    board.remove_ranges(completed);
+
+    (board, lines)
+}
+
+/// Score of a board left by a placement clearing `lines` rows.
+pub fn evaluate(weights: &Weights, board: &Board, lines: u32) -> f32 {
+    let heights: Vec<isize> = (0..board.cols)
+        .map(|j| {
+            (0..board.rows)
+                .rev()
+                .find(|&i| board.is_set(i, j))
+                .map_or(0, |i| i + 1)
+        })
+        .collect();
+
+    let holes = (0..board.cols)
+        .map(|j| {
+            (0..heights[j as usize])
+                .filter(|&i| !board.is_set(i, j))
+                .count()
+        })
+        .sum::<usize>();
+    let bumpiness: isize = heights.windows(2).map(|w| (w[0] - w[1]).abs()).sum();
+    let aggregate_height: isize = heights.iter().sum();
+
+    weights.aggregate_height * aggregate_height as f32
+        + weights.lines * lines as f32
+        + weights.holes * holes as f32
+        + weights.bumpiness * bumpiness as f32
+}
+
+fn position_key(piece: &PieceWithPosition) -> (isize, isize, Rotation) {
+    (piece.row(), piece.col(), piece.tetris_piece_ref().rotation)
+}
+
+/// Placements of symmetric pieces covering the same cells are the same.
+fn cells_key(piece: &PieceWithPosition) -> Vec<(isize, isize)> {
+    let mut cells: Vec<_> = piece
+        .tetris_piece_ref()
+        .set_cells()
+        .map(|(i, j)| (i + piece.row(), j + piece.col()))
+        .collect();
+    cells.sort_unstable();
+    cells
+}
+
+/// Chooses placements and turns them into one input per tick.
+pub struct Bot {
+    pub weights: Weights,
+    /// Pieces placed per second, at most
+    pub pps: f32,
+    /// Preview pieces looked at when scoring a placement
+    pub lookahead: usize,
+    /// Actions left for the current piece
+    actions: VecDeque<Action>,
+    target: Option<PieceWithPosition>,
+    /// Frames since the last hard drop, spawn delays included
+    piece_frames: f32,
+    replanned: bool,
+}
+
+impl Bot {
+    pub fn new(weights: Weights, pps: f32) -> Self {
+        Self {
+            weights,
+            pps,
+            lookahead: 1,
+            actions: VecDeque::new(),
+            target: None,
+            piece_frames: 0.,
+            replanned: false,
+        }
+    }
+
+    /// The best placement of the current piece, or of the one swapped in when
+    /// holding is possible.
+    pub fn plan(&self, game: &Game) -> Option<Plan> {
+        let piece = game.curr_piece.as_ref()?;
+        let next: Vec<&Piece> = game.piece_bag.iter_next_pieces().collect();
+        let size = game.config.board_size;
+
+        let mut best =
+            self.best_placement(&game.board, size, piece, &next)
+                .map(|(placement, score)| Plan {
+                    hold: false,
+                    placement,
+                    score,
+                });
+
+        if game.hold_piece.can_swap() {
+            // an empty hold takes the next piece in its place
+            let (swapped, next) = match &game.hold_piece {
+                Some(hold) => (hold.piece.clone(), &next[..]),
+                None => match next.split_first() {
+                    Some((&first, rest)) => (first.clone(), rest),
+                    None => return best,
+                },
+            };
+            let Some(swapped) = spawn(&game.board, size, swapped) else { return best };
+
+            if let Some((placement, score)) = self.best_placement(&game.board, size, &swapped, next)
+            {
+                if best.as_ref().is_none_or(|best| score > best.score) {
+                    best = Some(Plan {
+                        hold: true,
+                        placement,
+                        score,
+                    });
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The input of the next tick of `game`, which runs for `frames` frames.
+    pub fn next_input(&mut self, game: &Game, frames: f32) -> Input {
+        self.piece_frames += frames;
+        let Some(piece) = &game.curr_piece else {
+            self.target = None;
+            self.actions.clear();
+            return Input::default();
+        };
+
+        if self.target.is_none() {
+            self.replanned = false;
+            match self.plan(game) {
+                Some(plan) => self.follow(plan),
+                None => return Input::default().press(Action::HardDrop),
+            }
+        }
+
+        match self.actions.front() {
+            Some(Action::SoftDrop) => {
+                if !piece.collides_down(&game.board) {
+                    return Input::default().hold(Action::SoftDrop);
+                }
+                self.actions.pop_front();
+                self.next_input(game, 0.)
+            }
+            Some(&action) => {
+                self.actions.pop_front();
+                Input::default().press(action)
+            }
+            None => self.drop(game, piece),
+        }
+    }
+
+    fn follow(&mut self, plan: Plan) {
+        self.actions = plan.placement.actions.into();
+        if plan.hold {
+            self.actions.push_front(Action::Hold);
+        }
+        self.target = Some(plan.placement.piece);
+    }
+
+    /// Hard drops once the piece is where it was meant to go and its time is
+    /// up. Gravity may have made a path fail, the piece is then placed again
+    /// from where it is, once.
+    fn drop(&mut self, game: &Game, piece: &PieceWithPosition) -> Input {
+        let mut landing = piece.clone();
+        while !landing.collides_down(&game.board) {
+            landing.move_down();
+        }
+
+        let on_target = self
+            .target
+            .as_ref()
+            .is_some_and(|target| cells_key(target) == cells_key(&landing));
+        if !on_target && !self.replanned {
+            self.replanned = true;
+            let next: Vec<&Piece> = game.piece_bag.iter_next_pieces().collect();
+            let size = game.config.board_size;
+            if let Some((placement, _)) = self.best_placement(&game.board, size, piece, &next) {
+                self.actions = placement.actions.into();
+                self.target = Some(placement.piece);
+                return Input::default();
+            }
+        }
+
+        if self.piece_frames < FRAMES_PER_SECOND / self.pps.max(0.01) {
+            return Input::default();
+        }
+        self.piece_frames = 0.;
+        Input::default().press(Action::HardDrop)
+    }
+
+    /// The placement of `piece` with the best score. The placements scoring
+    /// best on their own are scored again with the best placement of the
+    /// next piece on the board they leave.
+    fn best_placement(
+        &self,
+        board: &Board,
+        size: BoardSize,
+        piece: &PieceWithPosition,
+        next: &[&Piece],
+    ) -> Option<(Placement, f32)> {
+        self.search(board, size, piece, next, self.lookahead)
+    }
+
+    fn search(
+        &self,
+        board: &Board,
+        size: BoardSize,
+        piece: &PieceWithPosition,
+        next: &[&Piece],
+        depth: usize,
+    ) -> Option<(Placement, f32)> {
+        let mut scored: Vec<_> = placements(board, piece)
+            .into_iter()
+            .map(|placement| {
+                let (board, lines) = lock(board, &placement.piece);
+                let score = evaluate(&self.weights, &board, lines);
+                (placement, board, lines, score)
+            })
+            .collect();
+        scored.sort_by(|(.., a), (.., b)| b.total_cmp(a));
+
+        let Some((&next_piece, rest)) = next.split_first().filter(|_| depth > 0) else {
+            return scored
+                .into_iter()
+                .next()
+                .map(|(placement, .., score)| (placement, score));
+        };
+
+        scored
+            .into_iter()
+            .take(BEAM_WIDTH)
+            .map(|(placement, board, lines, score)| {
+                // the lines of this placement count on top of the best following one
+                let score = match spawn(&board, size, next_piece.clone()) {
+                    Some(spawned) => self
+                        .search(&board, size, &spawned, rest, depth - 1)
+                        .map_or(score, |(_, next_score)| {
+                            self.weights.lines * lines as f32 + next_score
+                        }),
+                    None => f32::NEG_INFINITY,
+                };
+                (placement, score)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+/// `piece` where it enters `board`, unless something is in the way.
+fn spawn(board: &Board, size: BoardSize, piece: Piece) -> Option<PieceWithPosition> {
+    let piece = PieceWithPosition::spawn(piece, size);
+    (!piece.collides(board)).then_some(piece)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::PieceType,
+        game::{GameConfig, GameEvent},
+        test_utils::load_board,
+    };
+    use test_case::test_case;
+
+    fn spawned(board: &Board, piece_type: PieceType) -> PieceWithPosition {
+        spawn(board, BoardSize::default(), Piece::new(piece_type)).unwrap()
+    }
+
+    #[test_case(PieceType::O, 9)]
+    #[test_case(PieceType::I, 17)]
+    #[test_case(PieceType::T, 34)]
+    #[test_case(PieceType::S, 17)]
+    fn test_placements_on_empty_board(piece_type: PieceType, count: usize) {
+        let board = Board::with_size(BoardSize::default());
+        let piece = spawned(&board, piece_type);
+
+        assert_eq!(placements(&board, &piece).len(), count);
+    }
+
+    fn covering(placements: Vec<Placement>, cells: &[(isize, isize)]) -> Placement {
+        placements
+            .into_iter()
+            .find(|p| cells_key(&p.piece) == cells)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_placement_under_overhang() {
+        let mut board = Board::with_size(BoardSize::default());
+        load_board(&mut board, &format!("{:20}****      ", ""));
+        let piece = spawned(&board, PieceType::O);
+
+        // only reachable by dropping next to the overhang, then sliding under it
+        let tuck = covering(
+            placements(&board, &piece),
+            &[(0, 2), (0, 3), (1, 2), (1, 3)],
+        );
+        assert_eq!(tuck.actions.last(), Some(&Action::Left));
+        assert!(tuck.actions.contains(&Action::SoftDrop));
+    }
+
+    #[test]
+    fn test_lock_clears_lines() {
+        let mut board = Board::with_size(BoardSize::default());
+        load_board(&mut board, "****  ****");
+        let piece = spawned(&board, PieceType::O);
+        let placement = covering(
+            placements(&board, &piece),
+            &[(0, 4), (0, 5), (1, 4), (1, 5)],
+        );
+
+        let (board, lines) = lock(&board, &placement.piece);
+        assert_eq!(lines, 1);
+        assert!(board.is_set(0, 4));
+        assert!(!board.is_set(0, 0));
+        assert!(!board.is_set(1, 4));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let weights = Weights {
+            aggregate_height: 1.,
+            lines: 100.,
+            holes: 10.,
+            bumpiness: 1000.,
+        };
+        let mut board = Board::with_size(BoardSize::default());
+        load_board(&mut board, "*  *      ** *      ");
+
+        // heights 2 2 0 2, one hole in the second column
+        assert_eq!(evaluate(&weights, &board, 1), 6. + 100. + 10. + 6000.);
+    }
+
+    #[test]
+    fn test_plan_fills_the_well() {
+        let mut game = Game::new(GameConfig::default(), Some(0));
+        load_board(&mut game.board, &"********* ".repeat(4));
+        game.piece_bag.add_top(Piece::new(PieceType::I));
+        game.tick(1., &Input::default());
+
+        let mut bot = Bot::new(Weights::default(), 10.);
+        bot.lookahead = 0;
+        let plan = bot.plan(&game).unwrap();
+
+        let (board, lines) = lock(&game.board, &plan.placement.piece);
+        assert_eq!(lines, 4);
+        assert!(board.is_empty());
+    }
+
+    #[test]
+    fn test_bot_plays() {
+        let mut game = Game::new(GameConfig::default(), Some(7));
+        let mut bot = Bot::new(Weights::default(), 1000.);
+        bot.lookahead = 0;
+
+        let mut pieces = 0;
+        while pieces < 100 && !game.is_over() {
+            let input = bot.next_input(&game, 1.);
+            game.tick(1., &input);
+            pieces += game
+                .drain_events()
+                .filter(|e| matches!(e, GameEvent::Locked(_)))
+                .count();
+        }
+
+        assert!(!game.is_over());
+        assert!(game.lines >= 30, "{} lines", game.lines);
+    }
+
+    #[test]
+    fn test_paced_by_pps() {
+        let mut game = Game::new(GameConfig::default(), Some(7));
+        let mut bot = Bot::new(Weights::default(), 2.);
+        bot.lookahead = 0;
+
+        let mut pieces = 0;
+        for _ in 0..600 {
+            let input = bot.next_input(&game, 1.);
+            game.tick(1., &input);
+            pieces += game
+                .drain_events()
+                .filter(|e| matches!(e, GameEvent::Locked(_)))
+                .count();
+        }
+
+        // at most two pieces per second during ten seconds, tucks take longer
+        assert!((15..=20).contains(&pieces), "{} pieces", pieces);
+    }
+}
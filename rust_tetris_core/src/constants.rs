@@ -2,9 +2,6 @@ use lazy_static::lazy_static;
 
 use crate::enums::{PieceType, Rotation};
 
-pub const R: isize = 20;
-pub const C: isize = 10;
-
 pub type Kick = (isize, isize);
 
 pub(crate) static I_KICKS: [[Kick; 5]; 8] = [
@@ -18,6 +15,18 @@ pub(crate) static I_KICKS: [[Kick; 5]; 8] = [
     [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
 ];
 
+/// TETR.IO SRS+ I kicks, mirrored between clockwise and counterclockwise rotations
+pub(crate) static SRS_PLUS_I_KICKS: [[Kick; 5]; 8] = [
+    [(0, 0), (1, 0), (-2, 0), (-2, -1), (1, 2)],
+    [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    [(0, 0), (-1, 0), (2, 0), (2, -1), (-1, 2)],
+];
+
 pub(crate) static DEFAULT_KICKS: [[Kick; 5]; 8] = [
     [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
     [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
@@ -36,6 +45,11 @@ pub(crate) static KICKS_180: [[Kick; 6]; 4] = [
     [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
 ];
 
+/// ARS tries one column right, then one column left
+pub(crate) static ARS_KICKS: [Kick; 3] = [(0, 0), (1, 0), (-1, 0)];
+
+pub(crate) static NO_KICKS: [Kick; 1] = [(0, 0)];
+
 lazy_static! {
     pub static ref I_MATRIX: [Vec<u8>; 4] = [
         vec![0, 15, 0, 0],
@@ -57,6 +71,28 @@ lazy_static! {
         [vec![2, 7, 0], vec![2, 3, 2], vec![0, 7, 2], vec![2, 6, 2]];
 }
 
+// ARS pieces spawn flat side up and rest on the bottom of their box
+lazy_static! {
+    pub static ref ARS_I_MATRIX: [Vec<u8>; 4] = [
+        vec![0, 15, 0, 0],
+        vec![2, 2, 2, 2],
+        vec![0, 15, 0, 0],
+        vec![2, 2, 2, 2]
+    ];
+    pub static ref ARS_O_MATRIX: [Vec<u8>; 4] =
+        [vec![0, 6, 6], vec![0, 6, 6], vec![0, 6, 6], vec![0, 6, 6],];
+    pub static ref ARS_Z_MATRIX: [Vec<u8>; 4] =
+        [vec![0, 6, 3], vec![1, 3, 2], vec![0, 6, 3], vec![1, 3, 2]];
+    pub static ref ARS_S_MATRIX: [Vec<u8>; 4] =
+        [vec![0, 3, 6], vec![4, 6, 2], vec![0, 3, 6], vec![4, 6, 2]];
+    pub static ref ARS_J_MATRIX: [Vec<u8>; 4] =
+        [vec![0, 7, 1], vec![2, 2, 6], vec![0, 4, 7], vec![3, 2, 2]];
+    pub static ref ARS_L_MATRIX: [Vec<u8>; 4] =
+        [vec![0, 7, 4], vec![6, 2, 2], vec![0, 1, 7], vec![2, 2, 3]];
+    pub static ref ARS_T_MATRIX: [Vec<u8>; 4] =
+        [vec![0, 7, 2], vec![2, 6, 2], vec![0, 2, 7], vec![2, 3, 2]];
+}
+
 pub fn get_rotations(piece: PieceType, rotation: Rotation) -> &'static Vec<u8> {
     piece!(
         piece,
@@ -69,3 +105,16 @@ pub fn get_rotations(piece: PieceType, rotation: Rotation) -> &'static Vec<u8> {
         T => &T_MATRIX[rotation as usize],
     )
 }
+
+pub fn get_ars_rotations(piece: PieceType, rotation: Rotation) -> &'static Vec<u8> {
+    piece!(
+        piece,
+        O => &ARS_O_MATRIX[rotation as usize],
+        I => &ARS_I_MATRIX[rotation as usize],
+        Z => &ARS_Z_MATRIX[rotation as usize],
+        S => &ARS_S_MATRIX[rotation as usize],
+        J => &ARS_J_MATRIX[rotation as usize],
+        L => &ARS_L_MATRIX[rotation as usize],
+        T => &ARS_T_MATRIX[rotation as usize],
+    )
+}
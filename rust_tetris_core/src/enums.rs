@@ -11,6 +11,18 @@ pub enum PieceType {
 }
 }
 
+impl PieceType {
+    pub const ALL: [PieceType; 7] = [
+        PieceType::T,
+        PieceType::L,
+        PieceType::J,
+        PieceType::O,
+        PieceType::I,
+        PieceType::S,
+        PieceType::Z,
+    ];
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PieceTypeColor {
     Playable(PieceType),
@@ -0,0 +1,219 @@
+//! Finesse: placing each piece with the fewest keys. A placement is judged
+//! against the shortest way to reach it on an empty board, counting taps,
+//! DAS charges and rotations as one key each.
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    board::{Board, BoardSize},
+    input::{Action, Input},
+    pieces::{Piece, PieceWithPosition},
+};
+
+/// Keys counted towards the finesse of a placement
+const FINESSE_ACTIONS: [Action; 5] = [
+    Action::Left,
+    Action::Right,
+    Action::RotateCw,
+    Action::RotateCcw,
+    Action::Rotate180,
+];
+
+/// Cells of a piece relative to its lowest row, the same wherever it falls from.
+pub type Footprint = Vec<(isize, isize)>;
+
+pub fn footprint(piece: &PieceWithPosition) -> Footprint {
+    let cells: Vec<_> = piece
+        .tetris_piece_ref()
+        .set_cells()
+        .map(|(i, j)| (i + piece.row(), j + piece.col()))
+        .collect();
+    let bottom = cells.iter().map(|&(i, _)| i).min().unwrap_or(0);
+
+    let mut footprint: Footprint = cells.into_iter().map(|(i, j)| (i - bottom, j)).collect();
+    footprint.sort_unstable();
+    footprint
+}
+
+/// Fewest keys moving `piece` from where it spawns to the columns and
+/// orientation of `target` on an empty board of `size`.
+pub fn minimal_inputs(piece: Piece, size: BoardSize, target: &PieceWithPosition) -> Option<u32> {
+    let board = Board::with_size(size);
+    let goal = footprint(target);
+    let start = PieceWithPosition::spawn(piece, size);
+
+    let mut visited = HashSet::from([footprint(&start)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((piece, inputs)) = queue.pop_front() {
+        if footprint(&piece) == goal {
+            return Some(inputs);
+        }
+
+        for key in KEYS {
+            let mut next = piece.clone();
+            if key(&mut next, &board) && visited.insert(footprint(&next)) {
+                queue.push_back((next, inputs + 1));
+            }
+        }
+    }
+
+    None
+}
+
+type Key = fn(&mut PieceWithPosition, &Board) -> bool;
+
+/// What a single key can do: tap, charge DAS or rotate
+const KEYS: [Key; 7] = [
+    PieceWithPosition::try_move_left,
+    PieceWithPosition::try_move_right,
+    |piece, board| das(piece, board, PieceWithPosition::try_move_left),
+    |piece, board| das(piece, board, PieceWithPosition::try_move_right),
+    PieceWithPosition::try_rotate,
+    PieceWithPosition::try_rotate_prev,
+    PieceWithPosition::try_rotate_180,
+];
+
+/// Moves as far as the wall, returns whether the piece moved at all.
+fn das(piece: &mut PieceWithPosition, board: &Board, tap: Key) -> bool {
+    let moved = tap(piece, board);
+    while tap(piece, board) {}
+    moved
+}
+
+/// A piece placed with more keys than needed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FinesseFault {
+    pub inputs: u32,
+    pub minimal: u32,
+}
+
+/// Counts the keys pressed for the current piece and judges its placement.
+#[derive(Clone, Debug, Default)]
+pub struct FinesseTracker {
+    inputs: u32,
+    /// Soft drops reach places no finesse leads to, those pieces are not judged
+    soft_dropped: bool,
+}
+
+impl FinesseTracker {
+    pub fn handle_input(&mut self, input: &Input) {
+        self.inputs += FINESSE_ACTIONS
+            .iter()
+            .filter(|&&action| input.is_pressed(action))
+            .count() as u32;
+        self.soft_dropped |= input.is_down(Action::SoftDrop);
+    }
+
+    /// Starts over with a new piece, such as after a hold.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Judges the placement of `piece` locked on a board of `size`, then
+    /// starts over for the next piece.
+    pub fn locked(&mut self, piece: &PieceWithPosition, size: BoardSize) -> Option<FinesseFault> {
+        let tracker = std::mem::take(self);
+        if tracker.soft_dropped {
+            return None;
+        }
+
+        let spawned = Piece::with_system(
+            piece.tetris_piece_ref().piece_type,
+            piece.tetris_piece_ref().system(),
+        );
+        let minimal = minimal_inputs(spawned, size, piece)?;
+        (tracker.inputs > minimal).then_some(FinesseFault {
+            inputs: tracker.inputs,
+            minimal,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enums::PieceType, rotation::Srs};
+    use test_case::test_case;
+
+    /// `piece_type` rotated `rotations` times clockwise, then moved by `cols`.
+    fn placed(piece_type: PieceType, rotations: usize, cols: isize) -> PieceWithPosition {
+        let size = BoardSize::default();
+        let board = Board::with_size(size);
+        let mut piece = PieceWithPosition::spawn(Piece::new(piece_type), size);
+        for _ in 0..rotations {
+            assert!(piece.try_rotate(&board));
+        }
+        for _ in 0..cols.abs() {
+            assert!(if cols < 0 {
+                piece.try_move_left(&board)
+            } else {
+                piece.try_move_right(&board)
+            });
+        }
+        piece
+    }
+
+    #[test_case(PieceType::T, 0, 0, 0; "in place")]
+    #[test_case(PieceType::T, 0, -1, 1; "one tap")]
+    #[test_case(PieceType::T, 0, -3, 1; "das to the wall")]
+    #[test_case(PieceType::T, 0, -2, 2; "das then tap back")]
+    #[test_case(PieceType::T, 2, 0, 1; "half turn")]
+    #[test_case(PieceType::T, 3, 0, 1; "counterclockwise")]
+    #[test_case(PieceType::I, 1, 0, 1; "vertical I")]
+    #[test_case(PieceType::O, 0, 4, 1; "O to the right wall")]
+    fn test_minimal_inputs(piece_type: PieceType, rotations: usize, cols: isize, inputs: u32) {
+        let target = placed(piece_type, rotations, cols);
+        assert_eq!(
+            minimal_inputs(Piece::new(piece_type), BoardSize::default(), &target),
+            Some(inputs)
+        );
+    }
+
+    #[test]
+    fn test_symmetric_orientations_match() {
+        let size = BoardSize::default();
+        let mut ccw = PieceWithPosition::spawn(Piece::new(PieceType::S), size);
+        assert!(ccw.try_rotate_prev(&Board::with_size(size)));
+        ccw.move_right();
+
+        // the same columns as a clockwise turn in place
+        assert_eq!(footprint(&ccw), footprint(&placed(PieceType::S, 1, 0)));
+        assert_eq!(
+            minimal_inputs(Piece::new(PieceType::S), size, &ccw),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_tracker_reports_extra_keys() {
+        let size = BoardSize::default();
+        let target = placed(PieceType::T, 0, -3);
+        let mut tracker = FinesseTracker::default();
+
+        // tapping thrice instead of charging DAS once
+        for _ in 0..3 {
+            tracker.handle_input(&Input::default().press(Action::Left));
+        }
+        assert_eq!(
+            tracker.locked(&target, size),
+            Some(FinesseFault {
+                inputs: 3,
+                minimal: 1
+            })
+        );
+
+        tracker.handle_input(&Input::default().press(Action::Left));
+        assert_eq!(tracker.locked(&target, size), None);
+    }
+
+    #[test]
+    fn test_tracker_skips_soft_drops() {
+        let mut tracker = FinesseTracker::default();
+        tracker.handle_input(&Input::default().press(Action::RotateCw));
+        tracker.handle_input(&Input::default().press(Action::RotateCcw));
+        tracker.handle_input(&Input::default().hold(Action::SoftDrop));
+
+        let target =
+            PieceWithPosition::spawn(Piece::with_system(PieceType::T, &Srs), BoardSize::default());
+        assert_eq!(tracker.locked(&target, BoardSize::default()), None);
+    }
+}
@@ -0,0 +1,772 @@
+//! Import and export of v115 fumen strings, the format of the fumen
+//! board editor used to share setups.
+use crate::{
+    board::{Board, Cell},
+    enums::{PieceType, PieceTypeColor, Rotation},
+    pieces::{Piece, PieceWithPosition},
+};
+use std::fmt;
+
+const VERSION: &str = "v115@";
+const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const COMMENT_TABLE: &[u8] =
+    b" !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const COMMENT_BASE: u32 = COMMENT_TABLE.len() as u32 + 1;
+const MAX_COMMENT_LENGTH: usize = 4095;
+
+/// Columns of a fumen field.
+pub const FIELD_WIDTH: isize = 10;
+/// Rows of a fumen field, not counting the garbage row below it.
+pub const FIELD_HEIGHT: isize = 23;
+const FIELD_BLOCKS: usize = ((FIELD_HEIGHT + 1) * FIELD_WIDTH) as usize;
+
+/// Fumen cells, top row first, the last row is the garbage row.
+type Field = [u8; FIELD_BLOCKS];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FumenError {
+    /// Not a v115 fumen
+    Version,
+    /// A character outside of the fumen alphabet
+    Character(char),
+    /// The data ends in the middle of a page
+    Truncated,
+    /// The data decodes to an impossible field, piece or comment
+    Invalid,
+}
+
+impl fmt::Display for FumenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FumenError::Version => write!(f, "only v115 fumens are supported"),
+            FumenError::Character(c) => write!(f, "unexpected character {:?}", c),
+            FumenError::Truncated => write!(f, "unexpected end of data"),
+            FumenError::Invalid => write!(f, "invalid page data"),
+        }
+    }
+}
+
+impl std::error::Error for FumenError {}
+
+/// A page of a fumen.
+#[derive(Clone)]
+pub struct Page {
+    /// Field before the piece is placed, `FIELD_HEIGHT` x `FIELD_WIDTH`
+    pub board: Board,
+    pub piece: Option<PieceWithPosition>,
+    pub comment: String,
+    /// Whether the piece is placed and the full rows cleared
+    /// for the next page
+    pub lock: bool,
+}
+
+impl Page {
+    pub fn new(board: Board) -> Self {
+        Page {
+            board,
+            piece: None,
+            comment: String::new(),
+            lock: true,
+        }
+    }
+}
+
+/// Decodes all the pages of `data`, either a bare fumen or a fumen url.
+pub fn decode(data: &str) -> Result<Vec<Page>, FumenError> {
+    let start = data.find(VERSION).ok_or(FumenError::Version)?;
+    let mut reader = Reader::new(data[start + VERSION.len()..].trim())?;
+
+    let mut pages = Vec::new();
+    let mut field = [0; FIELD_BLOCKS];
+    let mut repeat = 0;
+    let mut comment = String::new();
+
+    while !reader.is_done() {
+        if repeat > 0 {
+            repeat -= 1;
+        } else {
+            let mut index = 0;
+            let mut changed = true;
+            while index < FIELD_BLOCKS {
+                let run = reader.poll(2)? as usize;
+                let diff = run / FIELD_BLOCKS;
+                let count = run % FIELD_BLOCKS + 1;
+                if diff == 8 && count == FIELD_BLOCKS {
+                    changed = false;
+                }
+                if index + count > FIELD_BLOCKS {
+                    return Err(FumenError::Invalid);
+                }
+                for block in &mut field[index..index + count] {
+                    let value = (*block as usize + diff)
+                        .checked_sub(8)
+                        .filter(|&v| v <= 8)
+                        .ok_or(FumenError::Invalid)?;
+                    *block = value as u8;
+                }
+                index += count;
+            }
+            if !changed {
+                repeat = reader.poll(1)?;
+            }
+        }
+
+        let mut action = reader.poll(3)? as usize;
+        let kind = action % 8;
+        action /= 8;
+        let rotation = action % 4;
+        action /= 4;
+        let position = action % FIELD_BLOCKS;
+        action /= FIELD_BLOCKS;
+        let rise = action % 2 == 1;
+        action /= 2;
+        let mirror = action % 2 == 1;
+        // the colorize flag only matters to the editor
+        action /= 4;
+        let has_comment = action % 2 == 1;
+        action /= 2;
+        let lock = action.is_multiple_of(2);
+
+        if has_comment {
+            let length = reader.poll(2)? as usize;
+            let mut escaped = String::with_capacity(length + 3);
+            for _ in 0..length.div_ceil(4) {
+                let mut value = reader.poll(5)?;
+                for _ in 0..4 {
+                    let ch = COMMENT_TABLE
+                        .get((value % COMMENT_BASE) as usize)
+                        .ok_or(FumenError::Invalid)?;
+                    escaped.push(*ch as char);
+                    value /= COMMENT_BASE;
+                }
+            }
+            escaped.truncate(length);
+            comment = unescape(&escaped);
+        }
+
+        let piece = match kind {
+            0 => None,
+            _ => {
+                let piece_type = piece_type_from(kind).ok_or(FumenError::Invalid)?;
+                let rotation = rotation_from(rotation);
+                let (x, y) = center_from(piece_type, rotation, position);
+                Some(piece_at(piece_type, rotation, x, y).ok_or(FumenError::Invalid)?)
+            }
+        };
+
+        let page = Page {
+            board: to_board(&field),
+            piece,
+            comment: comment.clone(),
+            lock,
+        };
+
+        if lock {
+            if let Some(piece) = &page.piece {
+                place(&mut field, piece);
+            }
+            clear_lines(&mut field);
+            if rise {
+                rise_garbage(&mut field);
+            }
+            if mirror {
+                mirror_field(&mut field);
+            }
+        }
+        pages.push(page);
+    }
+
+    Ok(pages)
+}
+
+/// Encodes `pages` as a v115 fumen.
+pub fn encode(pages: &[Page]) -> String {
+    let mut values = Vec::new();
+    let mut prev = [0; FIELD_BLOCKS];
+    let mut last_repeat: Option<usize> = None;
+    let mut prev_comment = String::new();
+
+    for (index, page) in pages.iter().enumerate() {
+        let mut field = from_board(&page.board);
+
+        let changed = field != prev;
+        match last_repeat {
+            Some(at) if !changed && values[at] < 63 => values[at] += 1,
+            _ => {
+                encode_field(&mut values, &prev, &field);
+                last_repeat = if changed {
+                    None
+                } else {
+                    values.push(0);
+                    Some(values.len() - 1)
+                };
+            }
+        }
+
+        let comment_changed = page.comment != prev_comment;
+        let (kind, rotation, position) = match &page.piece {
+            Some(piece) => encode_piece(piece),
+            None => (0, 0, 0),
+        };
+
+        let mut action = usize::from(!page.lock);
+        action = action * 2 + usize::from(comment_changed);
+        // pages after the first inherit the colorize flag
+        action = action * 2 + usize::from(index == 0);
+        // mirror and rise are never set on export
+        action *= 4;
+        action = action * FIELD_BLOCKS + position;
+        action = action * 4 + rotation;
+        action = action * 8 + kind;
+        push(&mut values, action as u32, 3);
+
+        if comment_changed {
+            let mut escaped = escape(&page.comment);
+            escaped.truncate(MAX_COMMENT_LENGTH);
+            push(&mut values, escaped.len() as u32, 2);
+            for chunk in escaped.as_bytes().chunks(4) {
+                let value = chunk.iter().rev().fold(0, |value, ch| {
+                    let index = COMMENT_TABLE.iter().position(|c| c == ch).unwrap_or(0);
+                    value * COMMENT_BASE + index as u32
+                });
+                push(&mut values, value, 5);
+            }
+            prev_comment = page.comment.clone();
+        }
+
+        if page.lock {
+            if let Some(piece) = &page.piece {
+                place(&mut field, piece);
+            }
+            clear_lines(&mut field);
+        }
+        prev = field;
+    }
+
+    let mut data = String::from(VERSION);
+    for (i, value) in values.iter().enumerate() {
+        if i >= 42 && (i - 42) % 47 == 0 {
+            data.push('?');
+        }
+        data.push(TABLE[*value as usize] as char);
+    }
+    data
+}
+
+struct Reader {
+    values: Vec<u32>,
+    pos: usize,
+}
+
+impl Reader {
+    fn new(data: &str) -> Result<Self, FumenError> {
+        let values = data
+            .chars()
+            .filter(|&c| c != '?')
+            .map(|c| {
+                TABLE
+                    .iter()
+                    .position(|&t| t as char == c)
+                    .map(|v| v as u32)
+                    .ok_or(FumenError::Character(c))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Reader { values, pos: 0 })
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.values.len()
+    }
+
+    /// Reads a little endian number of `n` digits.
+    fn poll(&mut self, n: usize) -> Result<u32, FumenError> {
+        let digits = self
+            .values
+            .get(self.pos..self.pos + n)
+            .ok_or(FumenError::Truncated)?;
+        self.pos += n;
+
+        Ok(digits
+            .iter()
+            .rev()
+            .fold(0, |value, digit| value * 64 + digit))
+    }
+}
+
+fn push(values: &mut Vec<u32>, mut value: u32, n: usize) {
+    for _ in 0..n {
+        values.push(value % 64);
+        value /= 64;
+    }
+}
+
+fn encode_field(values: &mut Vec<u32>, prev: &Field, field: &Field) {
+    let diffs = prev
+        .iter()
+        .zip(field.iter())
+        .map(|(&p, &c)| c as usize + 8 - p as usize);
+
+    let mut run: Option<(usize, usize)> = None;
+    for diff in diffs {
+        run = match run {
+            Some((d, count)) if d == diff => Some((d, count + 1)),
+            Some((d, count)) => {
+                push(values, (d * FIELD_BLOCKS + count - 1) as u32, 2);
+                Some((diff, 1))
+            }
+            None => Some((diff, 1)),
+        };
+    }
+    if let Some((d, count)) = run {
+        push(values, (d * FIELD_BLOCKS + count - 1) as u32, 2);
+    }
+}
+
+fn piece_type_from(kind: usize) -> Option<PieceType> {
+    match kind {
+        1 => Some(PieceType::I),
+        2 => Some(PieceType::L),
+        3 => Some(PieceType::O),
+        4 => Some(PieceType::Z),
+        5 => Some(PieceType::T),
+        6 => Some(PieceType::J),
+        7 => Some(PieceType::S),
+        _ => None,
+    }
+}
+
+fn kind_of(piece_type: PieceType) -> u8 {
+    piece!(piece_type,
+        O => 3,
+        I => 1,
+        Z => 4,
+        S => 7,
+        J => 6,
+        L => 2,
+        T => 5,
+    )
+}
+
+fn cell_from(block: u8) -> Cell {
+    match piece_type_from(block as usize) {
+        Some(piece_type) => Cell::Filled(PieceTypeColor::Playable(piece_type)),
+        None if block == 8 => Cell::Filled(PieceTypeColor::NotPlayable),
+        None => Cell::Empty,
+    }
+}
+
+fn block_of(cell: Cell) -> u8 {
+    match cell {
+        Cell::Filled(PieceTypeColor::Playable(piece_type)) => kind_of(piece_type),
+        Cell::Filled(PieceTypeColor::NotPlayable) => 8,
+        Cell::Empty => 0,
+    }
+}
+
+fn rotation_from(rotation: usize) -> Rotation {
+    match rotation {
+        0 => Rotation::R180,
+        1 => Rotation::R90,
+        2 => Rotation::R0,
+        _ => Rotation::R270,
+    }
+}
+
+fn rotation_of(rotation: Rotation) -> usize {
+    match rotation {
+        Rotation::R180 => 0,
+        Rotation::R90 => 1,
+        Rotation::R0 => 2,
+        Rotation::R270 => 3,
+    }
+}
+
+/// Index of the cell at column `x`, row `y` counting up from the bottom
+/// of the field, the garbage row is `y = -1`.
+fn index_of(x: isize, y: isize) -> Option<usize> {
+    if (0..FIELD_WIDTH).contains(&x) && (-1..FIELD_HEIGHT).contains(&y) {
+        Some(((FIELD_HEIGHT - 1 - y) * FIELD_WIDTH + x) as usize)
+    } else {
+        None
+    }
+}
+
+/// Cells of a piece relative to its fumen center, y up.
+fn shape(piece_type: PieceType, rotation: Rotation) -> [(isize, isize); 4] {
+    let spawn = piece!(piece_type,
+        O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+        Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+        S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+        L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+        T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+    );
+
+    spawn.map(|(x, y)| match rotation {
+        Rotation::R0 => (x, y),
+        Rotation::R90 => (y, -x),
+        Rotation::R180 => (-x, -y),
+        Rotation::R270 => (-y, x),
+    })
+}
+
+/// Fumen stores the centers of O, I, S and Z off by one cell in some
+/// rotations, converts a stored position to the center of `shape`.
+fn center_from(piece_type: PieceType, rotation: Rotation, position: usize) -> (isize, isize) {
+    let x = (position % FIELD_WIDTH as usize) as isize;
+    let y = FIELD_HEIGHT - 1 - (position / FIELD_WIDTH as usize) as isize;
+    let (dx, dy) = center_adjustment(piece_type, rotation);
+    (x - dx, y - dy)
+}
+
+fn position_of(piece_type: PieceType, rotation: Rotation, (x, y): (isize, isize)) -> usize {
+    let (dx, dy) = center_adjustment(piece_type, rotation);
+    ((FIELD_HEIGHT - 1 - (y + dy)) * FIELD_WIDTH + x + dx) as usize
+}
+
+fn center_adjustment(piece_type: PieceType, rotation: Rotation) -> (isize, isize) {
+    match (piece_type, rotation) {
+        (PieceType::O, Rotation::R0) => (0, 1),
+        (PieceType::O, Rotation::R180) => (-1, 0),
+        (PieceType::O, Rotation::R270) => (-1, 1),
+        (PieceType::I, Rotation::R180) => (-1, 0),
+        (PieceType::I, Rotation::R270) => (0, 1),
+        (PieceType::S | PieceType::Z, Rotation::R0) => (0, 1),
+        (PieceType::S, Rotation::R90) => (1, 0),
+        (PieceType::Z, Rotation::R270) => (-1, 0),
+        _ => (0, 0),
+    }
+}
+
+/// Lowest of two cells, by row then column.
+fn lowest((x, y): (isize, isize), (i, j): (isize, isize)) -> (isize, isize) {
+    if (y, x) < (j, i) {
+        (x, y)
+    } else {
+        (i, j)
+    }
+}
+
+/// Places a piece so that its cells match the fumen ones.
+fn piece_at(
+    piece_type: PieceType,
+    rotation: Rotation,
+    x: isize,
+    y: isize,
+) -> Option<PieceWithPosition> {
+    let piece = Piece::with_rotation(piece_type, rotation);
+    let cells = shape(piece_type, rotation).map(|(dx, dy)| (x + dx, y + dy));
+    if cells
+        .iter()
+        .any(|&(x, y)| y < 0 || index_of(x, y).is_none())
+    {
+        return None;
+    }
+
+    let (fx, fy) = cells.into_iter().reduce(lowest)?;
+    let (pi, pj) = piece.set_cells().min()?;
+    Some(PieceWithPosition::new(fy - pi, fx - pj, piece))
+}
+
+fn encode_piece(piece: &PieceWithPosition) -> (usize, usize, usize) {
+    let p = piece.tetris_piece_ref();
+    let (fx, fy) = shape(p.piece_type, p.rotation)
+        .into_iter()
+        .reduce(lowest)
+        .unwrap();
+    let (pi, pj) = p.set_cells().min().unwrap();
+    let center = (piece.col() + pj - fx, piece.row() + pi - fy);
+
+    (
+        kind_of(p.piece_type) as usize,
+        rotation_of(p.rotation),
+        position_of(p.piece_type, p.rotation, center),
+    )
+}
+
+fn place(field: &mut Field, piece: &PieceWithPosition) {
+    let p = piece.tetris_piece_ref();
+    for (i, j) in p.set_cells() {
+        if let Some(index) = index_of(piece.col() + j, piece.row() + i) {
+            field[index] = kind_of(p.piece_type);
+        }
+    }
+}
+
+fn clear_lines(field: &mut Field) {
+    let width = FIELD_WIDTH as usize;
+    let garbage = FIELD_BLOCKS - width;
+    let rows: Vec<_> = field[..garbage]
+        .chunks(width)
+        .filter(|row| row.contains(&0))
+        .flatten()
+        .copied()
+        .collect();
+
+    let cleared = garbage - rows.len();
+    field[..cleared].fill(0);
+    field[cleared..garbage].copy_from_slice(&rows);
+}
+
+fn rise_garbage(field: &mut Field) {
+    let width = FIELD_WIDTH as usize;
+    field.copy_within(width.., 0);
+    field[FIELD_BLOCKS - width..].fill(0);
+}
+
+fn mirror_field(field: &mut Field) {
+    let garbage = FIELD_BLOCKS - FIELD_WIDTH as usize;
+    for row in field[..garbage].chunks_mut(FIELD_WIDTH as usize) {
+        row.reverse();
+    }
+}
+
+fn to_board(field: &Field) -> Board {
+    let mut board = Board::new(FIELD_HEIGHT, FIELD_WIDTH);
+    for y in 0..FIELD_HEIGHT {
+        for x in 0..FIELD_WIDTH {
+            board.set_val(y, x, cell_from(field[index_of(x, y).unwrap()]));
+        }
+    }
+    board
+}
+
+/// Cells of `board` past the fumen field are dropped.
+fn from_board(board: &Board) -> Field {
+    let mut field = [0; FIELD_BLOCKS];
+    for y in 0..FIELD_HEIGHT.min(board.rows) {
+        for x in 0..FIELD_WIDTH.min(board.cols) {
+            field[index_of(x, y).unwrap()] = block_of(board.get(y, x));
+        }
+    }
+    field
+}
+
+/// Comments are stored the way javascript's `escape` leaves them.
+fn escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for unit in s.encode_utf16() {
+        match char::from_u32(unit as u32) {
+            Some(c) if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) => escaped.push(c),
+            _ if unit < 256 => escaped.push_str(&format!("%{:02X}", unit)),
+            _ => escaped.push_str(&format!("%u{:04X}", unit)),
+        }
+    }
+    escaped
+}
+
+fn unescape(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |from: usize, len: usize| {
+            s.get(from..from + len)
+                .and_then(|h| u16::from_str_radix(h, 16).ok())
+        };
+        let unit = match bytes[i] {
+            b'%' if bytes.get(i + 1) == Some(&b'u') => hex(i + 2, 4).map(|u| (u, 6)),
+            b'%' => hex(i + 1, 2).map(|u| (u, 3)),
+            _ => None,
+        };
+        match unit {
+            Some((unit, len)) => {
+                units.push(unit);
+                i += len;
+            }
+            None => {
+                units.push(bytes[i] as u16);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::load_board;
+    use test_case::test_case;
+
+    fn assert_same_board(a: &Board, b: &Board) {
+        for i in 0..a.rows {
+            for j in 0..a.cols {
+                assert_eq!(a.get(i, j), b.get(i, j), "cell {} {}", i, j);
+            }
+        }
+    }
+
+    fn cells(piece: &PieceWithPosition) -> Vec<(isize, isize)> {
+        let mut cells: Vec<_> = piece
+            .tetris_piece_ref()
+            .set_cells()
+            .map(|(i, j)| (i + piece.row(), j + piece.col()))
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn test_empty_field() {
+        let pages = decode("v115@vhAAgH").unwrap();
+
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].board.is_empty());
+        assert!(pages[0].piece.is_none());
+        assert!(pages[0].comment.is_empty());
+
+        let page = Page::new(Board::new(FIELD_HEIGHT, FIELD_WIDTH));
+        assert_eq!(encode(&[page]), "v115@vhAAgH");
+    }
+
+    /// A T-spin double setup, the T locks on the first page and clears both rows.
+    const TSD: &str = "v115@KhglFeC8CezhD8AeE8JeFLYDAUNSBAvhAAAA";
+
+    fn tsd_board() -> Board {
+        let mut board = Board::new(FIELD_HEIGHT, FIELD_WIDTH);
+        load_board(&mut board, "**** ********   IIII   L      ");
+        board
+    }
+
+    #[test]
+    fn test_decode_tsd() {
+        let pages = decode(TSD).unwrap();
+        assert_eq!(pages.len(), 2);
+
+        assert_same_board(&pages[0].board, &tsd_board());
+        let piece = pages[0].piece.as_ref().unwrap();
+        assert_eq!(piece.tetris_piece_ref().piece_type, PieceType::T);
+        assert_eq!(piece.tetris_piece_ref().rotation, Rotation::R180);
+        assert_eq!(cells(piece), vec![(0, 4), (1, 3), (1, 4), (1, 5)]);
+        assert_eq!(pages[0].comment, "TSD");
+        assert!(pages[0].lock);
+
+        let mut board = Board::new(FIELD_HEIGHT, FIELD_WIDTH);
+        load_board(&mut board, "   L      ");
+        assert_same_board(&pages[1].board, &board);
+        assert!(pages[1].piece.is_none());
+        assert_eq!(pages[1].comment, "TSD");
+    }
+
+    #[test]
+    fn test_encode_tsd() {
+        let mut first = Page::new(tsd_board());
+        first.piece = Some(piece_at(PieceType::T, Rotation::R180, 4, 1).unwrap());
+        first.comment = String::from("TSD");
+
+        let mut board = Board::new(FIELD_HEIGHT, FIELD_WIDTH);
+        load_board(&mut board, "   L      ");
+        let mut second = Page::new(board);
+        second.comment = String::from("TSD");
+
+        assert_eq!(encode(&[first, second]), TSD);
+    }
+
+    #[test_case("v114@vhAAgH", FumenError::Version)]
+    #[test_case("v115@vh!AgH", FumenError::Character('!'))]
+    #[test_case("v115@vhAAg", FumenError::Truncated)]
+    fn test_decode_errors(data: &str, error: FumenError) {
+        assert_eq!(decode(data).err(), Some(error));
+    }
+
+    #[test]
+    fn test_url() {
+        let pages = decode("https://fumen.zui.jp/?v115@vhAAgH").unwrap();
+        assert_eq!(pages.len(), 1);
+    }
+
+    #[test]
+    fn test_field_round_trip() {
+        let mut board = Board::new(FIELD_HEIGHT, FIELD_WIDTH);
+        load_board(&mut board, "IIII ****TTTJJJ  SS T  J   SS  ");
+        let mut page = Page::new(board.clone());
+        page.comment = String::from("opener 100%");
+
+        let pages = decode(&encode(&[page])).unwrap();
+        assert_same_board(&pages[0].board, &board);
+        assert_eq!(pages[0].comment, "opener 100%");
+    }
+
+    #[test]
+    fn test_piece_round_trip() {
+        let rotations = [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270];
+        use PieceType::*;
+        for piece_type in [O, I, Z, S, J, L, T] {
+            for rotation in rotations {
+                let piece =
+                    PieceWithPosition::new(5, 4, Piece::with_rotation(piece_type, rotation));
+                let mut page = Page::new(Board::new(FIELD_HEIGHT, FIELD_WIDTH));
+                page.piece = Some(piece.clone());
+
+                let pages = decode(&encode(&[page])).unwrap();
+                let decoded = pages[0].piece.as_ref().unwrap();
+                assert_eq!(decoded.tetris_piece_ref().piece_type, piece_type);
+                assert_eq!(decoded.tetris_piece_ref().rotation, rotation);
+                assert_eq!(cells(decoded), cells(&piece));
+            }
+        }
+    }
+
+    // centered on the fifth column, second row
+    #[test_case(PieceType::T, Rotation::R0, 214; "t spawn")]
+    #[test_case(PieceType::O, Rotation::R0, 204; "o spawn")]
+    #[test_case(PieceType::O, Rotation::R180, 213; "o reverse")]
+    #[test_case(PieceType::I, Rotation::R180, 213; "i reverse")]
+    fn test_piece_position(piece_type: PieceType, rotation: Rotation, position: usize) {
+        let piece = piece_at(piece_type, rotation, 4, 1).unwrap();
+        let (_, _, encoded) = encode_piece(&piece);
+        assert_eq!(encoded, position);
+        assert_eq!(center_from(piece_type, rotation, encoded), (4, 1));
+    }
+
+    #[test]
+    fn test_lock_clears_lines() {
+        let mut board = Board::new(FIELD_HEIGHT, FIELD_WIDTH);
+        load_board(&mut board, "******    *");
+        let mut first = Page::new(board);
+        first.piece = Some(piece_at(PieceType::I, Rotation::R0, 7, 0).unwrap());
+        first.comment = String::from("clear");
+
+        let mut second = Page::new(Board::new(FIELD_HEIGHT, FIELD_WIDTH));
+        second.board.set(0, 0, PieceTypeColor::NotPlayable);
+        second.comment = String::from("clear");
+
+        let data = encode(&[first, second]);
+        let pages = decode(&data).unwrap();
+
+        assert_eq!(pages.len(), 2);
+        assert!(pages[1].board.is_set(0, 0));
+        assert!(!pages[1].board.is_set(1, 0));
+        // unchanged comments carry over
+        assert_eq!(pages[1].comment, "clear");
+    }
+
+    #[test]
+    fn test_repeated_fields() {
+        let pages = vec![Page::new(Board::new(FIELD_HEIGHT, FIELD_WIDTH)); 70];
+
+        let data = encode(&pages);
+        assert!(data.contains('?'));
+        assert_eq!(decode(&data).unwrap().len(), 70);
+    }
+
+    #[test_case("hello world", "hello%20world")]
+    #[test_case("100%", "100%25")]
+    #[test_case("テト", "%u30C6%u30C8")]
+    fn test_escape(s: &str, escaped: &str) {
+        assert_eq!(escape(s), escaped);
+        assert_eq!(unescape(escaped), s);
+    }
+
+    #[test]
+    fn test_unicode_comment() {
+        let mut page = Page::new(Board::new(FIELD_HEIGHT, FIELD_WIDTH));
+        page.comment = String::from("テトリス 🎮");
+
+        let pages = decode(&encode(&[page])).unwrap();
+        assert_eq!(pages[0].comment, "テトリス 🎮");
+    }
+}
@@ -0,0 +1,1241 @@
+use std::mem;
+
+use log::debug;
+
+use crate::{
+    attack,
+    board::{Board, BoardSize},
+    enums::PieceType,
+    finesse::FinesseTracker,
+    fumen::Page,
+    garbage::{GarbageGenerator, GarbagePattern},
+    holder::{HoldPiece, Swappable},
+    input::{Action, Input},
+    lock::{LockDelay, LockDelayPolicy},
+    mode::{level_gravity, GameMode, CHEESE_RACE_ROWS},
+    piece_bag::{PieceBag, RandomizerType},
+    pieces::{Piece, PieceWithPosition},
+    rotation::RotationSystemType,
+    score::{Moves, ScoreType, ScoreUpdater},
+    timer::FrameTimer,
+};
+
+pub const FRAMES_PER_SECOND: f32 = 60.;
+
+/// Tunable parameters of a game. Durations are in frames,
+/// gravities in rows per frame.
+#[derive(Clone, Debug)]
+pub struct GameConfig {
+    pub gravity: f32,
+    pub soft_drop_gravity: f32,
+    pub das: f32,
+    pub arr: f32,
+    pub lock_delay: f32,
+    pub lock_delay_policy: LockDelayPolicy,
+    pub spawn_delay: f32,
+    /// Frames the cleared rows stay on the board before being removed
+    pub line_clear_delay: f32,
+    pub preview_size: usize,
+    pub randomizer: RandomizerType,
+    pub rotation_system: RotationSystemType,
+    pub board_size: BoardSize,
+    pub garbage: GarbagePattern,
+    pub mode: GameMode,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            gravity: 0.0156,
+            soft_drop_gravity: 0.5,
+            das: 8.,
+            arr: 4.,
+            lock_delay: 30.,
+            lock_delay_policy: LockDelayPolicy::MoveReset,
+            spawn_delay: 10.,
+            line_clear_delay: 0.,
+            preview_size: 6,
+            randomizer: RandomizerType::SevenBag,
+            rotation_system: RotationSystemType::Srs,
+            board_size: BoardSize::default(),
+            garbage: GarbagePattern::Clean,
+            mode: GameMode::Endless,
+        }
+    }
+}
+
+/// Something that happened during a tick, for sounds, effects and statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    PieceSpawned(PieceType),
+    /// The piece moved sideways by `cells`
+    Moved {
+        cells: u32,
+    },
+    /// The piece rotated using the kick at `kick_index` of its kick table
+    Rotated {
+        kick_index: usize,
+    },
+    SoftDropped {
+        rows: u32,
+    },
+    HardDropped {
+        rows: u32,
+    },
+    /// The piece landed, or moved/rotated while resting on the ground
+    TouchedGround,
+    Locked(PieceType),
+    /// The piece locked at `cells` took `inputs` keys where `minimal` would do
+    FinesseFault {
+        piece: PieceType,
+        inputs: u32,
+        minimal: u32,
+        cells: [(isize, isize); 4],
+    },
+    /// Rows were cleared, or a T-spin was scored without clearing any.
+    /// `combo` and `b2b` are the streaks after it, `attack` the lines it
+    /// sent before cancelling pending garbage.
+    LinesCleared {
+        rows: u32,
+        score_type: Option<ScoreType>,
+        combo: u32,
+        b2b: u32,
+        attack: u32,
+    },
+    Hold,
+    HoldFailed,
+    /// Pending garbage rows entered the board
+    GarbageInserted {
+        rows: u32,
+    },
+    /// A clear attacked with more rows than were pending, for opponents to receive
+    GarbageSent {
+        rows: u32,
+    },
+    TopOut,
+    /// Reached a new level in modes with levels
+    LevelUp {
+        level: u32,
+    },
+    /// The goal of the game mode was reached
+    Finished,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveState {
+    Left,
+    Right,
+    No,
+}
+
+/// Headless tetris game: owns the field and runs the whole play loop.
+pub struct Game {
+    pub board: Board,
+    pub piece_bag: PieceBag,
+    pub hold_piece: Option<HoldPiece>,
+    pub curr_piece: Option<PieceWithPosition>,
+    pub config: GameConfig,
+    pub score_updater: ScoreUpdater,
+
+    pub lines: u32,
+    /// Elapsed time in seconds
+    pub time: f32,
+
+    das_timer: FrameTimer,
+    lock_delay: LockDelay,
+    spawn_delay_timer: FrameTimer,
+    line_clear_timer: FrameTimer,
+    /// Completed rows waiting for the line clear delay to end
+    clearing_rows: Vec<(isize, isize)>,
+    garbage: GarbageGenerator,
+    /// Garbage rows waiting for a piece to lock without clearing rows
+    pending_garbage: u32,
+    garbage_inserted: u32,
+    garbage_cleared: u32,
+    /// Attack left after cancelling pending garbage, over the whole game
+    garbage_sent: u32,
+
+    accumulated_down: f32,
+    accumulated_move: f32,
+    move_state: MoveState,
+    finesse: FinesseTracker,
+
+    over: bool,
+    finished: bool,
+    events: Vec<GameEvent>,
+}
+
+impl Game {
+    pub fn new(config: GameConfig, seed: Option<u64>) -> Self {
+        let mut spawn_delay_timer = FrameTimer::new(config.spawn_delay);
+        // the first piece spawns right away
+        spawn_delay_timer.tick(config.spawn_delay);
+
+        let mut piece_bag =
+            PieceBag::with_randomizer(config.preview_size, seed, config.randomizer.build());
+        piece_bag.set_rotation_system(config.rotation_system.system());
+
+        let mut game = Game {
+            board: Board::with_size(config.board_size),
+            piece_bag,
+            hold_piece: None,
+            curr_piece: None,
+            score_updater: ScoreUpdater::default(),
+
+            lines: 0,
+            time: 0.,
+
+            das_timer: FrameTimer::new(config.das),
+            lock_delay: LockDelay::new(config.lock_delay_policy, config.lock_delay),
+            spawn_delay_timer,
+            line_clear_timer: FrameTimer::new(config.line_clear_delay),
+            clearing_rows: Vec::new(),
+            // a stream of its own, so that garbage does not change the pieces
+            garbage: GarbageGenerator::new(
+                config.garbage,
+                config.board_size.cols,
+                seed.map(|seed| !seed),
+            ),
+            pending_garbage: 0,
+            garbage_inserted: 0,
+            garbage_cleared: 0,
+            garbage_sent: 0,
+
+            accumulated_down: 0.,
+            accumulated_move: 0.,
+            move_state: MoveState::No,
+            finesse: FinesseTracker::default(),
+
+            over: false,
+            finished: false,
+            events: Vec::new(),
+            config,
+        };
+        game.refill_cheese();
+
+        game
+    }
+
+    /// Starts from the field of a fumen page, with the page piece
+    /// dealt first. Cells past the board are dropped.
+    pub fn load_page(&mut self, page: &Page) {
+        self.board.clear_all();
+        for i in 0..page.board.rows.min(self.board.rows) {
+            for j in 0..page.board.cols.min(self.board.cols) {
+                self.board.set_val(i, j, page.board.get(i, j));
+            }
+        }
+
+        if let Some(piece) = &page.piece {
+            let piece_type = piece.tetris_piece_ref().piece_type;
+            self.piece_bag.add_top(Piece::with_system(
+                piece_type,
+                self.config.rotation_system.system(),
+            ));
+        }
+    }
+
+    /// Queues `rows` garbage rows, they enter the board once a piece locks
+    /// without clearing rows, the attack of clears cancels them instead.
+    pub fn queue_garbage(&mut self, rows: u32) {
+        self.pending_garbage = self.pending_garbage.saturating_add(rows);
+    }
+
+    pub fn pending_garbage(&self) -> u32 {
+        self.pending_garbage
+    }
+
+    /// Garbage rows sent to opponents so far.
+    pub fn garbage_sent(&self) -> u32 {
+        self.garbage_sent
+    }
+
+    /// Cleared rows that had garbage in them.
+    pub fn garbage_cleared(&self) -> u32 {
+        self.garbage_cleared
+    }
+
+    /// Lines counting towards the goal of the mode.
+    pub fn goal_lines(&self) -> u32 {
+        match self.config.mode {
+            GameMode::CheeseRace { .. } => self.garbage_cleared,
+            _ => self.lines,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// The current level, 1 in modes without levels.
+    pub fn level(&self) -> u32 {
+        self.config.mode.level(self.lines).unwrap_or(1)
+    }
+
+    /// Gravity in rows per frame, following the level in modes with levels.
+    pub fn gravity(&self) -> f32 {
+        if self.config.mode.level(self.lines).is_some() {
+            level_gravity(self.level())
+        } else {
+            self.config.gravity
+        }
+    }
+
+    /// Whether the game ended by reaching the goal of its mode.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Rows cleared by the last piece, still shown during the line clear delay.
+    pub fn clearing_rows(&self) -> impl Iterator<Item = isize> + '_ {
+        self.clearing_rows
+            .iter()
+            .flat_map(|&(from, to)| to + 1..=from)
+    }
+
+    /// Elapsed fraction of the line clear delay.
+    pub fn line_clear_progress(&self) -> f32 {
+        self.line_clear_timer.progress()
+    }
+
+    /// Returns the events emitted since the last call.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = GameEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Advances the game by `frame` frames with the given input.
+    pub fn tick(&mut self, frame: f32, input: &Input) {
+        if self.over {
+            return;
+        }
+
+        self.time += frame / FRAMES_PER_SECOND;
+        self.finesse.handle_input(input);
+
+        if !self.clearing_rows.is_empty() {
+            self.line_clear_timer.tick(frame);
+            if self.line_clear_timer.done() {
+                self.board.remove_ranges(mem::take(&mut self.clearing_rows));
+                self.refill_cheese();
+            }
+        }
+
+        if self.curr_piece.is_none() {
+            // the spawn delay starts once the cleared rows are gone
+            if self.clearing_rows.is_empty() {
+                self.spawn_delay_timer.tick(frame);
+            }
+
+            if self.clearing_rows.is_empty() && self.spawn_delay_timer.done() {
+                if let Some(p) = self.spawn_piece() {
+                    self.lock_delay.start(p.row());
+                    self.curr_piece = Some(p);
+                } else {
+                    self.top_out();
+                }
+                self.spawn_delay_timer.reset();
+            } else {
+                self.handle_irs(input);
+            }
+        }
+
+        self.handle_move(frame, input);
+        self.handle_hold(input);
+        self.handle_rotate(input);
+        self.handle_gravity(frame, input);
+        self.handle_freeze(frame);
+    }
+
+    fn handle_gravity(&mut self, frame: f32, input: &Input) {
+        let gravity = if input.is_down(Action::SoftDrop) {
+            self.config.soft_drop_gravity.max(self.gravity())
+        } else {
+            self.gravity()
+        };
+        let Some(piece) = &mut self.curr_piece else { return };
+
+        let touch_ground_before = piece.collides_down(&self.board);
+        let mut rows = 0;
+        self.accumulated_down += gravity * frame;
+        if self.accumulated_down >= 1.0 {
+            let step = self.accumulated_down.floor() as usize;
+            self.accumulated_down -= step as f32;
+            for _ in 0..step {
+                if piece.collides_down(&self.board) {
+                    break;
+                } else {
+                    piece.move_down();
+                    rows += 1;
+                }
+            }
+        }
+
+        if rows > 0 {
+            self.lock_delay.fell_to(piece.row());
+            if input.is_down(Action::SoftDrop) {
+                self.score_updater.set_last_move(Moves::Down);
+                self.score_updater.dropped(rows, false);
+                self.events.push(GameEvent::SoftDropped { rows });
+            } else {
+                self.score_updater.set_last_move(Moves::Fall);
+            }
+        }
+
+        if !touch_ground_before && piece.collides_down(&self.board) {
+            self.events.push(GameEvent::TouchedGround);
+        }
+    }
+
+    fn handle_freeze(&mut self, frame: f32) {
+        let Some(piece) = &mut self.curr_piece else { return };
+        if self
+            .lock_delay
+            .tick(frame, piece.collides_down(&self.board))
+        {
+            self.freeze_piece();
+        }
+    }
+
+    fn handle_move(&mut self, frame: f32, input: &Input) {
+        if input.is_pressed(Action::HardDrop) {
+            self.hard_drop();
+            return;
+        }
+
+        let first_move = if input.is_pressed(Action::Left) {
+            self.change_move_state(MoveState::Left);
+            true
+        } else if input.is_pressed(Action::Right) {
+            self.change_move_state(MoveState::Right);
+            true
+        } else {
+            false
+        };
+
+        if first_move {
+            self.move_step(1);
+        }
+
+        match self.move_state {
+            MoveState::Left if input.is_down(Action::Left) => self.handle_das(frame),
+            MoveState::Right if input.is_down(Action::Right) => self.handle_das(frame),
+            _ => {
+                self.change_move_state(MoveState::No);
+            }
+        }
+    }
+
+    fn handle_hold(&mut self, input: &Input) {
+        if input.is_pressed(Action::Hold) {
+            if self.hold_piece.can_swap() {
+                // hold success
+                let Some(piece) = self.curr_piece.take() else { return };
+
+                self.curr_piece = if let Some(p) = self.hold_piece.take() {
+                    self.init_piece(p.piece)
+                } else {
+                    self.spawn_piece()
+                };
+
+                if let Some(p) = &self.curr_piece {
+                    self.lock_delay.start(p.row());
+                }
+
+                let mut hp = HoldPiece::new(piece.tetris_piece());
+                hp.set_hold();
+                self.hold_piece = Some(hp);
+                self.finesse.reset();
+                self.events.push(GameEvent::Hold);
+            } else {
+                // hold failed
+                self.events.push(GameEvent::HoldFailed);
+            }
+        }
+    }
+
+    fn handle_rotate(&mut self, input: &Input) {
+        let Some(piece) = &mut self.curr_piece else { return };
+
+        let touch_ground_before = piece.collides_down(&self.board);
+
+        let half_turn = input.is_pressed(Action::Rotate180);
+        let kick_index = if input.is_pressed(Action::RotateCw) {
+            piece.try_rotate_kick(&self.board)
+        } else if input.is_pressed(Action::RotateCcw) {
+            piece.try_rotate_prev_kick(&self.board)
+        } else if half_turn {
+            piece.try_rotate_180_kick(&self.board)
+        } else {
+            None
+        };
+
+        if let Some(kick_index) = kick_index {
+            self.score_updater.set_last_rotation(kick_index, half_turn);
+            self.events.push(GameEvent::Rotated { kick_index });
+            self.lock_delay
+                .moved(touch_ground_before || piece.collides_down(&self.board));
+
+            if touch_ground_before {
+                self.events.push(GameEvent::TouchedGround);
+            }
+        }
+    }
+
+    /// handle rotation input during spawn delay
+    fn handle_irs(&mut self, input: &Input) {
+        let next_piece = self.piece_bag.next_piece_ref_mut();
+        if input.is_pressed(Action::RotateCw) {
+            next_piece.rotate_piece()
+        } else if input.is_pressed(Action::RotateCcw) {
+            next_piece.rotate_piece_prev()
+        } else if input.is_pressed(Action::Rotate180) {
+            next_piece.rotate_piece_180()
+        };
+    }
+
+    fn hard_drop(&mut self) {
+        let Some(piece) = &mut self.curr_piece else { return };
+
+        let mut rows = 0;
+        while !piece.collides_down(&self.board) {
+            piece.move_down();
+            rows += 1;
+        }
+
+        if rows > 0 {
+            self.score_updater.set_last_move(Moves::Down);
+            self.score_updater.dropped(rows, true);
+        }
+
+        self.events.push(GameEvent::HardDropped { rows });
+        self.freeze_piece();
+        self.curr_piece = None;
+
+        self.events.push(GameEvent::TouchedGround);
+    }
+
+    fn change_move_state(&mut self, state: MoveState) {
+        self.move_state = state;
+        self.das_timer.reset();
+        self.accumulated_move = 0.;
+    }
+
+    fn handle_das(&mut self, frame: f32) {
+        if self.das_timer.done() {
+            self.accumulated_move += frame / self.config.arr.max(0.000001);
+            let step = self.accumulated_move.floor() as usize;
+            self.accumulated_move = self.accumulated_move.fract();
+            self.move_step(step);
+        } else {
+            self.das_timer.tick(frame);
+        }
+    }
+
+    fn move_step(&mut self, mut step: usize) {
+        let Some(piece) = &mut self.curr_piece else { return };
+        let mut cells = 0;
+        let touch_ground_before = piece.collides_down(&self.board);
+
+        match self.move_state {
+            MoveState::Left => {
+                while step != 0 && !piece.collides_left(&self.board) {
+                    step -= 1;
+                    piece.move_left();
+                    cells += 1;
+                }
+            }
+            MoveState::Right => {
+                while step != 0 && !piece.collides_right(&self.board) {
+                    step -= 1;
+                    piece.move_right();
+                    cells += 1;
+                }
+            }
+            _ => {}
+        }
+
+        let touch_ground_after = piece.collides_down(&self.board);
+
+        if cells > 0 {
+            self.score_updater.set_last_move(Moves::Side);
+            self.events.push(GameEvent::Moved { cells });
+            self.lock_delay
+                .moved(touch_ground_before || touch_ground_after);
+
+            if touch_ground_before && touch_ground_after {
+                self.events.push(GameEvent::TouchedGround);
+            }
+        }
+    }
+
+    fn handle_clear(&mut self, piece: &PieceWithPosition) {
+        let completed_rows = self.board.completed_rows();
+        let lines = completed_rows
+            .iter()
+            .map(|(from, to)| (from - to) as u32)
+            .sum();
+
+        let level = self.level();
+
+        self.score_updater.set_level(level);
+        self.score_updater.finalized_piece(piece, &self.board);
+
+        self.lines += lines;
+        self.garbage_cleared += completed_rows
+            .iter()
+            .flat_map(|&(from, to)| to + 1..=from)
+            .filter(|&row| self.board.has_garbage(row))
+            .count() as u32;
+
+        let score_type = self.score_updater.get_last_score();
+        let attack = score_type.filter(|_| lines > 0).map(|score_type| {
+            attack::lines_sent(
+                score_type,
+                self.score_updater.get_combo(),
+                self.score_updater.get_b2b(),
+            )
+        });
+        if lines > 0 || score_type.is_some() {
+            self.events.push(GameEvent::LinesCleared {
+                rows: lines,
+                score_type,
+                combo: self.score_updater.get_combo().value().unwrap_or(0),
+                b2b: self.score_updater.get_b2b(),
+                attack: attack.unwrap_or(0),
+            });
+        }
+
+        if self.level() > level {
+            self.events.push(GameEvent::LevelUp {
+                level: self.level(),
+            });
+        }
+
+        if self.config.mode.is_complete(self.goal_lines()) {
+            self.finish();
+        }
+
+        if lines > 0 && self.config.line_clear_delay > 0. && !self.over {
+            self.clearing_rows = completed_rows;
+            self.line_clear_timer.reset();
+        } else {
+            self.board.remove_ranges(completed_rows);
+            self.refill_cheese();
+        }
+
+        if let Some(attack) = attack {
+            self.send_attack(attack);
+        } else if lines == 0 && self.pending_garbage > 0 && !self.over {
+            self.insert_garbage();
+        }
+    }
+
+    /// Cancels pending garbage with the attack of a clear, sending the rest.
+    fn send_attack(&mut self, attack: u32) {
+        let cancelled = attack.min(self.pending_garbage);
+        self.pending_garbage -= cancelled;
+
+        let rows = attack - cancelled;
+        if rows > 0 {
+            self.garbage_sent += rows;
+            self.events.push(GameEvent::GarbageSent { rows });
+        }
+    }
+
+    fn insert_garbage(&mut self) {
+        let rows = mem::take(&mut self.pending_garbage);
+        self.push_garbage(rows);
+    }
+
+    /// Tops the cheese race board back up to `CHEESE_RACE_ROWS` garbage rows.
+    fn refill_cheese(&mut self) {
+        let GameMode::CheeseRace { lines } = self.config.mode else { return };
+
+        let on_board = self.garbage_inserted - self.garbage_cleared;
+        let wanted = CHEESE_RACE_ROWS.min(lines - self.garbage_cleared.min(lines));
+        if wanted > on_board {
+            self.push_garbage(wanted - on_board);
+        }
+    }
+
+    fn push_garbage(&mut self, rows: u32) {
+        let holes = self.garbage.holes(rows);
+        self.garbage_inserted += rows;
+
+        let mut overflow = false;
+        for run in holes.chunk_by(|a, b| a == b) {
+            overflow |= self.board.insert_garbage(run.len(), run[0]);
+        }
+
+        self.events.push(GameEvent::GarbageInserted { rows });
+        if overflow {
+            self.top_out();
+        }
+    }
+
+    fn spawn_piece(&mut self) -> Option<PieceWithPosition> {
+        if let Some(hp) = &mut self.hold_piece {
+            hp.reset_hold();
+        }
+        let p = self.piece_bag.next_piece();
+        debug!("Spawned piece: {:?}", &p.piece_type);
+        self.score_updater.set_last_move(Moves::Fall);
+
+        self.events.push(GameEvent::PieceSpawned(p.piece_type));
+        self.init_piece(p)
+    }
+
+    fn init_piece(&self, p: Piece) -> Option<PieceWithPosition> {
+        let p = PieceWithPosition::spawn(p, self.config.board_size);
+        if p.collides(&self.board) {
+            None
+        } else {
+            Some(p)
+        }
+    }
+
+    fn freeze_piece(&mut self) {
+        let piece = self.curr_piece.take().unwrap();
+        piece.finalize_on(&mut self.board);
+
+        self.events
+            .push(GameEvent::Locked(piece.tetris_piece_ref().piece_type));
+        if let Some(fault) = self.finesse.locked(&piece, self.config.board_size) {
+            let mut cells = [(0, 0); 4];
+            for (cell, (i, j)) in cells.iter_mut().zip(piece.tetris_piece_ref().set_cells()) {
+                *cell = (i + piece.row(), j + piece.col());
+            }
+            self.events.push(GameEvent::FinesseFault {
+                piece: piece.tetris_piece_ref().piece_type,
+                inputs: fault.inputs,
+                minimal: fault.minimal,
+                cells,
+            });
+        }
+        self.handle_clear(&piece);
+    }
+
+    fn top_out(&mut self) {
+        self.over = true;
+        self.events.push(GameEvent::TopOut);
+    }
+
+    fn finish(&mut self) {
+        self.over = true;
+        self.finished = true;
+        self.events.push(GameEvent::Finished);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::{PieceTypeColor, Rotation},
+        test_utils::load_board,
+    };
+    use test_case::test_case;
+
+    fn get_game() -> Game {
+        Game::new(GameConfig::default(), Some(0xDEADBEEF))
+    }
+
+    fn idle() -> Input {
+        Input::default()
+    }
+
+    #[test]
+    fn test_first_piece_spawns_immediately() {
+        let mut game = get_game();
+        game.tick(1., &idle());
+
+        let piece = game.curr_piece.as_ref().unwrap();
+        assert_eq!(piece.tetris_piece_ref().piece_type, PieceType::L);
+        assert_eq!((piece.row(), piece.col()), (19, 3));
+        assert_eq!(
+            game.drain_events().collect::<Vec<_>>(),
+            vec![GameEvent::PieceSpawned(PieceType::L)]
+        );
+    }
+
+    #[test]
+    fn test_rotation_system_shapes_and_spawn() {
+        let mut game = Game::new(
+            GameConfig {
+                rotation_system: RotationSystemType::Ars,
+                ..Default::default()
+            },
+            Some(0xDEADBEEF),
+        );
+        game.tick(1., &idle());
+
+        // ARS pieces rest on the bottom of their box and spawn one row higher
+        let piece = game.curr_piece.as_ref().unwrap();
+        assert_eq!((piece.row(), piece.col()), (20, 3));
+        assert!(game
+            .piece_bag
+            .iter_next_pieces()
+            .filter(|p| p.piece_type != PieceType::I)
+            .all(|p| p.set_cells().all(|(i, _)| i < 2)));
+
+        game.tick(1., &idle().press(Action::HardDrop));
+        let events: Vec<_> = game.drain_events().collect();
+        assert!(events.contains(&GameEvent::HardDropped { rows: 20 }));
+    }
+
+    #[test]
+    fn test_board_size() {
+        let mut game = Game::new(
+            GameConfig {
+                board_size: BoardSize {
+                    cols: 4,
+                    visible_rows: 12,
+                    buffer_rows: 4,
+                },
+                ..Default::default()
+            },
+            Some(0xDEADBEEF),
+        );
+        game.tick(1., &idle());
+
+        assert_eq!((game.board.rows, game.board.cols), (16, 4));
+        let piece = game.curr_piece.as_ref().unwrap();
+        assert_eq!((piece.row(), piece.col()), (11, 0));
+
+        game.tick(1., &idle().press(Action::HardDrop));
+        let events: Vec<_> = game.drain_events().collect();
+        assert!(events.contains(&GameEvent::HardDropped { rows: 12 }));
+    }
+
+    #[test]
+    fn test_garbage_enters_on_lock() {
+        let mut game = get_game();
+        game.queue_garbage(3);
+        game.tick(1., &idle());
+        game.tick(1., &idle().press(Action::HardDrop));
+
+        assert_eq!(game.pending_garbage(), 0);
+        assert!(game
+            .drain_events()
+            .any(|e| e == GameEvent::GarbageInserted { rows: 3 }));
+        for row in 0..3 {
+            assert_eq!(
+                (0..10).filter(|&col| game.board.is_set(row, col)).count(),
+                9
+            );
+        }
+    }
+
+    #[test]
+    fn test_queued_garbage_saturates() {
+        let mut game = get_game();
+        game.queue_garbage(u32::MAX);
+        game.queue_garbage(u32::MAX);
+
+        assert_eq!(game.pending_garbage(), u32::MAX);
+    }
+
+    /// Clears a tetris below a single block, so that it is no all clear.
+    fn clear_tetris(game: &mut Game) {
+        load_board(&mut game.board, &("********* ".repeat(4) + "*         "));
+        game.piece_bag
+            .add_top(Piece::with_rotation(PieceType::I, Rotation::R90));
+        game.tick(1., &idle());
+
+        for _ in 0..10 {
+            game.tick(1., &idle().press(Action::Right));
+        }
+        game.tick(1., &idle().press(Action::HardDrop));
+    }
+
+    #[test_case(5, 1, 0)]
+    #[test_case(2, 0, 2)]
+    #[test_case(0, 0, 4)]
+    fn test_attack_cancels_garbage(queued: u32, pending: u32, sent: u32) {
+        let mut game = get_game();
+        game.queue_garbage(queued);
+        clear_tetris(&mut game);
+
+        assert_eq!(game.pending_garbage(), pending);
+        assert_eq!(game.garbage_sent(), sent);
+        assert_eq!(
+            game.drain_events()
+                .any(|e| e == GameEvent::GarbageSent { rows: sent }),
+            sent > 0
+        );
+        assert!(game.board.is_set(0, 0));
+        assert!(!game.board.is_set(1, 0));
+    }
+
+    #[test]
+    fn test_all_clear_cancels_garbage() {
+        let mut game = get_game();
+        load_board(&mut game.board, &"********* ".repeat(4));
+        game.queue_garbage(5);
+        game.piece_bag
+            .add_top(Piece::with_rotation(PieceType::I, Rotation::R90));
+        game.tick(1., &idle());
+
+        for _ in 0..10 {
+            game.tick(1., &idle().press(Action::Right));
+        }
+        game.tick(1., &idle().press(Action::HardDrop));
+
+        assert_eq!(game.pending_garbage(), 0);
+        assert_eq!(game.garbage_sent(), 5);
+        assert!(game.board.is_empty());
+    }
+
+    fn cheese_race(lines: u32) -> Game {
+        Game::new(
+            GameConfig {
+                garbage: GarbagePattern::Messy,
+                mode: GameMode::CheeseRace { lines },
+                ..Default::default()
+            },
+            Some(0xDEADBEEF),
+        )
+    }
+
+    fn garbage_rows(game: &Game) -> usize {
+        (0..game.board.rows)
+            .filter(|&row| game.board.has_garbage(row))
+            .count()
+    }
+
+    #[test_case(5, 5; "fewer than the rows kept")]
+    #[test_case(100, 10; "more than the rows kept")]
+    fn test_cheese_race_starts_with_garbage(lines: u32, rows: usize) {
+        let game = cheese_race(lines);
+
+        assert_eq!(garbage_rows(&game), rows);
+        assert!((0..rows as isize).all(|row| !game.board.is_complete(row)));
+    }
+
+    #[test]
+    fn test_cheese_race_refills_and_finishes() {
+        let mut game = cheese_race(12);
+
+        // clear the bottom row by filling its hole
+        for cleared in 1..=12 {
+            let hole = (0..10).find(|&col| !game.board.is_set(0, col)).unwrap();
+            game.board
+                .set(0, hole, PieceTypeColor::Playable(PieceType::T));
+            let piece = PieceWithPosition::new(30, 0, Piece::new(PieceType::O));
+            game.handle_clear(&piece);
+
+            assert_eq!(game.garbage_cleared(), cleared);
+            assert_eq!(garbage_rows(&game), 10.min(12 - cleared) as usize);
+        }
+
+        assert!(game.is_finished());
+    }
+
+    #[test]
+    fn test_load_page() {
+        let mut game = get_game();
+        let mut page = Page::new(Board::new(23, 10));
+        load_board(&mut page.board, "****  ****");
+        page.piece = Some(PieceWithPosition::new(0, 4, Piece::new(PieceType::O)));
+
+        game.load_page(&page);
+        game.tick(1., &idle());
+
+        assert!(game.board.is_set(0, 0));
+        assert!(!game.board.is_set(0, 4));
+        let piece = game.curr_piece.as_ref().unwrap();
+        assert_eq!(piece.tetris_piece_ref().piece_type, PieceType::O);
+    }
+
+    #[test]
+    fn test_hard_drop_locks_and_respawns_after_delay() {
+        let mut game = get_game();
+        game.tick(1., &idle());
+        game.tick(1., &idle().press(Action::HardDrop));
+
+        assert!(game.curr_piece.is_none());
+        assert!(!game.board.is_empty());
+        let events: Vec<_> = game.drain_events().collect();
+        assert!(events.contains(&GameEvent::HardDropped { rows: 20 }));
+        assert!(events.contains(&GameEvent::Locked(PieceType::L)));
+        assert_eq!(game.score_updater.get_score(), 40);
+
+        for _ in 0..9 {
+            game.tick(1., &idle());
+            assert!(game.curr_piece.is_none());
+        }
+        game.tick(1., &idle());
+        assert!(game.curr_piece.is_some());
+    }
+
+    #[test]
+    fn test_das_and_arr() {
+        let mut game = get_game();
+        game.tick(1., &idle());
+        let start = game.curr_piece.as_ref().unwrap().col();
+
+        game.tick(1., &idle().press(Action::Left));
+        assert_eq!(game.curr_piece.as_ref().unwrap().col(), start - 1);
+
+        // still charging DAS
+        for _ in 0..8 {
+            game.tick(1., &idle().hold(Action::Left));
+        }
+        assert_eq!(game.curr_piece.as_ref().unwrap().col(), start - 1);
+
+        // auto repeat every 4 frames
+        for _ in 0..4 {
+            game.tick(1., &idle().hold(Action::Left));
+        }
+        assert_eq!(game.curr_piece.as_ref().unwrap().col(), start - 2);
+
+        // releasing stops the movement
+        for _ in 0..20 {
+            game.tick(1., &idle());
+        }
+        assert_eq!(game.curr_piece.as_ref().unwrap().col(), start - 2);
+    }
+
+    #[test]
+    fn test_move_and_rotate_events() {
+        let mut game = get_game();
+        game.tick(1., &idle());
+        game.drain_events().for_each(drop);
+
+        game.tick(1., &idle().press(Action::Right));
+        game.tick(1., &idle().press(Action::RotateCw));
+        // half a row per frame
+        game.tick(1., &idle().hold(Action::SoftDrop));
+        game.tick(1., &idle().hold(Action::SoftDrop));
+
+        assert_eq!(
+            game.drain_events().collect::<Vec<_>>(),
+            vec![
+                GameEvent::Moved { cells: 1 },
+                GameEvent::Rotated { kick_index: 0 },
+                GameEvent::SoftDropped { rows: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hold() {
+        let mut game = get_game();
+        game.tick(1., &idle());
+        game.tick(1., &idle().press(Action::Hold));
+
+        assert_eq!(
+            game.hold_piece.as_ref().unwrap().piece.piece_type,
+            PieceType::L
+        );
+        assert_eq!(
+            game.curr_piece
+                .as_ref()
+                .unwrap()
+                .tetris_piece_ref()
+                .piece_type,
+            PieceType::O
+        );
+
+        game.drain_events().for_each(drop);
+        game.tick(1., &idle().press(Action::Hold));
+        assert_eq!(
+            game.drain_events().collect::<Vec<_>>(),
+            vec![GameEvent::HoldFailed]
+        );
+    }
+
+    #[test]
+    fn test_finesse_fault() {
+        let mut game = get_game();
+        game.tick(1., &idle());
+
+        // turning the L back and forth before dropping it in place
+        game.tick(1., &idle().press(Action::RotateCw));
+        game.tick(1., &idle().press(Action::RotateCcw));
+        game.tick(1., &idle().press(Action::HardDrop));
+        let faults: Vec<_> = game
+            .drain_events()
+            .filter(|event| matches!(event, GameEvent::FinesseFault { .. }))
+            .collect();
+        assert_eq!(
+            faults,
+            vec![GameEvent::FinesseFault {
+                piece: PieceType::L,
+                inputs: 2,
+                minimal: 0,
+                cells: [(0, 3), (0, 4), (0, 5), (1, 5)],
+            }]
+        );
+
+        // a hold starts the count over
+        game.tick(1., &idle());
+        game.tick(1., &idle().press(Action::Left));
+        game.tick(1., &idle().press(Action::Hold));
+        game.tick(1., &idle().press(Action::HardDrop));
+        assert!(!game
+            .drain_events()
+            .any(|event| matches!(event, GameEvent::FinesseFault { .. })));
+    }
+
+    #[test]
+    fn test_line_clear() {
+        let mut game = get_game();
+        // leave a well for the vertical I piece in the last column
+        load_board(&mut game.board, &"********* ".repeat(4));
+        game.piece_bag
+            .add_top(Piece::with_rotation(PieceType::I, Rotation::R90));
+        game.tick(1., &idle());
+
+        for _ in 0..10 {
+            game.tick(1., &idle().press(Action::Right));
+        }
+        game.tick(1., &idle().press(Action::HardDrop));
+
+        assert_eq!(game.lines, 4);
+        assert!(game.board.is_empty());
+        assert!(game.drain_events().any(|e| e
+            == GameEvent::LinesCleared {
+                rows: 4,
+                score_type: Some(ScoreType::AllClear),
+                combo: 0,
+                b2b: 0,
+                attack: 10,
+            }));
+    }
+
+    #[test]
+    fn test_sprint_finishes_at_goal() {
+        let mut game = Game::new(
+            GameConfig {
+                mode: GameMode::Sprint { lines: 4 },
+                ..Default::default()
+            },
+            Some(0xDEADBEEF),
+        );
+        load_board(&mut game.board, &"********* ".repeat(4));
+        game.piece_bag
+            .add_top(Piece::with_rotation(PieceType::I, Rotation::R90));
+        game.tick(1., &idle());
+
+        for _ in 0..10 {
+            game.tick(1., &idle().press(Action::Right));
+        }
+        game.tick(1., &idle().press(Action::HardDrop));
+
+        assert!(game.is_over());
+        assert!(game.is_finished());
+        assert!(game.drain_events().any(|e| e == GameEvent::Finished));
+
+        // time stops with the game
+        let time = game.time;
+        game.tick(1., &idle());
+        assert_eq!(game.time, time);
+        assert!(game.curr_piece.is_none());
+    }
+
+    #[test]
+    fn test_marathon_level_up() {
+        let mut game = Game::new(
+            GameConfig {
+                mode: GameMode::Marathon { endless: false },
+                ..Default::default()
+            },
+            Some(0xDEADBEEF),
+        );
+        game.lines = 18;
+        assert_eq!(game.level(), 2);
+        assert_eq!(game.gravity(), level_gravity(2));
+
+        load_board(&mut game.board, &"********* ".repeat(4));
+        game.piece_bag
+            .add_top(Piece::with_rotation(PieceType::I, Rotation::R90));
+        game.tick(1., &idle());
+
+        for _ in 0..10 {
+            game.tick(1., &idle().press(Action::Right));
+        }
+        game.tick(1., &idle().press(Action::HardDrop));
+
+        assert_eq!(game.level(), 3);
+        assert_eq!(game.gravity(), level_gravity(3));
+        assert!(game
+            .drain_events()
+            .any(|e| e == GameEvent::LevelUp { level: 3 }));
+        // hard drop points, then the all clear tetris scored at level 2
+        assert_eq!(game.score_updater.get_score(), 2 * 18 + (800 + 2000) * 2);
+    }
+
+    #[test]
+    fn test_lock_delay_move_reset() {
+        let mut game = get_game();
+        game.tick(1., &idle().press(Action::SoftDrop));
+        while !game.curr_piece.as_ref().unwrap().collides_down(&game.board) {
+            game.tick(1., &idle().hold(Action::SoftDrop));
+        }
+
+        // the landing tick already counts
+        game.tick(28., &idle());
+        assert!(game.curr_piece.is_some());
+
+        // sliding on the ground gives the full delay again
+        game.tick(1., &idle().press(Action::Left));
+        game.tick(28., &idle());
+        assert!(game.curr_piece.is_some());
+
+        game.tick(1., &idle());
+        assert!(game.curr_piece.is_none());
+        assert!(game
+            .drain_events()
+            .any(|e| e == GameEvent::Locked(PieceType::L)));
+    }
+
+    #[test]
+    fn test_line_clear_delay() {
+        let mut game = Game::new(
+            GameConfig {
+                line_clear_delay: 20.,
+                ..Default::default()
+            },
+            Some(0xDEADBEEF),
+        );
+        load_board(&mut game.board, &"********* ".repeat(2));
+        game.piece_bag
+            .add_top(Piece::with_rotation(PieceType::I, Rotation::R90));
+        game.tick(1., &idle());
+
+        for _ in 0..10 {
+            game.tick(1., &idle().press(Action::Right));
+        }
+        game.tick(1., &idle().press(Action::HardDrop));
+
+        // scored right away, removed after the delay
+        assert_eq!(game.lines, 2);
+        assert_eq!(game.clearing_rows().collect::<Vec<_>>(), vec![0, 1]);
+        assert!(game.board.is_complete(0));
+
+        game.tick(19., &idle());
+        assert!(game.board.is_complete(0));
+        assert!(game.curr_piece.is_none());
+
+        game.tick(1., &idle());
+        assert_eq!(game.clearing_rows().count(), 0);
+        assert!(!game.board.is_complete(0));
+        assert!(game.curr_piece.is_none());
+
+        // then the regular spawn delay
+        game.tick(10., &idle());
+        assert!(game.curr_piece.is_some());
+    }
+
+    #[test]
+    fn test_top_out() {
+        let mut game = get_game();
+        load_board(&mut game.board, &"*".repeat(300));
+        game.tick(1., &idle());
+
+        assert!(game.is_over());
+        assert!(!game.is_finished());
+        assert!(game.drain_events().any(|e| e == GameEvent::TopOut));
+    }
+}
@@ -0,0 +1,233 @@
+//! Gamepad buttons and left analog stick, read through a backend so the
+//! bindings work the same with a real device or a scripted one.
+use crate::input::{Action, Input};
+
+/// A gamepad button, or a direction of the left stick past the deadzone.
+///
+/// Face buttons are named after their position, as labels differ between vendors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    StickUp,
+    StickDown,
+    StickLeft,
+    StickRight,
+}
+
+impl Button {
+    pub const ALL: [Button; 18] = [
+        Button::South,
+        Button::East,
+        Button::West,
+        Button::North,
+        Button::LeftBumper,
+        Button::RightBumper,
+        Button::LeftTrigger,
+        Button::RightTrigger,
+        Button::Select,
+        Button::Start,
+        Button::DPadUp,
+        Button::DPadDown,
+        Button::DPadLeft,
+        Button::DPadRight,
+        Button::StickUp,
+        Button::StickDown,
+        Button::StickLeft,
+        Button::StickRight,
+    ];
+
+    fn mask(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// State of a gamepad at one frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PadReading {
+    /// Buttons held, stick directions excluded
+    pub buttons: Vec<Button>,
+    /// Left stick position within [-1, 1], up and right positive
+    pub stick: (f32, f32),
+}
+
+/// Source of the gamepad state, a device library or a script in tests.
+pub trait GamepadBackend {
+    /// The state of the gamepad, none if no gamepad is connected.
+    fn read(&mut self) -> Option<PadReading>;
+}
+
+/// Buttons held on the current and the previous frame, to tell presses from holds.
+pub struct Gamepad {
+    backend: Box<dyn GamepadBackend>,
+    /// Stick deflection below which it counts as centered
+    pub deadzone: f32,
+    down: u32,
+    prev_down: u32,
+}
+
+impl Gamepad {
+    pub fn new(backend: Box<dyn GamepadBackend>, deadzone: f32) -> Self {
+        Self {
+            backend,
+            deadzone,
+            down: 0,
+            prev_down: 0,
+        }
+    }
+
+    /// Reads the backend, once per frame.
+    pub fn update(&mut self) {
+        self.prev_down = self.down;
+        self.down = 0;
+
+        let Some(reading) = self.backend.read() else { return };
+        let (x, y) = reading.stick;
+        for (button, deflected) in [
+            (Button::StickUp, y > self.deadzone),
+            (Button::StickDown, y < -self.deadzone),
+            (Button::StickLeft, x < -self.deadzone),
+            (Button::StickRight, x > self.deadzone),
+        ] {
+            if deflected {
+                self.down |= button.mask();
+            }
+        }
+        for button in reading.buttons {
+            self.down |= button.mask();
+        }
+    }
+
+    pub fn is_down(&self, button: Button) -> bool {
+        self.down & button.mask() != 0
+    }
+
+    /// The button went down on this frame.
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.is_down(button) && self.prev_down & button.mask() == 0
+    }
+
+    /// A button pressed on this frame, to bind it.
+    pub fn last_pressed(&self) -> Option<Button> {
+        Button::ALL.into_iter().find(|b| self.is_pressed(*b))
+    }
+
+    /// Samples the buttons bound to gameplay actions into an input snapshot.
+    pub fn input(&self, bindings: impl IntoIterator<Item = (Action, Option<Button>)>) -> Input {
+        let mut input = Input::default();
+        for (action, button) in bindings {
+            let Some(button) = button else { continue };
+            input.set(action, self.is_pressed(button), self.is_down(button));
+        }
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use test_case::test_case;
+
+    /// Plays back a reading per frame, then disconnects.
+    struct Script(VecDeque<Option<PadReading>>);
+
+    impl GamepadBackend for Script {
+        fn read(&mut self) -> Option<PadReading> {
+            self.0.pop_front().flatten()
+        }
+    }
+
+    fn gamepad(readings: Vec<Option<PadReading>>) -> Gamepad {
+        Gamepad::new(Box::new(Script(readings.into())), 0.3)
+    }
+
+    fn buttons(buttons: &[Button]) -> Option<PadReading> {
+        Some(PadReading {
+            buttons: buttons.to_vec(),
+            stick: (0., 0.),
+        })
+    }
+
+    fn stick(x: f32, y: f32) -> Option<PadReading> {
+        Some(PadReading {
+            buttons: vec![],
+            stick: (x, y),
+        })
+    }
+
+    #[test]
+    fn test_press_then_hold() {
+        let mut pad = gamepad(vec![
+            buttons(&[Button::South]),
+            buttons(&[Button::South, Button::East]),
+            None,
+        ]);
+
+        pad.update();
+        assert!(pad.is_pressed(Button::South));
+        assert_eq!(pad.last_pressed(), Some(Button::South));
+
+        pad.update();
+        assert!(!pad.is_pressed(Button::South));
+        assert!(pad.is_down(Button::South));
+        assert_eq!(pad.last_pressed(), Some(Button::East));
+
+        // a disconnected gamepad holds nothing
+        pad.update();
+        assert!(!pad.is_down(Button::South));
+        assert_eq!(pad.last_pressed(), None);
+    }
+
+    #[test_case(0.2, 0., None; "within the deadzone")]
+    #[test_case(-0.5, 0., Some(Button::StickLeft); "left")]
+    #[test_case(0.5, 0., Some(Button::StickRight); "right")]
+    #[test_case(0., 0.9, Some(Button::StickUp); "up")]
+    #[test_case(0., -0.9, Some(Button::StickDown); "down")]
+    fn test_stick_deadzone(x: f32, y: f32, expected: Option<Button>) {
+        let mut pad = gamepad(vec![stick(x, y)]);
+        pad.update();
+
+        assert_eq!(pad.last_pressed(), expected);
+    }
+
+    #[test]
+    fn test_input() {
+        let mut pad = gamepad(vec![
+            stick(-1., 0.),
+            Some(PadReading {
+                buttons: vec![Button::RightBumper],
+                stick: (-1., 0.),
+            }),
+        ]);
+        let bindings = [
+            (Action::Left, Some(Button::StickLeft)),
+            (Action::Hold, Some(Button::RightBumper)),
+            (Action::HardDrop, None),
+        ];
+
+        pad.update();
+        let input = pad.input(bindings);
+        assert!(input.is_pressed(Action::Left));
+        assert!(!input.is_down(Action::Hold));
+
+        pad.update();
+        let input = pad.input(bindings);
+        assert!(!input.is_pressed(Action::Left));
+        assert!(input.is_down(Action::Left));
+        assert!(input.is_pressed(Action::Hold));
+        assert!(!input.is_down(Action::HardDrop));
+    }
+}
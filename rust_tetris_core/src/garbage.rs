@@ -0,0 +1,115 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Where the holes of consecutive garbage rows are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GarbagePattern {
+    /// Every row has its hole in the same column
+    #[default]
+    Clean,
+    /// Every row has its hole in a random column
+    Messy,
+    /// Each row moves its hole to another column with the given
+    /// percent chance
+    Cheese(u32),
+}
+
+/// Picks the hole columns of the garbage rows entering a board.
+#[derive(Clone, Debug)]
+pub struct GarbageGenerator {
+    pattern: GarbagePattern,
+    cols: isize,
+    hole: isize,
+    rng: StdRng,
+}
+
+impl GarbageGenerator {
+    pub fn new(pattern: GarbagePattern, cols: isize, seed: Option<u64>) -> Self {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        let hole = rng.gen_range(0..cols.max(1));
+
+        Self {
+            pattern,
+            cols,
+            hole,
+            rng,
+        }
+    }
+
+    pub fn pattern(&self) -> GarbagePattern {
+        self.pattern
+    }
+
+    /// Hole columns of the next `rows` rows, in the order they enter the board.
+    pub fn holes(&mut self, rows: u32) -> Vec<isize> {
+        (0..rows).map(|_| self.next_hole()).collect()
+    }
+
+    fn next_hole(&mut self) -> isize {
+        let moves = match self.pattern {
+            GarbagePattern::Clean => false,
+            GarbagePattern::Messy => {
+                self.hole = self.rng.gen_range(0..self.cols.max(1));
+                false
+            }
+            GarbagePattern::Cheese(percent) => self.rng.gen_range(0..100) < percent,
+        };
+
+        if moves && self.cols > 1 {
+            self.hole = (self.hole + self.rng.gen_range(1..self.cols)) % self.cols;
+        }
+        self.hole
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn hole_changes(holes: &[isize]) -> usize {
+        holes.windows(2).filter(|w| w[0] != w[1]).count()
+    }
+
+    #[test]
+    fn test_clean_keeps_the_hole() {
+        let mut generator = GarbageGenerator::new(GarbagePattern::Clean, 10, Some(0));
+        let holes = generator.holes(20);
+
+        assert_eq!(hole_changes(&holes), 0);
+        assert_eq!(generator.holes(5)[0], holes[0]);
+    }
+
+    #[test_case(GarbagePattern::Messy)]
+    #[test_case(GarbagePattern::Cheese(50))]
+    fn test_holes_stay_in_bounds(pattern: GarbagePattern) {
+        let mut generator = GarbageGenerator::new(pattern, 10, Some(0));
+
+        assert!(generator
+            .holes(200)
+            .iter()
+            .all(|hole| (0..10).contains(hole)));
+    }
+
+    #[test_case(0, 0)]
+    #[test_case(100, 99)]
+    fn test_cheese_percent(percent: u32, changes: usize) {
+        let mut generator = GarbageGenerator::new(GarbagePattern::Cheese(percent), 10, Some(0));
+
+        assert_eq!(hole_changes(&generator.holes(100)), changes);
+    }
+
+    #[test]
+    fn test_messy_moves_the_hole() {
+        let mut generator = GarbageGenerator::new(GarbagePattern::Messy, 10, Some(0));
+
+        assert!(hole_changes(&generator.holes(100)) > 50);
+    }
+
+    #[test]
+    fn test_same_seed_same_holes() {
+        let mut a = GarbageGenerator::new(GarbagePattern::Cheese(30), 10, Some(42));
+        let mut b = GarbageGenerator::new(GarbagePattern::Cheese(30), 10, Some(42));
+
+        assert_eq!(a.holes(50), b.holes(50));
+    }
+}
@@ -0,0 +1,276 @@
+//! Career history: every game played to its end, with the totals, personal
+//! bests and trends computed from it.
+use std::fmt::Write;
+
+use crate::mode::GameMode;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    /// The goal of the mode was reached
+    Finished,
+    ToppedOut,
+}
+
+/// A game played to its end.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    pub mode: GameMode,
+    /// Seconds since the Unix epoch when the game ended
+    pub date: u64,
+    pub seed: u64,
+    /// Duration in seconds
+    pub time: f32,
+    pub lines: u32,
+    pub score: u32,
+    pub pps: f32,
+    pub result: GameResult,
+}
+
+impl GameRecord {
+    /// Name of the mode as exported, and its line goal.
+    pub fn mode_name(&self) -> (&'static str, Option<u32>) {
+        match self.mode {
+            GameMode::Endless => ("endless", None),
+            GameMode::Sprint { lines } => ("sprint", Some(lines)),
+            GameMode::Marathon { .. } => ("marathon", self.mode.line_goal()),
+            GameMode::CheeseRace { lines } => ("cheese", Some(lines)),
+        }
+    }
+
+    /// The result as exported.
+    pub fn result_name(&self) -> &'static str {
+        match self.result {
+            GameResult::Finished => "finished",
+            GameResult::ToppedOut => "topped out",
+        }
+    }
+}
+
+/// Sums over every game of the history.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Totals {
+    pub games: u32,
+    pub finished: u32,
+    /// Time played in seconds
+    pub time: f32,
+    pub lines: u32,
+}
+
+/// Games played to their end, oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    pub games: Vec<GameRecord>,
+}
+
+impl History {
+    /// Appends a game, dropping the oldest ones past `max_games`.
+    pub fn push(&mut self, record: GameRecord, max_games: usize) {
+        self.games.push(record);
+        let excess = self.games.len().saturating_sub(max_games);
+        self.games.drain(..excess);
+    }
+
+    pub fn totals(&self) -> Totals {
+        self.games
+            .iter()
+            .fold(Totals::default(), |totals, game| Totals {
+                games: totals.games + 1,
+                finished: totals.finished + u32::from(game.result == GameResult::Finished),
+                time: totals.time + game.time,
+                lines: totals.lines + game.lines,
+            })
+    }
+
+    /// Fastest time of each mode with a goal, in the order they were first finished.
+    pub fn best_times(&self) -> Vec<(GameMode, f32)> {
+        let mut bests: Vec<(GameMode, f32)> = Vec::new();
+        for game in self
+            .games
+            .iter()
+            .filter(|g| g.result == GameResult::Finished)
+        {
+            match bests.iter_mut().find(|(mode, _)| *mode == game.mode) {
+                Some((_, best)) => *best = best.min(game.time),
+                None => bests.push((game.mode, game.time)),
+            }
+        }
+        bests
+    }
+
+    /// The last `n` games, and the `n` games before them.
+    pub fn recent(&self, n: usize) -> (&[GameRecord], &[GameRecord]) {
+        let (older, last) = self.games.split_at(self.games.len().saturating_sub(n));
+        (last, &older[older.len().saturating_sub(n)..])
+    }
+
+    /// One line per game under a header, dates in UTC.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,mode,goal,seed,time,lines,score,pps,result\n");
+        for game in &self.games {
+            let (mode, goal) = game.mode_name();
+            writeln!(
+                csv,
+                "{},{},{},{},{:.3},{},{},{:.2},{}",
+                date_to_string(game.date),
+                mode,
+                goal.map(|goal| goal.to_string()).unwrap_or_default(),
+                game.seed,
+                game.time,
+                game.lines,
+                game.score,
+                game.pps,
+                game.result_name(),
+            )
+            .unwrap();
+        }
+        csv
+    }
+}
+
+/// Average of `value` over `games`, none if there are no games.
+pub fn average(games: &[GameRecord], value: impl Fn(&GameRecord) -> f32) -> Option<f32> {
+    if games.is_empty() {
+        return None;
+    }
+    Some(games.iter().map(value).sum::<f32>() / games.len() as f32)
+}
+
+/// ISO 8601 date and time of `secs` seconds since the Unix epoch, in UTC.
+pub fn date_to_string(secs: u64) -> String {
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn record(mode: GameMode, time: f32, result: GameResult) -> GameRecord {
+        GameRecord {
+            mode,
+            date: 0,
+            seed: 1,
+            time,
+            lines: 40,
+            score: 1000,
+            pps: 2.,
+            result,
+        }
+    }
+
+    #[test]
+    fn test_push_drops_oldest() {
+        let mut history = History::default();
+        for time in [1., 2., 3.] {
+            history.push(record(GameMode::Endless, time, GameResult::ToppedOut), 2);
+        }
+
+        let times: Vec<_> = history.games.iter().map(|g| g.time).collect();
+        assert_eq!(times, vec![2., 3.]);
+    }
+
+    #[test]
+    fn test_totals_and_bests() {
+        let sprint = GameMode::Sprint { lines: 40 };
+        let cheese = GameMode::CheeseRace { lines: 10 };
+        let mut history = History::default();
+        for game in [
+            record(sprint, 60., GameResult::Finished),
+            record(cheese, 30., GameResult::Finished),
+            record(sprint, 50., GameResult::Finished),
+            record(sprint, 10., GameResult::ToppedOut),
+        ] {
+            history.push(game, 100);
+        }
+
+        assert_eq!(
+            history.totals(),
+            Totals {
+                games: 4,
+                finished: 3,
+                time: 150.,
+                lines: 160,
+            }
+        );
+        assert_eq!(history.best_times(), vec![(sprint, 50.), (cheese, 30.)]);
+    }
+
+    #[test_case(5, 5, 0; "fewer games than asked")]
+    #[test_case(3, 3, 2; "partial previous window")]
+    #[test_case(2, 2, 2; "full windows")]
+    fn test_recent(n: usize, last: usize, previous: usize) {
+        let mut history = History::default();
+        for time in [1., 2., 3., 4., 5.] {
+            history.push(record(GameMode::Endless, time, GameResult::ToppedOut), 100);
+        }
+
+        let (recent, before) = history.recent(n);
+        assert_eq!((recent.len(), before.len()), (last, previous));
+        assert_eq!(recent.last().map(|g| g.time), Some(5.));
+        assert_eq!(average(recent, |g| g.pps), Some(2.));
+        assert_eq!(average(&[], |g| g.pps), None);
+    }
+
+    #[test_case(0, "1970-01-01T00:00:00Z")]
+    #[test_case(951782400, "2000-02-29T00:00:00Z")]
+    #[test_case(1792234567, "2026-10-17T10:56:07Z")]
+    fn test_date_to_string(secs: u64, expected: &str) {
+        assert_eq!(date_to_string(secs), expected);
+    }
+
+    #[test_case(GameMode::Endless, ("endless", None); "endless")]
+    #[test_case(GameMode::Sprint { lines: 40 }, ("sprint", Some(40)); "sprint")]
+    #[test_case(GameMode::Marathon { endless: false }, ("marathon", Some(150)); "marathon")]
+    #[test_case(GameMode::CheeseRace { lines: 10 }, ("cheese", Some(10)); "cheese")]
+    fn test_mode_name(mode: GameMode, expected: (&str, Option<u32>)) {
+        assert_eq!(record(mode, 1., GameResult::Finished).mode_name(), expected);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut history = History::default();
+        history.push(
+            GameRecord {
+                date: 1792234567,
+                ..record(GameMode::Sprint { lines: 40 }, 45.5, GameResult::Finished)
+            },
+            100,
+        );
+        history.push(
+            record(
+                GameMode::Marathon { endless: true },
+                600.,
+                GameResult::ToppedOut,
+            ),
+            100,
+        );
+
+        assert_eq!(
+            history.to_csv(),
+            "date,mode,goal,seed,time,lines,score,pps,result\n\
+             2026-10-17T10:56:07Z,sprint,40,1,45.500,40,1000,2.00,finished\n\
+             1970-01-01T00:00:00Z,marathon,,1,600.000,40,1000,2.00,topped out\n"
+        );
+    }
+}
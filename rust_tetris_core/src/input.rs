@@ -0,0 +1,182 @@
+/// A gameplay action the player can perform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Left,
+    Right,
+    SoftDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    Hold,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Left,
+        Action::Right,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateCw,
+        Action::RotateCcw,
+        Action::Rotate180,
+        Action::Hold,
+    ];
+
+    fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Snapshot of the player input for a single tick.
+///
+/// `pressed` holds the actions which went down during this tick,
+/// `down` the ones currently held.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Input {
+    pressed: u8,
+    down: u8,
+}
+
+impl Input {
+    pub fn set(&mut self, action: Action, pressed: bool, down: bool) {
+        if pressed {
+            self.pressed |= action.mask();
+        } else {
+            self.pressed &= !action.mask();
+        }
+
+        if down || pressed {
+            self.down |= action.mask();
+        } else {
+            self.down &= !action.mask();
+        }
+    }
+
+    /// Marks the action as pressed during this tick.
+    pub fn press(mut self, action: Action) -> Self {
+        self.set(action, true, true);
+        self
+    }
+
+    /// Marks the action as held, without a new press.
+    pub fn hold(mut self, action: Action) -> Self {
+        self.set(action, false, true);
+        self
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.pressed & action.mask() != 0
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        self.down & action.mask() != 0
+    }
+
+    /// Adds the presses of a newer sample, keeping its held actions.
+    /// Presses are kept until a tick consumes them.
+    pub fn merge(self, newer: Input) -> Self {
+        let pressed = self.pressed | newer.pressed;
+        Self {
+            pressed,
+            down: newer.down | pressed,
+        }
+    }
+
+    /// The actions of either of two devices sampled on the same tick.
+    pub fn union(self, other: Input) -> Self {
+        Self {
+            pressed: self.pressed | other.pressed,
+            down: self.down | other.down,
+        }
+    }
+
+    /// The same input without the presses, once a tick consumed them.
+    pub fn held(self) -> Self {
+        Self {
+            pressed: 0,
+            down: self.down,
+        }
+    }
+
+    /// Packs the input in 16 bits: held actions in the high byte, presses in the low one.
+    pub fn to_bits(&self) -> u16 {
+        (self.down as u16) << 8 | self.pressed as u16
+    }
+
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            pressed: bits as u8,
+            down: (bits >> 8) as u8,
+        }
+    }
+
+    /// Number of actions pressed during this tick.
+    pub fn pressed_count(&self) -> u32 {
+        self.pressed.count_ones()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_press_and_hold() {
+        let input = Input::default().press(Action::Left).hold(Action::SoftDrop);
+
+        assert!(input.is_pressed(Action::Left));
+        assert!(input.is_down(Action::Left));
+        assert!(!input.is_pressed(Action::SoftDrop));
+        assert!(input.is_down(Action::SoftDrop));
+        assert_eq!(input.pressed_count(), 1);
+
+        for a in Action::ALL
+            .into_iter()
+            .filter(|a| !matches!(a, Action::Left | Action::SoftDrop))
+        {
+            assert!(!input.is_pressed(a));
+            assert!(!input.is_down(a));
+        }
+    }
+
+    #[test]
+    fn test_set_release() {
+        let mut input = Input::default().press(Action::Hold);
+        input.set(Action::Hold, false, false);
+        assert_eq!(input, Input::default());
+    }
+
+    #[test]
+    fn test_merge_keeps_presses() {
+        let first = Input::default().press(Action::HardDrop);
+        let second = Input::default().hold(Action::Left);
+        let merged = first.merge(second);
+
+        assert!(merged.is_pressed(Action::HardDrop));
+        assert!(merged.is_down(Action::HardDrop));
+        assert!(merged.is_down(Action::Left));
+
+        let held = merged.held();
+        assert!(!held.is_pressed(Action::HardDrop));
+        assert!(held.is_down(Action::Left));
+    }
+
+    #[test]
+    fn test_union() {
+        let keyboard = Input::default().press(Action::Left);
+        let gamepad = Input::default().hold(Action::Left).press(Action::Hold);
+        let input = keyboard.union(gamepad);
+
+        assert!(input.is_pressed(Action::Left));
+        assert!(input.is_pressed(Action::Hold));
+        assert_eq!(input.pressed_count(), 2);
+        assert_eq!(input.union(Input::default()), input);
+    }
+
+    #[test]
+    fn test_bits_round_trip() {
+        let input = Input::default().press(Action::Hold).hold(Action::SoftDrop);
+        assert_eq!(Input::from_bits(input.to_bits()), input);
+    }
+}
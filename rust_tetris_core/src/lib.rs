@@ -5,11 +5,29 @@ extern crate enum_derive;
 
 #[macro_use]
 pub mod macros;
+pub mod attack;
 pub mod board;
+pub mod bot;
 pub mod constants;
 pub mod enums;
+pub mod finesse;
+pub mod fumen;
+pub mod game;
+pub mod gamepad;
+pub mod garbage;
+pub mod history;
 pub mod holder;
+pub mod input;
+pub mod lock;
+pub mod mode;
+pub mod net;
 pub mod piece_bag;
 pub mod pieces;
+pub mod protocol;
+pub mod replay;
+pub mod rotation;
 pub mod score;
+pub mod stats;
+pub mod step;
 pub mod test_utils;
+pub mod timer;
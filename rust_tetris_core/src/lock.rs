@@ -0,0 +1,164 @@
+use crate::timer::FrameTimer;
+
+/// Moves and rotations allowed to reset the lock delay before the piece
+/// reaches a new lowest row.
+pub const MAX_LOCK_RESETS: u32 = 15;
+
+/// How a grounded piece gains more time before locking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LockDelayPolicy {
+    /// Moves and rotations on the ground reset the delay, up to
+    /// `MAX_LOCK_RESETS` times per lowest row reached
+    #[default]
+    MoveReset,
+    /// The delay never resets, the piece locks once it spent the
+    /// whole delay on the ground
+    Classic,
+    /// Moves and rotations on the ground always reset the delay
+    Infinity,
+}
+
+/// Lock delay of the current piece.
+#[derive(Clone, Debug)]
+pub struct LockDelay {
+    policy: LockDelayPolicy,
+    timer: FrameTimer,
+    resets: u32,
+    lowest_row: isize,
+}
+
+impl LockDelay {
+    pub fn new(policy: LockDelayPolicy, delay: f32) -> Self {
+        Self {
+            policy,
+            timer: FrameTimer::new(delay),
+            resets: 0,
+            lowest_row: isize::MAX,
+        }
+    }
+
+    /// Starts over for a piece entering the field at `row`.
+    pub fn start(&mut self, row: isize) {
+        self.timer.reset();
+        self.resets = 0;
+        self.lowest_row = row;
+    }
+
+    /// The piece moved down to `row`.
+    pub fn fell_to(&mut self, row: isize) {
+        if row >= self.lowest_row {
+            return;
+        }
+
+        self.lowest_row = row;
+        self.resets = 0;
+        if self.policy != LockDelayPolicy::Classic {
+            self.timer.reset();
+        }
+    }
+
+    /// The piece moved or rotated, `grounded` if it rests on the ground.
+    pub fn moved(&mut self, grounded: bool) {
+        if !grounded {
+            return;
+        }
+
+        match self.policy {
+            LockDelayPolicy::MoveReset if self.resets < MAX_LOCK_RESETS => {
+                self.resets += 1;
+                self.timer.reset();
+            }
+            LockDelayPolicy::Infinity => self.timer.reset(),
+            _ => {}
+        }
+    }
+
+    /// Advances the delay while the piece is on the ground,
+    /// returns whether it must lock.
+    pub fn tick(&mut self, frame: f32, grounded: bool) -> bool {
+        if !grounded {
+            return false;
+        }
+
+        self.timer.tick(frame);
+        self.timer.done()
+    }
+
+    pub fn resets_left(&self) -> u32 {
+        MAX_LOCK_RESETS.saturating_sub(self.resets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    /// Ticks `frames` frames on the ground, moving every `move_every` frames.
+    fn locks_after(lock: &mut LockDelay, move_every: u32, frames: u32) -> Option<u32> {
+        for frame in 1..=frames {
+            if frame % move_every == 0 {
+                lock.moved(true);
+            }
+            if lock.tick(1., true) {
+                return Some(frame);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_locks_after_delay() {
+        let mut lock = LockDelay::new(LockDelayPolicy::MoveReset, 30.);
+        lock.start(19);
+
+        assert!(!lock.tick(29., true));
+        assert!(lock.tick(1., true));
+    }
+
+    #[test]
+    fn test_airborne_does_not_lock() {
+        let mut lock = LockDelay::new(LockDelayPolicy::MoveReset, 30.);
+        lock.start(19);
+
+        assert!(!lock.tick(100., false));
+    }
+
+    // the frame of the last reset counts towards the delay
+    #[test_case(LockDelayPolicy::MoveReset, Some(15 * 20 + 29); "move reset is capped")]
+    #[test_case(LockDelayPolicy::Classic, Some(30); "classic never resets")]
+    #[test_case(LockDelayPolicy::Infinity, None; "infinity always resets")]
+    fn test_move_resets(policy: LockDelayPolicy, expected: Option<u32>) {
+        let mut lock = LockDelay::new(policy, 30.);
+        lock.start(19);
+
+        assert_eq!(locks_after(&mut lock, 20, 1000), expected);
+    }
+
+    #[test]
+    fn test_new_lowest_row_restores_resets() {
+        let mut lock = LockDelay::new(LockDelayPolicy::MoveReset, 30.);
+        lock.start(19);
+        for _ in 0..MAX_LOCK_RESETS {
+            lock.moved(true);
+        }
+        assert_eq!(lock.resets_left(), 0);
+
+        // climbing back up does not count
+        lock.fell_to(19);
+        assert_eq!(lock.resets_left(), 0);
+
+        lock.fell_to(10);
+        assert_eq!(lock.resets_left(), MAX_LOCK_RESETS);
+    }
+
+    #[test]
+    fn test_classic_keeps_elapsed_time() {
+        let mut lock = LockDelay::new(LockDelayPolicy::Classic, 30.);
+        lock.start(19);
+
+        assert!(!lock.tick(20., true));
+        lock.fell_to(10);
+        assert!(lock.tick(10., true));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::game::FRAMES_PER_SECOND;
+
+/// Rules deciding when a game ends, besides topping out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+    /// Play until topping out
+    #[default]
+    Endless,
+    /// Clear `lines` lines as fast as possible
+    Sprint { lines: u32 },
+    /// Level up every 10 lines, until level 15 unless `endless`
+    Marathon { endless: bool },
+    /// Dig out `lines` rows of garbage as fast as possible
+    CheeseRace { lines: u32 },
+}
+
+pub const LINES_PER_LEVEL: u32 = 10;
+pub const MARATHON_LEVELS: u32 = 15;
+/// Garbage rows on the board at once during a cheese race
+pub const CHEESE_RACE_ROWS: u32 = 10;
+/// Fastest gravity in rows per frame
+pub const MAX_GRAVITY: f32 = 20.;
+
+impl GameMode {
+    /// Whether the goal of the mode is reached after clearing `lines` lines,
+    /// counting only garbage rows in cheese races.
+    pub fn is_complete(&self, lines: u32) -> bool {
+        match *self {
+            GameMode::Endless => false,
+            GameMode::Sprint { lines: goal } | GameMode::CheeseRace { lines: goal } => {
+                lines >= goal
+            }
+            GameMode::Marathon { endless } => {
+                !endless && lines >= MARATHON_LEVELS * LINES_PER_LEVEL
+            }
+        }
+    }
+
+    /// The number of lines to clear, if the mode has one.
+    pub fn line_goal(&self) -> Option<u32> {
+        match *self {
+            GameMode::Endless => None,
+            GameMode::Sprint { lines } | GameMode::CheeseRace { lines } => Some(lines),
+            GameMode::Marathon { endless: false } => Some(MARATHON_LEVELS * LINES_PER_LEVEL),
+            GameMode::Marathon { endless: true } => None,
+        }
+    }
+
+    /// The level reached after clearing `lines` lines, if the mode has levels.
+    pub fn level(&self, lines: u32) -> Option<u32> {
+        match *self {
+            GameMode::Marathon { endless } => {
+                let level = lines / LINES_PER_LEVEL + 1;
+                Some(if endless {
+                    level
+                } else {
+                    level.min(MARATHON_LEVELS)
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Guideline gravity in rows per frame: `(0.8 - (level - 1) * 0.007)^(level - 1)`
+/// seconds per row, capped at 20G.
+pub fn level_gravity(level: u32) -> f32 {
+    let level = level.max(1) as f32;
+    let base = 0.8 - (level - 1.) * 0.007;
+    if base <= 0. {
+        return MAX_GRAVITY;
+    }
+
+    let seconds_per_row = base.powf(level - 1.);
+    (1. / (seconds_per_row * FRAMES_PER_SECOND)).min(MAX_GRAVITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(GameMode::Endless, 1000, false; "endless never completes")]
+    #[test_case(GameMode::Sprint { lines: 40 }, 39, false; "sprint below goal")]
+    #[test_case(GameMode::Sprint { lines: 40 }, 40, true; "sprint at goal")]
+    #[test_case(GameMode::Sprint { lines: 40 }, 42, true; "sprint past goal")]
+    #[test_case(GameMode::Marathon { endless: false }, 149, false; "marathon before level 15 is cleared")]
+    #[test_case(GameMode::Marathon { endless: false }, 150, true; "marathon after level 15 is cleared")]
+    #[test_case(GameMode::Marathon { endless: true }, 1000, false; "endless marathon")]
+    #[test_case(GameMode::CheeseRace { lines: 18 }, 18, true; "cheese race at goal")]
+    fn test_is_complete(mode: GameMode, lines: u32, expected: bool) {
+        assert_eq!(mode.is_complete(lines), expected);
+    }
+
+    #[test_case(GameMode::Endless, 25, None; "endless has no level")]
+    #[test_case(GameMode::Marathon { endless: false }, 0, Some(1); "marathon starts at 1")]
+    #[test_case(GameMode::Marathon { endless: false }, 25, Some(3); "marathon level every 10 lines")]
+    #[test_case(GameMode::Marathon { endless: false }, 150, Some(15); "marathon stops at 15")]
+    #[test_case(GameMode::Marathon { endless: true }, 150, Some(16); "endless marathon keeps going")]
+    fn test_level(mode: GameMode, lines: u32, expected: Option<u32>) {
+        assert_eq!(mode.level(lines), expected);
+    }
+
+    #[test]
+    fn test_level_gravity() {
+        // level 1 drops a row every 0.8^0 = 1 second
+        assert!((level_gravity(1) - 1. / 60.).abs() < 1e-6);
+        // level 2: 0.793 seconds per row
+        assert!((level_gravity(2) - 1. / (0.793 * 60.)).abs() < 1e-6);
+        assert!(level_gravity(15) > 2. && level_gravity(15) < 3.);
+        assert_eq!(level_gravity(20), MAX_GRAVITY);
+        assert_eq!(level_gravity(200), MAX_GRAVITY);
+
+        for level in 1..30 {
+            assert!(level_gravity(level + 1) >= level_gravity(level));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::protocol::Message;
+
+/// Where the relay listens unless told otherwise
+pub const DEFAULT_RELAY_ADDR: &str = "127.0.0.1:7878";
+/// Port of relay addresses given without one
+pub const DEFAULT_RELAY_PORT: u16 = 7878;
+
+/// Time given to the relay to accept the connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Bytes waiting to be sent past which the peer is deemed not to read anymore
+const MAX_OUTBOX: usize = 64 * 1024;
+/// Bytes read in a single poll, the rest waits in the socket for the next one
+const MAX_INBOX: usize = 64 * 1024;
+/// Longest line accepted, far above the longest message
+const MAX_LINE: usize = 4 * 1024;
+
+/// A non-blocking TCP stream of protocol messages, one per line, which can
+/// be polled once per frame. Neither sending nor polling ever waits for the peer.
+pub struct Connection {
+    stream: TcpStream,
+    /// Bytes received after the last complete line
+    buffer: Vec<u8>,
+    /// Bytes sent but not yet taken by the socket
+    outbox: Vec<u8>,
+}
+
+impl Connection {
+    /// Connects to the first of the addresses which accepts in time.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let mut error = io::Error::new(ErrorKind::InvalidInput, "no address");
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => return Self::from_stream(stream),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+
+    pub fn from_stream(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+            outbox: Vec::new(),
+        })
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Queues the message and sends as much of the queue as the socket takes.
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        self.outbox.extend_from_slice(message.encode().as_bytes());
+        self.outbox.push(b'\n');
+        self.flush()
+    }
+
+    /// Sends the queued bytes the socket takes without waiting. Fails once
+    /// the queue grew too long, the peer stopped reading.
+    fn flush(&mut self) -> io::Result<()> {
+        let mut sent = 0;
+        while sent < self.outbox.len() {
+            match self.stream.write(&self.outbox[sent..]) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => sent += n,
+                // the peer is slower than us, the rest goes at the next poll
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.outbox.drain(..sent);
+
+        if self.outbox.len() > MAX_OUTBOX {
+            return Err(io::Error::other("peer is not reading"));
+        }
+        Ok(())
+    }
+
+    /// Messages received since the last poll, after sending what is still
+    /// queued. Fails once the peer closed the connection and every message
+    /// before was polled, when it sent a line which is not a message or is too
+    /// long, or when it does not read what is sent to it.
+    pub fn poll(&mut self) -> io::Result<Vec<Message>> {
+        self.flush()?;
+
+        let mut chunk = [0; 4096];
+        let mut closed = false;
+        while self.buffer.len() < MAX_INBOX {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    closed = true;
+                    break;
+                }
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let end = self.buffer.iter().rposition(|&b| b == b'\n');
+        let lines: Vec<u8> = end.map_or_else(Vec::new, |end| self.buffer.drain(..=end).collect());
+        if self.buffer.len() > MAX_LINE {
+            return Err(io::Error::new(ErrorKind::InvalidData, "line too long"));
+        }
+        let messages = String::from_utf8_lossy(&lines)
+            .lines()
+            .map(|line| {
+                Message::decode(line).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if closed && messages.is_empty() {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::Snapshot;
+    use std::{net::TcpListener, thread, time::Duration};
+
+    fn pair() -> (Connection, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Connection::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        (client, Connection::from_stream(stream).unwrap())
+    }
+
+    /// Polls until `count` messages arrived.
+    fn receive(connection: &mut Connection, count: usize) -> Vec<Message> {
+        let mut messages = Vec::new();
+        for _ in 0..1000 {
+            messages.extend(connection.poll().unwrap());
+            if messages.len() >= count {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        messages
+    }
+
+    #[test]
+    fn test_send_and_poll() {
+        let (mut client, mut server) = pair();
+
+        assert_eq!(server.poll().unwrap(), vec![]);
+
+        client.send(&Message::Ready).unwrap();
+        client.send(&Message::Garbage { rows: 2 }).unwrap();
+
+        assert_eq!(
+            receive(&mut server, 2),
+            vec![Message::Ready, Message::Garbage { rows: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_partial_lines_wait_for_the_rest() {
+        let (client, mut server) = pair();
+        let mut stream = client.stream;
+
+        stream.write_all(b"GARB").unwrap();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(server.poll().unwrap(), vec![]);
+
+        stream.write_all(b"AGE 1\n").unwrap();
+        assert_eq!(receive(&mut server, 1), vec![Message::Garbage { rows: 1 }]);
+    }
+
+    #[test]
+    fn test_peer_not_reading_fails() {
+        let (mut client, _server) = pair();
+        let snapshot = Message::Snapshot(Snapshot {
+            cols: 10,
+            rows: 40,
+            pending_garbage: 0,
+            cells: vec![crate::board::Cell::Empty; 400],
+        });
+
+        // the socket buffers fill up, then the queue, without ever blocking
+        let sent = (0..100_000)
+            .take_while(|_| client.send(&snapshot).is_ok())
+            .count();
+        assert!(sent < 100_000);
+        assert!(client.poll().is_err());
+    }
+
+    #[test]
+    fn test_line_too_long_fails() {
+        let (client, mut server) = pair();
+        let mut stream = client.stream;
+        stream.write_all(&[b'.'; MAX_LINE + 1]).unwrap();
+
+        let mut result = server.poll();
+        for _ in 0..1000 {
+            if result.is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+            result = server.poll();
+        }
+
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_closed_connection_fails() {
+        let (client, mut server) = pair();
+        drop(client);
+
+        let mut result = server.poll();
+        for _ in 0..1000 {
+            if result.is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+            result = server.poll();
+        }
+
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -1,29 +1,280 @@
 use std::collections::VecDeque;
 
-use crate::{enums::PieceType, pieces::Piece};
-use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use crate::{
+    enums::PieceType,
+    pieces::Piece,
+    rotation::{RotationSystem, Srs},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use PieceType::*;
 
-/// Struct implementing a tetris bag.
+/// Every piece type, in the order the bags are shuffled from.
+const PIECES: [PieceType; 7] = [I, S, Z, O, T, L, J];
+
+/// Generates the sequence of pieces dealt by a `PieceBag`.
+pub trait Randomizer {
+    /// Draws the next piece, using `rng` as the only source of randomness.
+    fn next(&mut self, rng: &mut StdRng) -> PieceType;
+}
+
+/// Randomizers that games can be configured with.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RandomizerType {
+    /// Guideline 7-bag
+    #[default]
+    SevenBag,
+    /// Two copies of every piece per bag
+    FourteenBag,
+    /// Independent uniform draws
+    Random,
+    /// History of 4 with 4 rolls
+    Tgm1,
+    /// History of 4 with 6 rolls
+    Tgm2,
+    /// 35-bag with drought tracking
+    Tgm3,
+    /// One reroll on repeats
+    Nes,
+    /// The same pieces over and over
+    Sequence(Vec<PieceType>),
+}
+
+impl RandomizerType {
+    pub fn build(&self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerType::SevenBag => Box::new(Bag::new(1)),
+            RandomizerType::FourteenBag => Box::new(Bag::new(2)),
+            RandomizerType::Random => Box::new(PureRandom),
+            RandomizerType::Tgm1 => Box::new(History::new(4, [Z, Z, Z, Z])),
+            RandomizerType::Tgm2 => Box::new(History::new(6, [Z, S, S, Z])),
+            RandomizerType::Tgm3 => Box::new(Tgm3::new()),
+            RandomizerType::Nes => Box::new(Nes::default()),
+            RandomizerType::Sequence(pieces) => Box::new(Sequence::new(pieces.clone())),
+        }
+    }
+}
+
+/// Deals shuffled bags holding `copies` of every piece.
+pub struct Bag {
+    copies: usize,
+    /// The internal permutation, dealt from the back
+    permutation: Vec<PieceType>,
+}
+
+impl Bag {
+    pub fn new(copies: usize) -> Self {
+        Self {
+            copies,
+            permutation: Vec::with_capacity(PIECES.len() * copies),
+        }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self, rng: &mut StdRng) -> PieceType {
+        if self.permutation.is_empty() {
+            for _ in 0..self.copies {
+                self.permutation.extend(PIECES);
+            }
+            self.permutation.as_mut_slice().shuffle(rng);
+        }
+
+        self.permutation.pop().unwrap()
+    }
+}
+
+/// Every piece is equally likely, whatever came before.
+pub struct PureRandom;
+
+impl Randomizer for PureRandom {
+    fn next(&mut self, rng: &mut StdRng) -> PieceType {
+        *PIECES.choose(rng).unwrap()
+    }
+}
+
+/// TGM randomizer: rerolls pieces found among the last four, up to `rolls` times.
+pub struct History {
+    rolls: u32,
+    history: VecDeque<PieceType>,
+    first: bool,
+}
+
+impl History {
+    pub fn new(rolls: u32, history: [PieceType; 4]) -> Self {
+        Self {
+            rolls,
+            history: history.into_iter().collect(),
+            first: true,
+        }
+    }
+
+    fn push(&mut self, piece: PieceType) {
+        self.history.pop_front();
+        self.history.push_back(piece);
+    }
+}
+
+impl Randomizer for History {
+    fn next(&mut self, rng: &mut StdRng) -> PieceType {
+        let piece = if self.first {
+            self.first = false;
+            first_piece(rng)
+        } else {
+            let mut piece = *PIECES.choose(rng).unwrap();
+            for _ in 1..self.rolls {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = *PIECES.choose(rng).unwrap();
+            }
+            piece
+        };
+
+        self.push(piece);
+        piece
+    }
+}
+
+/// TGM3 randomizer: draws from a pool of 35 pieces rerolling the last four,
+/// and refills the pool with the piece that has not come for the longest time.
+pub struct Tgm3 {
+    pool: Vec<PieceType>,
+    history: VecDeque<PieceType>,
+    /// Pieces from the longest to the shortest drought
+    droughts: Vec<PieceType>,
+    first: bool,
+}
+
+impl Tgm3 {
+    const ROLLS: u32 = 6;
+
+    pub fn new() -> Self {
+        Self {
+            pool: PIECES.iter().flat_map(|&p| [p; 5]).collect(),
+            history: [S, Z, S, Z].into_iter().collect(),
+            droughts: vec![J, I, Z, L, O, T, S],
+            first: true,
+        }
+    }
+
+    fn push(&mut self, piece: PieceType) {
+        self.history.pop_front();
+        self.history.push_back(piece);
+
+        self.droughts.retain(|&p| p != piece);
+        self.droughts.push(piece);
+    }
+}
+
+impl Default for Tgm3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Randomizer for Tgm3 {
+    fn next(&mut self, rng: &mut StdRng) -> PieceType {
+        if self.first {
+            self.first = false;
+            let piece = first_piece(rng);
+            self.push(piece);
+            return piece;
+        }
+
+        let mut index = rng.gen_range(0..self.pool.len());
+        for _ in 1..Self::ROLLS {
+            if !self.history.contains(&self.pool[index]) {
+                break;
+            }
+            // rerolled pieces make room for the most droughted one
+            self.pool[index] = self.droughts[0];
+            index = rng.gen_range(0..self.pool.len());
+        }
+
+        let piece = self.pool[index];
+        self.push(piece);
+        self.pool[index] = self.droughts[0];
+        piece
+    }
+}
+
+/// NES randomizer: rolls one extra face, rerolling once on it or on the previous piece.
+#[derive(Default)]
+pub struct Nes {
+    prev: Option<PieceType>,
+}
+
+impl Randomizer for Nes {
+    fn next(&mut self, rng: &mut StdRng) -> PieceType {
+        let roll = rng.gen_range(0..=PIECES.len());
+        let piece = match PIECES.get(roll) {
+            Some(&piece) if Some(piece) != self.prev => piece,
+            _ => *PIECES.choose(rng).unwrap(),
+        };
+
+        self.prev = Some(piece);
+        piece
+    }
+}
+
+/// Deals a fixed sequence over and over, for puzzles.
+pub struct Sequence {
+    pieces: Vec<PieceType>,
+    index: usize,
+}
+
+impl Sequence {
+    pub fn new(pieces: Vec<PieceType>) -> Self {
+        assert!(!pieces.is_empty(), "a sequence needs at least one piece");
+        Self { pieces, index: 0 }
+    }
+}
+
+impl Randomizer for Sequence {
+    fn next(&mut self, _rng: &mut StdRng) -> PieceType {
+        let piece = self.pieces[self.index];
+        self.index = (self.index + 1) % self.pieces.len();
+        piece
+    }
+}
+
+/// TGM games never start with an S, Z or O piece.
+fn first_piece(rng: &mut StdRng) -> PieceType {
+    *[I, J, L, T].choose(rng).unwrap()
+}
+
+/// Struct implementing a tetris bag: a preview buffer on top of a `Randomizer`.
 pub struct PieceBag {
     /// Buffer holding the next pieces, from back to front
     buffer_next_pieces: VecDeque<Piece>,
-    /// The internal permutation
-    internal_permutation: Vec<PieceType>,
-    /// the random generator used for generating the bags
+    /// Generates the pieces entering the buffer
+    randomizer: Box<dyn Randomizer>,
+    /// the random generator used by the randomizer
     rng: StdRng,
     /// the buffer size, usually 5 in standard tetris
     buffer_size: usize,
+    /// Shapes of the dealt pieces
+    rotation_system: &'static dyn RotationSystem,
 }
 
 impl PieceBag {
+    /// A 7-bag with `buffer_size` pieces of preview.
     pub fn new(buffer_size: usize, seed: Option<u64>) -> Self {
+        Self::with_randomizer(buffer_size, seed, RandomizerType::SevenBag.build())
+    }
+
+    pub fn with_randomizer(
+        buffer_size: usize,
+        seed: Option<u64>,
+        randomizer: Box<dyn Randomizer>,
+    ) -> Self {
         let mut inst = PieceBag {
             buffer_next_pieces: VecDeque::with_capacity(buffer_size),
-            internal_permutation: Vec::with_capacity(7),
+            randomizer,
             rng: seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
             buffer_size,
+            rotation_system: &Srs,
         };
-        inst.fill_permutation();
 
         for _ in 0..inst.buffer_size {
             inst.new_block_in_buffer();
@@ -32,12 +283,20 @@ impl PieceBag {
         inst
     }
 
+    /// Deals pieces of `system` from now on, including the ones in the buffer.
+    pub fn set_rotation_system(&mut self, system: &'static dyn RotationSystem) {
+        self.rotation_system = system;
+        for piece in self.buffer_next_pieces.iter_mut() {
+            piece.set_system(system);
+        }
+    }
+
     pub fn piece_buffer(&self) -> &VecDeque<Piece> {
         &self.buffer_next_pieces
     }
 
     /// Returns the next piece in the bag.
-    /// If the buffer is empty, it gets filled from the permutation.
+    /// If the buffer is empty, it gets filled from the randomizer.
     pub fn next_piece(&mut self) -> Piece {
         let ret = self.buffer_next_pieces.pop_back().unwrap();
         self.new_block_in_buffer();
@@ -60,27 +319,10 @@ impl PieceBag {
         self.buffer_next_pieces.iter().rev().take(self.buffer_size)
     }
 
-    fn fill_permutation(&mut self) {
-        let mut nums: Vec<_> = vec![
-            PieceType::I,
-            PieceType::S,
-            PieceType::Z,
-            PieceType::O,
-            PieceType::T,
-            PieceType::L,
-            PieceType::J,
-        ];
-        nums.as_mut_slice().shuffle(&mut self.rng);
-        self.internal_permutation.extend(nums.into_iter());
-    }
-
     fn new_block_in_buffer(&mut self) {
-        if self.internal_permutation.is_empty() {
-            self.fill_permutation();
-        }
-
-        let piece = self.internal_permutation.pop().unwrap();
-        self.buffer_next_pieces.push_front(Piece::new(piece));
+        let piece = self.randomizer.next(&mut self.rng);
+        self.buffer_next_pieces
+            .push_front(Piece::with_system(piece, self.rotation_system));
     }
 }
 
@@ -94,7 +336,7 @@ impl Default for PieceBag {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use PieceType::*;
+    use test_case::test_case;
 
     const FIRST_ORDER: [PieceType; 7] = [J, S, Z, I, T, O, L];
     const SECOND_ORDER: [PieceType; 7] = [L, T, Z, I, O, S, J];
@@ -104,13 +346,28 @@ mod tests {
         PieceBag::new(5, Some(0xDEADBEEF))
     }
 
+    fn draw(randomizer: RandomizerType, seed: u64, count: usize) -> Vec<PieceType> {
+        let mut randomizer = randomizer.build();
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count).map(|_| randomizer.next(&mut rng)).collect()
+    }
+
+    fn count(pieces: &[PieceType], piece: PieceType) -> usize {
+        pieces.iter().filter(|&&p| p == piece).count()
+    }
+
     #[test]
     fn test_init() {
-        let bag = get_bag();
+        let mut bag = get_bag();
         assert_eq!(bag.buffer_size, 5);
 
         let (exp_perm, exp_buf) = FIRST_ORDER.split_at(2);
-        assert_eq!(bag.internal_permutation, exp_perm);
+        assert_eq!(
+            (0..2)
+                .map(|_| bag.randomizer.next(&mut bag.rng))
+                .collect::<Vec<_>>(),
+            exp_perm.iter().rev().copied().collect::<Vec<_>>()
+        );
         assert_eq!(
             bag.buffer_next_pieces
                 .iter()
@@ -156,4 +413,55 @@ mod tests {
                 .collect::<Vec<_>>()
         );
     }
+
+    #[test_case(RandomizerType::SevenBag, 1; "7-bag")]
+    #[test_case(RandomizerType::FourteenBag, 2; "14-bag")]
+    fn test_bags_deal_every_piece(randomizer: RandomizerType, copies: usize) {
+        let pieces = draw(randomizer, 7, 70);
+
+        for bag in pieces.chunks(7 * copies) {
+            for piece in PIECES {
+                assert_eq!(count(bag, piece), copies);
+            }
+        }
+    }
+
+    #[test_case(RandomizerType::Random; "random")]
+    #[test_case(RandomizerType::Tgm1; "tgm1")]
+    #[test_case(RandomizerType::Tgm2; "tgm2")]
+    #[test_case(RandomizerType::Tgm3; "tgm3")]
+    #[test_case(RandomizerType::Nes; "nes")]
+    fn test_deals_every_piece(randomizer: RandomizerType) {
+        let pieces = draw(randomizer.clone(), 7, 700);
+
+        assert_eq!(pieces, draw(randomizer, 7, 700));
+        for piece in PIECES {
+            assert!(count(&pieces, piece) > 50, "{:?} is too rare", piece);
+        }
+    }
+
+    #[test_case(RandomizerType::Tgm1; "tgm1")]
+    #[test_case(RandomizerType::Tgm2; "tgm2")]
+    #[test_case(RandomizerType::Tgm3; "tgm3")]
+    fn test_tgm_first_piece(randomizer: RandomizerType) {
+        for seed in 0..50 {
+            let first = draw(randomizer.clone(), seed, 1)[0];
+            assert!(![S, Z, O].contains(&first));
+        }
+    }
+
+    #[test_case(RandomizerType::Random, RandomizerType::Tgm2; "tgm2")]
+    #[test_case(RandomizerType::Random, RandomizerType::Tgm3; "tgm3")]
+    #[test_case(RandomizerType::Random, RandomizerType::Nes; "nes")]
+    fn test_fewer_repeats_than_random(random: RandomizerType, randomizer: RandomizerType) {
+        let repeats = |pieces: Vec<PieceType>| pieces.windows(2).filter(|w| w[0] == w[1]).count();
+
+        assert!(repeats(draw(randomizer, 7, 1000)) < repeats(draw(random, 7, 1000)));
+    }
+
+    #[test]
+    fn test_sequence_loops() {
+        let pieces = draw(RandomizerType::Sequence(vec![T, I, O]), 7, 7);
+        assert_eq!(pieces, vec![T, I, O, T, I, O, T]);
+    }
 }
@@ -1,6 +1,7 @@
-use crate::board::{playable_piece_to_cell, Board, Cell};
-use crate::constants::{get_rotations, Kick, DEFAULT_KICKS, I_KICKS, KICKS_180};
+use crate::board::{playable_piece_to_cell, Board, BoardSize, Cell};
+use crate::constants::Kick;
 use crate::enums::{PieceType, PieceTypeColor, Rotation};
+use crate::rotation::{RotationSystem, Srs};
 use std::fmt;
 
 #[derive(Clone)]
@@ -8,6 +9,7 @@ pub struct Piece {
     pub piece_type: PieceType,
     pub board: Board,
     pub rotation: Rotation,
+    system: &'static dyn RotationSystem,
 }
 
 impl Piece {
@@ -16,6 +18,7 @@ impl Piece {
             piece_type,
             rotation,
             board: Board::new(0, 0),
+            system: &Srs,
         };
 
         tetris_piece.setup_board();
@@ -27,8 +30,23 @@ impl Piece {
         Self::with_rotation(piece_type, Rotation::R0)
     }
 
+    pub fn with_system(piece_type: PieceType, system: &'static dyn RotationSystem) -> Self {
+        let mut piece = Self::new(piece_type);
+        piece.set_system(system);
+        piece
+    }
+
+    pub fn system(&self) -> &'static dyn RotationSystem {
+        self.system
+    }
+
+    pub fn set_system(&mut self, system: &'static dyn RotationSystem) {
+        self.system = system;
+        self.setup_board();
+    }
+
     fn setup_board(&mut self) {
-        self.board = get_piece_matrix(self.piece_type, self.rotation);
+        self.board = get_piece_matrix(self.system, self.piece_type, self.rotation);
     }
 
     pub fn rotate_piece(&mut self) {
@@ -80,34 +98,7 @@ impl Piece {
     }
 
     pub fn get_kicks(&self, from_rot: Rotation) -> &'static [Kick] {
-        use Rotation::*;
-        let kick_index = match (from_rot, self.rotation) {
-            (R0, R90) => 0,
-            (R90, R0) => 1,
-            (R90, R180) => 2,
-            (R180, R90) => 3,
-            (R180, R270) => 4,
-            (R270, R180) => 5,
-            (R270, R0) => 6,
-            (R0, R270) => 7,
-            (R0, R180) => 0,
-            (R180, R0) => 1,
-            (R90, R270) => 2,
-            (R270, R90) => 3,
-            _ => unreachable!(),
-        };
-
-        if (self.rotation as usize).abs_diff(from_rot as usize) != 2 {
-            // 90 Deg
-            match self.piece_type {
-                PieceType::I => &I_KICKS[kick_index],
-                PieceType::O => &[(0, 0)],
-                _ => &DEFAULT_KICKS[kick_index],
-            }
-        } else {
-            // 180 deg
-            &KICKS_180[kick_index]
-        }
+        self.system.kicks(self.piece_type, from_rot, self.rotation)
     }
 }
 
@@ -128,8 +119,13 @@ impl fmt::Display for Piece {
     }
 }
 
-fn fill_piece_matrix(piece: PieceType, matrix: &mut Board, rotation: Rotation) {
-    let matrix_bytes = get_rotations(piece, rotation);
+fn fill_piece_matrix(
+    system: &dyn RotationSystem,
+    piece: PieceType,
+    matrix: &mut Board,
+    rotation: Rotation,
+) {
+    let matrix_bytes = system.shape(piece, rotation);
     let cols = matrix.cols;
 
     for (row, row_vec) in matrix_bytes.iter().rev().zip(matrix.rows_mut()) {
@@ -147,12 +143,12 @@ fn fill_piece_matrix(piece: PieceType, matrix: &mut Board, rotation: Rotation) {
     }
 }
 
-fn get_piece_matrix(piece: PieceType, rotation: Rotation) -> Board {
+fn get_piece_matrix(system: &dyn RotationSystem, piece: PieceType, rotation: Rotation) -> Board {
     let (r, c) = get_piece_size(piece);
 
     let mut matrix = Board::new(r, c);
 
-    fill_piece_matrix(piece, &mut matrix, rotation);
+    fill_piece_matrix(system, piece, &mut matrix, rotation);
 
     matrix
 }
@@ -177,6 +173,12 @@ impl PieceWithPosition {
         PieceWithPosition { r, c, piece }
     }
 
+    /// `piece` where it enters a board of `size`.
+    pub fn spawn(piece: Piece, size: BoardSize) -> Self {
+        let (row, col) = piece.system().spawn_offset(piece.piece_type);
+        Self::new(size.spawn_row() + row, size.spawn_col() + col, piece)
+    }
+
     pub fn row(&self) -> isize {
         self.r
     }
@@ -251,49 +253,69 @@ impl PieceWithPosition {
     }
 
     pub fn rotate_and_kick(&self, prev_rot: Rotation, matrix: &Board) -> Option<&'static Kick> {
-        self.piece
-            .get_kicks(prev_rot)
+        self.kick_index(prev_rot, matrix)
+            .map(|i| &self.piece.get_kicks(prev_rot)[i])
+    }
+
+    /// Returns the index of the first kick that fits, in the kick table for `prev_rot`.
+    pub fn kick_index(&self, prev_rot: Rotation, matrix: &Board) -> Option<usize> {
+        let mut kicks = self.piece.get_kicks(prev_rot);
+        if !self
+            .piece
+            .system
+            .allows_kicks(&self.piece, self.r, self.c, matrix)
+        {
+            kicks = &kicks[..kicks.len().min(1)];
+        }
+
+        kicks
             .iter()
-            .find(|&kick| !self.collides_kick(matrix, kick))
+            .position(|kick| !self.collides_kick(matrix, kick))
     }
 
-    pub fn try_rotate(&mut self, matrix: &Board) -> bool {
+    fn rotate_with(
+        &mut self,
+        matrix: &Board,
+        rotate: fn(&mut Piece),
+        undo: fn(&mut Piece),
+    ) -> Option<usize> {
         let prev = self.piece.rotation;
-        self.piece.rotate_piece();
+        rotate(&mut self.piece);
 
-        if let Some(k) = self.rotate_and_kick(prev, matrix) {
-            self.kick_by(k);
-            true
+        if let Some(i) = self.kick_index(prev, matrix) {
+            self.kick_by(&self.piece.get_kicks(prev)[i]);
+            Some(i)
         } else {
-            self.piece.rotate_piece_prev();
-            false
+            undo(&mut self.piece);
+            None
         }
     }
 
-    pub fn try_rotate_prev(&mut self, matrix: &Board) -> bool {
-        let prev = self.piece.rotation;
-        self.piece.rotate_piece_prev();
+    pub fn try_rotate(&mut self, matrix: &Board) -> bool {
+        self.try_rotate_kick(matrix).is_some()
+    }
 
-        if let Some(k) = self.rotate_and_kick(prev, matrix) {
-            self.kick_by(k);
-            true
-        } else {
-            self.piece.rotate_piece();
-            false
-        }
+    pub fn try_rotate_prev(&mut self, matrix: &Board) -> bool {
+        self.try_rotate_prev_kick(matrix).is_some()
     }
 
     pub fn try_rotate_180(&mut self, matrix: &Board) -> bool {
-        let prev = self.piece.rotation;
-        self.piece.rotate_piece_180();
+        self.try_rotate_180_kick(matrix).is_some()
+    }
 
-        if let Some(k) = self.rotate_and_kick(prev, matrix) {
-            self.kick_by(k);
-            true
-        } else {
-            self.piece.rotate_piece_180();
-            false
-        }
+    /// Like `try_rotate`, but returns the index of the kick used.
+    pub fn try_rotate_kick(&mut self, matrix: &Board) -> Option<usize> {
+        self.rotate_with(matrix, Piece::rotate_piece, Piece::rotate_piece_prev)
+    }
+
+    /// Like `try_rotate_prev`, but returns the index of the kick used.
+    pub fn try_rotate_prev_kick(&mut self, matrix: &Board) -> Option<usize> {
+        self.rotate_with(matrix, Piece::rotate_piece_prev, Piece::rotate_piece)
+    }
+
+    /// Like `try_rotate_180`, but returns the index of the kick used.
+    pub fn try_rotate_180_kick(&mut self, matrix: &Board) -> Option<usize> {
+        self.rotate_with(matrix, Piece::rotate_piece_180, Piece::rotate_piece_180)
     }
 
     pub fn move_down(&mut self) {
@@ -312,7 +334,7 @@ impl PieceWithPosition {
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
-    use super::{get_piece_size, Board, Piece, PieceType, Rotation};
+    use super::{get_piece_size, Board, Piece, PieceType, PieceWithPosition, Rotation};
     use crate::test_utils::load_board;
     use test_case::test_case;
 
@@ -425,4 +447,18 @@ mod tests {
         let s = format!("{}", t);
         assert_eq!(s, "   \n***\n * \n");
     }
+
+    #[test]
+    fn test_try_rotate_kick() {
+        let mut b = Board::new(4, 5);
+        let mut t = PieceWithPosition::new(0, 0, Piece::new(PieceType::T));
+        assert_eq!(t.try_rotate_kick(&b), Some(0));
+        assert_eq!(t.try_rotate_prev_kick(&b), Some(0));
+
+        // the unkicked R90 position is blocked, the first kick moves it left
+        load_board(&mut b, " *");
+        assert_eq!(t.try_rotate_kick(&b), Some(1));
+        assert_eq!((t.row(), t.col()), (0, -1));
+        assert_eq!(t.tetris_piece_ref().rotation, Rotation::R90);
+    }
 }
@@ -0,0 +1,280 @@
+//! Messages of online versus matches. Clients talk to a relay which pairs
+//! them in rooms and forwards their messages to the opponent. Messages are
+//! lines of text, a keyword followed by its arguments.
+use crate::{
+    board::{Board, Cell},
+    enums::{PieceType, PieceTypeColor},
+    game::Game,
+};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// Client: enters the room with this name
+    Join { room: String },
+    /// Relay: players in the room after someone joined
+    Joined { players: u32 },
+    /// Relay: the room already has two players
+    RoomFull,
+    /// Client: wants to start the next match of the room
+    Ready,
+    /// Relay: both players are ready, the seed deals the same pieces to both
+    Start { seed: u64 },
+    /// Forwarded: garbage rows sent to the opponent
+    Garbage { rows: u32 },
+    /// Forwarded: the field of the sender
+    Snapshot(Snapshot),
+    /// Forwarded: the sender topped out and lost the match
+    TopOut,
+    /// Relay: the opponent left the room
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// A keyword no message starts with
+    Unknown,
+    /// Arguments missing or not matching the keyword
+    Arguments,
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::Unknown => write!(f, "unknown message"),
+            ProtocolError::Arguments => write!(f, "invalid message arguments"),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+/// Largest fields a snapshot may carry, so that a hostile line cannot make
+/// the receiver allocate a huge board.
+pub const MAX_SNAPSHOT_COLS: isize = 20;
+pub const MAX_SNAPSHOT_ROWS: isize = 40;
+/// Most garbage rows a message may send, more than any board can hold.
+pub const MAX_GARBAGE_ROWS: u32 = MAX_SNAPSHOT_ROWS as u32;
+
+/// Visible rows of a field with the garbage waiting to enter it,
+/// enough to draw the board of the opponent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub cols: isize,
+    pub rows: isize,
+    pub pending_garbage: u32,
+    /// Bottom row first
+    pub cells: Vec<Cell>,
+}
+
+impl Snapshot {
+    /// The locked blocks of the visible rows of `game`.
+    pub fn from_game(game: &Game) -> Self {
+        let rows = game.config.board_size.visible_rows.min(game.board.rows);
+        let cols = game.board.cols;
+
+        Self {
+            cols,
+            rows,
+            pending_garbage: game.pending_garbage(),
+            cells: (0..rows)
+                .flat_map(|i| (0..cols).map(move |j| (i, j)))
+                .map(|(i, j)| game.board.get(i, j))
+                .collect(),
+        }
+    }
+
+    pub fn board(&self) -> Board {
+        let mut board = Board::new(self.rows, self.cols);
+        for (index, &cell) in self.cells.iter().enumerate() {
+            let index = index as isize;
+            board.set_val(index / self.cols, index % self.cols, cell);
+        }
+        board
+    }
+}
+
+impl Message {
+    /// The line of the message, without the line break.
+    pub fn encode(&self) -> String {
+        match self {
+            Message::Join { room } => format!("JOIN {}", room),
+            Message::Joined { players } => format!("JOINED {}", players),
+            Message::RoomFull => "FULL".to_string(),
+            Message::Ready => "READY".to_string(),
+            Message::Start { seed } => format!("START {}", seed),
+            Message::Garbage { rows } => format!("GARBAGE {}", rows),
+            Message::Snapshot(snapshot) => format!(
+                "BOARD {} {} {} {}",
+                snapshot.cols,
+                snapshot.rows,
+                snapshot.pending_garbage,
+                snapshot
+                    .cells
+                    .iter()
+                    .map(|&c| cell_char(c))
+                    .collect::<String>()
+            ),
+            Message::TopOut => "TOPOUT".to_string(),
+            Message::Left => "LEFT".to_string(),
+        }
+    }
+
+    pub fn decode(line: &str) -> Result<Message, ProtocolError> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (keyword, args) = line.split_once(' ').unwrap_or((line, ""));
+
+        let message = match keyword {
+            "JOIN" if !args.is_empty() => Message::Join {
+                room: args.to_string(),
+            },
+            "JOINED" => Message::Joined {
+                players: parse(args)?,
+            },
+            "FULL" => Message::RoomFull,
+            "READY" => Message::Ready,
+            "START" => Message::Start { seed: parse(args)? },
+            "GARBAGE" => Message::Garbage {
+                rows: decode_garbage(args)?,
+            },
+            "BOARD" => Message::Snapshot(decode_snapshot(args)?),
+            "TOPOUT" => Message::TopOut,
+            "LEFT" => Message::Left,
+            "JOIN" => return Err(ProtocolError::Arguments),
+            _ => return Err(ProtocolError::Unknown),
+        };
+        Ok(message)
+    }
+}
+
+fn parse<T: std::str::FromStr>(arg: &str) -> Result<T, ProtocolError> {
+    arg.parse().map_err(|_| ProtocolError::Arguments)
+}
+
+fn decode_garbage(args: &str) -> Result<u32, ProtocolError> {
+    let rows = parse(args)?;
+    if rows > MAX_GARBAGE_ROWS {
+        return Err(ProtocolError::Arguments);
+    }
+    Ok(rows)
+}
+
+fn decode_snapshot(args: &str) -> Result<Snapshot, ProtocolError> {
+    let [cols, rows, pending, cells] = args
+        .split(' ')
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| ProtocolError::Arguments)?;
+
+    let snapshot = Snapshot {
+        cols: parse(cols)?,
+        rows: parse(rows)?,
+        pending_garbage: parse(pending)?,
+        cells: cells
+            .chars()
+            .map(char_cell)
+            .collect::<Option<_>>()
+            .ok_or(ProtocolError::Arguments)?,
+    };
+
+    if !(1..=MAX_SNAPSHOT_COLS).contains(&snapshot.cols)
+        || !(1..=MAX_SNAPSHOT_ROWS).contains(&snapshot.rows)
+        || snapshot.cols.checked_mul(snapshot.rows) != Some(snapshot.cells.len() as isize)
+    {
+        return Err(ProtocolError::Arguments);
+    }
+    Ok(snapshot)
+}
+
+/// Empty cells are dots, garbage is `X`, pieces are their letter.
+fn cell_char(cell: Cell) -> char {
+    match cell {
+        Cell::Empty => '.',
+        Cell::Filled(PieceTypeColor::NotPlayable) => 'X',
+        Cell::Filled(PieceTypeColor::Playable(piece)) => piece!(piece,
+            O => 'O',
+            I => 'I',
+            Z => 'Z',
+            S => 'S',
+            J => 'J',
+            L => 'L',
+            T => 'T',
+        ),
+    }
+}
+
+fn char_cell(c: char) -> Option<Cell> {
+    match c {
+        '.' => Some(Cell::Empty),
+        'X' => Some(Cell::Filled(PieceTypeColor::NotPlayable)),
+        _ => c
+            .to_string()
+            .parse::<PieceType>()
+            .ok()
+            .map(|piece| Cell::Filled(PieceTypeColor::Playable(piece))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::GameConfig, test_utils::load_board};
+    use test_case::test_case;
+
+    #[test_case(Message::Join { room: "room 1".to_string() }, "JOIN room 1")]
+    #[test_case(Message::Joined { players: 2 }, "JOINED 2")]
+    #[test_case(Message::RoomFull, "FULL")]
+    #[test_case(Message::Ready, "READY")]
+    #[test_case(Message::Start { seed: u64::MAX }, "START 18446744073709551615")]
+    #[test_case(Message::Garbage { rows: 4 }, "GARBAGE 4")]
+    #[test_case(Message::TopOut, "TOPOUT")]
+    #[test_case(Message::Left, "LEFT")]
+    fn test_round_trip(message: Message, line: &str) {
+        assert_eq!(message.encode(), line);
+        assert_eq!(Message::decode(line), Ok(message));
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut game = Game::new(GameConfig::default(), Some(0));
+        load_board(&mut game.board, "**** *****T         ");
+        game.queue_garbage(3);
+
+        let snapshot = Snapshot::from_game(&game);
+        let line = Message::Snapshot(snapshot.clone()).encode();
+
+        assert!(line.starts_with("BOARD 10 20 3 XXXX.XXXXXT........."));
+        assert_eq!(
+            Message::decode(&line),
+            Ok(Message::Snapshot(snapshot.clone()))
+        );
+
+        let board = snapshot.board();
+        assert_eq!(board.get(0, 4), Cell::Empty);
+        assert_eq!(board.get(0, 5), Cell::Filled(PieceTypeColor::NotPlayable));
+        assert_eq!(
+            board.get(1, 0),
+            Cell::Filled(PieceTypeColor::Playable(PieceType::T))
+        );
+    }
+
+    #[test_case("HELLO", ProtocolError::Unknown)]
+    #[test_case("JOIN", ProtocolError::Arguments)]
+    #[test_case("GARBAGE many", ProtocolError::Arguments)]
+    #[test_case("GARBAGE 4294967295", ProtocolError::Arguments; "too much garbage")]
+    #[test_case("START", ProtocolError::Arguments)]
+    #[test_case("BOARD 2 1 0 ...", ProtocolError::Arguments)]
+    #[test_case("BOARD 2 1 0 .Q", ProtocolError::Arguments)]
+    #[test_case("BOARD 0 0 0 ", ProtocolError::Arguments)]
+    #[test_case("BOARD 4611686018427387904 4 0 ", ProtocolError::Arguments; "overflowing size")]
+    #[test_case("BOARD 1 41 0 .........................................", ProtocolError::Arguments; "too many rows")]
+    #[test_case("BOARD 21 1 0 .....................", ProtocolError::Arguments; "too many cols")]
+    fn test_invalid(line: &str, error: ProtocolError) {
+        assert_eq!(Message::decode(line), Err(error));
+    }
+
+    #[test]
+    fn test_line_break_is_ignored() {
+        assert_eq!(Message::decode("READY\r\n"), Ok(Message::Ready));
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    game::{Game, GameConfig},
+    input::Input,
+    step::FixedStep,
+};
+
+/// Everything needed to play a game again: the seed of the piece bag,
+/// the config, the tick rate and the input of every tick.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: GameConfig,
+    /// Ticks per second the game was simulated at
+    pub tick_rate: u32,
+    /// Inputs run-length encoded as (input, ticks)
+    runs: Vec<(Input, u32)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, config: GameConfig, tick_rate: u32) -> Self {
+        Self::from_runs(seed, config, tick_rate, Vec::new())
+    }
+
+    pub fn from_runs(
+        seed: u64,
+        config: GameConfig,
+        tick_rate: u32,
+        runs: Vec<(Input, u32)>,
+    ) -> Self {
+        Self {
+            seed,
+            config,
+            tick_rate,
+            runs,
+        }
+    }
+
+    pub fn runs(&self) -> &[(Input, u32)] {
+        &self.runs
+    }
+
+    /// Appends the input of the next tick.
+    pub fn record(&mut self, input: Input) {
+        match self.runs.last_mut() {
+            Some((last, ticks)) if *last == input => *ticks += 1,
+            _ => self.runs.push((input, 1)),
+        }
+    }
+
+    /// Number of recorded ticks.
+    pub fn ticks(&self) -> u32 {
+        self.runs.iter().map(|&(_, ticks)| ticks).sum()
+    }
+
+    /// Length of the recording in seconds.
+    pub fn duration(&self) -> f32 {
+        self.ticks() as f32 / self.tick_rate as f32
+    }
+
+    /// A fixed step at the recorded tick rate.
+    pub fn step(&self) -> FixedStep {
+        FixedStep::new(self.tick_rate)
+    }
+
+    /// Starts a game identical to the recorded one.
+    pub fn new_game(&self) -> Game {
+        Game::new(self.config.clone(), Some(self.seed))
+    }
+}
+
+/// Feeds the inputs of a replay one tick at a time.
+pub struct ReplayPlayer {
+    replay: Replay,
+    run: usize,
+    offset: u32,
+    tick: u32,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            run: 0,
+            offset: 0,
+            tick: 0,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Ticks played so far.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Whether every recorded tick was played.
+    pub fn is_done(&self) -> bool {
+        self.run >= self.replay.runs.len()
+    }
+
+    /// Returns the input of the next tick, `None` once the replay is over.
+    pub fn next_input(&mut self) -> Option<Input> {
+        let &(input, ticks) = self.replay.runs.get(self.run)?;
+
+        self.offset += 1;
+        if self.offset == ticks {
+            self.run += 1;
+            self.offset = 0;
+        }
+        self.tick += 1;
+
+        Some(input)
+    }
+
+    /// Starts over from the first tick.
+    pub fn rewind(&mut self) {
+        self.run = 0;
+        self.offset = 0;
+        self.tick = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{input::Action, mode::GameMode, step::FRAME_RATE};
+    use test_case::test_case;
+
+    fn inputs() -> Vec<Input> {
+        let idle = Input::default();
+        let mut inputs = vec![];
+        for i in 0..600 {
+            inputs.push(match i % 40 {
+                0 => idle.press(Action::Left),
+                1..=12 => idle.hold(Action::Left),
+                15 => idle.press(Action::RotateCw),
+                20 => idle.press(Action::Hold),
+                25..=30 => idle.hold(Action::SoftDrop),
+                35 => idle.press(Action::HardDrop),
+                _ => idle,
+            });
+        }
+        inputs
+    }
+
+    #[test]
+    fn test_record_run_length() {
+        let mut replay = Replay::new(1, GameConfig::default(), FRAME_RATE);
+        let idle = Input::default();
+        for input in [idle, idle, idle.press(Action::Left), idle, idle] {
+            replay.record(input);
+        }
+
+        assert_eq!(replay.ticks(), 5);
+        assert_eq!(
+            replay.runs(),
+            &[(idle, 2), (idle.press(Action::Left), 1), (idle, 2)]
+        );
+    }
+
+    #[test_case(60; "at 60 Hz")]
+    #[test_case(240; "at 240 Hz")]
+    fn test_playback_matches_live_game(tick_rate: u32) {
+        let config = GameConfig {
+            mode: GameMode::Marathon { endless: true },
+            line_clear_delay: 10.,
+            ..Default::default()
+        };
+        let mut replay = Replay::new(0xC0FFEE, config, tick_rate);
+        let frames = replay.step().tick_frames();
+        let mut live = replay.new_game();
+        for input in inputs() {
+            live.tick(frames, &input);
+            replay.record(input);
+        }
+
+        let mut player = ReplayPlayer::new(replay);
+        let mut game = player.replay().new_game();
+        while let Some(input) = player.next_input() {
+            game.tick(frames, &input);
+        }
+
+        assert_eq!(player.tick(), 600);
+        assert!(player.is_done());
+        assert_eq!(format!("{:?}", game.board), format!("{:?}", live.board));
+        assert_eq!(
+            game.score_updater.get_score(),
+            live.score_updater.get_score()
+        );
+        assert_eq!(game.lines, live.lines);
+        assert_eq!(game.time, live.time);
+        assert!(game.score_updater.get_score() > 0);
+
+        player.rewind();
+        assert_eq!(player.next_input(), Some(inputs()[0]));
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{
+    board::Board,
+    constants::{
+        get_ars_rotations, get_rotations, Kick, ARS_KICKS, DEFAULT_KICKS, I_KICKS, KICKS_180,
+        NO_KICKS, SRS_PLUS_I_KICKS,
+    },
+    enums::{PieceType, Rotation},
+    pieces::Piece,
+};
+
+/// Shapes, spawn positions and wall kicks of the pieces.
+pub trait RotationSystem: Sync {
+    /// Rows of the piece matrix in `rotation`, top row first, one bit per column.
+    fn shape(&self, piece: PieceType, rotation: Rotation) -> &'static [u8];
+
+    /// Offset (rows, cols) of the piece matrix from the spawn position.
+    fn spawn_offset(&self, piece: PieceType) -> (isize, isize);
+
+    /// Kicks tried in order when rotating from `from` to `to`,
+    /// the rotation is not possible when empty.
+    fn kicks(&self, piece: PieceType, from: Rotation, to: Rotation) -> &'static [Kick];
+
+    /// Whether `piece`, rotated in place at (`row`, `col`), may try kicks past the first one.
+    fn allows_kicks(&self, _piece: &Piece, _row: isize, _col: isize, _board: &Board) -> bool {
+        true
+    }
+
+    /// Cell (row, col) of the T matrix in `rotation` that its three other blocks touch,
+    /// the center of the 3-corner T-spin check.
+    fn t_spin_center(&self, _rotation: Rotation) -> (isize, isize) {
+        (1, 1)
+    }
+}
+
+/// Rotation systems that games can be configured with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RotationSystemType {
+    #[default]
+    Srs,
+    /// SRS with symmetric I kicks
+    SrsPlus,
+    /// Arika rotation system
+    Ars,
+    /// Nintendo rotation, without kicks
+    Classic,
+    /// SRS without 180 rotations
+    SrsNo180,
+}
+
+impl RotationSystemType {
+    pub fn system(&self) -> &'static dyn RotationSystem {
+        match self {
+            RotationSystemType::Srs => &Srs,
+            RotationSystemType::SrsPlus => &SrsPlus,
+            RotationSystemType::Ars => &Ars,
+            RotationSystemType::Classic => &Classic,
+            RotationSystemType::SrsNo180 => &SrsNo180,
+        }
+    }
+}
+
+/// Index of the rotation in the kick tables, 90 and 180 degree tables are indexed apart.
+fn kick_index(from: Rotation, to: Rotation) -> usize {
+    use Rotation::*;
+    match (from, to) {
+        (R0, R90) => 0,
+        (R90, R0) => 1,
+        (R90, R180) => 2,
+        (R180, R90) => 3,
+        (R180, R270) => 4,
+        (R270, R180) => 5,
+        (R270, R0) => 6,
+        (R0, R270) => 7,
+        (R0, R180) => 0,
+        (R180, R0) => 1,
+        (R90, R270) => 2,
+        (R270, R90) => 3,
+        _ => unreachable!(),
+    }
+}
+
+fn is_180(from: Rotation, to: Rotation) -> bool {
+    (from as usize).abs_diff(to as usize) == 2
+}
+
+/// Super Rotation System, with the TETR.IO 180 kicks.
+pub struct Srs;
+
+impl RotationSystem for Srs {
+    fn shape(&self, piece: PieceType, rotation: Rotation) -> &'static [u8] {
+        get_rotations(piece, rotation)
+    }
+
+    fn spawn_offset(&self, piece: PieceType) -> (isize, isize) {
+        match piece {
+            PieceType::I => (-1, 0),
+            _ => (0, 0),
+        }
+    }
+
+    fn kicks(&self, piece: PieceType, from: Rotation, to: Rotation) -> &'static [Kick] {
+        let index = kick_index(from, to);
+        if is_180(from, to) {
+            return &KICKS_180[index];
+        }
+
+        match piece {
+            PieceType::I => &I_KICKS[index],
+            PieceType::O => &NO_KICKS,
+            _ => &DEFAULT_KICKS[index],
+        }
+    }
+}
+
+/// TETR.IO SRS+: SRS with I kicks mirrored between both directions.
+pub struct SrsPlus;
+
+impl RotationSystem for SrsPlus {
+    fn shape(&self, piece: PieceType, rotation: Rotation) -> &'static [u8] {
+        Srs.shape(piece, rotation)
+    }
+
+    fn spawn_offset(&self, piece: PieceType) -> (isize, isize) {
+        Srs.spawn_offset(piece)
+    }
+
+    fn kicks(&self, piece: PieceType, from: Rotation, to: Rotation) -> &'static [Kick] {
+        match piece {
+            PieceType::I if !is_180(from, to) => &SRS_PLUS_I_KICKS[kick_index(from, to)],
+            _ => Srs.kicks(piece, from, to),
+        }
+    }
+}
+
+/// SRS without 180 rotations.
+pub struct SrsNo180;
+
+impl RotationSystem for SrsNo180 {
+    fn shape(&self, piece: PieceType, rotation: Rotation) -> &'static [u8] {
+        Srs.shape(piece, rotation)
+    }
+
+    fn spawn_offset(&self, piece: PieceType) -> (isize, isize) {
+        Srs.spawn_offset(piece)
+    }
+
+    fn kicks(&self, piece: PieceType, from: Rotation, to: Rotation) -> &'static [Kick] {
+        if is_180(from, to) {
+            &[]
+        } else {
+            Srs.kicks(piece, from, to)
+        }
+    }
+}
+
+/// Arika rotation system: one column right then left, never for the I piece.
+/// L, J and T cannot kick when the first blocked cell of their box is in the center column.
+pub struct Ars;
+
+impl RotationSystem for Ars {
+    fn shape(&self, piece: PieceType, rotation: Rotation) -> &'static [u8] {
+        get_ars_rotations(piece, rotation)
+    }
+
+    fn spawn_offset(&self, piece: PieceType) -> (isize, isize) {
+        // shapes rest one row lower than in SRS
+        match piece {
+            PieceType::I => (-1, 0),
+            _ => (1, 0),
+        }
+    }
+
+    fn kicks(&self, piece: PieceType, _from: Rotation, _to: Rotation) -> &'static [Kick] {
+        match piece {
+            PieceType::I | PieceType::O => &NO_KICKS,
+            _ => &ARS_KICKS,
+        }
+    }
+
+    fn allows_kicks(&self, piece: &Piece, row: isize, col: isize, board: &Board) -> bool {
+        if !matches!(piece.piece_type, PieceType::L | PieceType::J | PieceType::T) {
+            return true;
+        }
+
+        // scan from the top left, rows go up
+        let first_blocked = piece
+            .set_cells()
+            .filter(|&(i, j)| board.is_set(row + i, col + j))
+            .min_by_key(|&(i, j)| (-i, j));
+
+        !matches!(first_blocked, Some((_, 1)))
+    }
+
+    fn t_spin_center(&self, rotation: Rotation) -> (isize, isize) {
+        // pointing up, the T rests on the bottom row of its box
+        match rotation {
+            Rotation::R180 => (0, 1),
+            _ => (1, 1),
+        }
+    }
+}
+
+/// Nintendo rotation: ARS shapes, rotating in place only, without 180 rotations.
+pub struct Classic;
+
+impl RotationSystem for Classic {
+    fn shape(&self, piece: PieceType, rotation: Rotation) -> &'static [u8] {
+        Ars.shape(piece, rotation)
+    }
+
+    fn spawn_offset(&self, piece: PieceType) -> (isize, isize) {
+        Ars.spawn_offset(piece)
+    }
+
+    fn kicks(&self, _piece: PieceType, from: Rotation, to: Rotation) -> &'static [Kick] {
+        if is_180(from, to) {
+            &[]
+        } else {
+            &NO_KICKS
+        }
+    }
+
+    fn t_spin_center(&self, rotation: Rotation) -> (isize, isize) {
+        Ars.t_spin_center(rotation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pieces::PieceWithPosition, test_utils::load_board};
+    use test_case::test_case;
+
+    fn piece(system: RotationSystemType, piece_type: PieceType) -> PieceWithPosition {
+        PieceWithPosition::new(0, 0, Piece::with_system(piece_type, system.system()))
+    }
+
+    #[test_case(RotationSystemType::Srs, true; "srs")]
+    #[test_case(RotationSystemType::SrsPlus, true; "srs plus")]
+    #[test_case(RotationSystemType::Ars, true; "ars")]
+    #[test_case(RotationSystemType::Classic, false; "classic")]
+    #[test_case(RotationSystemType::SrsNo180, false; "no 180")]
+    fn test_rotate_180(system: RotationSystemType, expected: bool) {
+        let b = Board::new(4, 5);
+        let mut t = piece(system, PieceType::T);
+
+        assert_eq!(t.try_rotate_180(&b), expected);
+    }
+
+    #[test]
+    fn test_srs_plus_i_kicks_are_symmetric() {
+        use Rotation::*;
+        let cw = SrsPlus.kicks(PieceType::I, R0, R90);
+        let ccw = SrsPlus.kicks(PieceType::I, R0, R270);
+
+        assert_eq!(cw[1].0, -ccw[1].0);
+        assert_eq!(cw[2].0, -ccw[2].0);
+        assert_ne!(cw, Srs.kicks(PieceType::I, R0, R90));
+    }
+
+    #[test]
+    fn test_ars_spawn_shape() {
+        // pointing down, resting on the bottom row of its box
+        let t = Piece::with_system(PieceType::T, &Ars);
+        let cells: Vec<_> = t.set_cells().collect();
+
+        assert_eq!(cells, vec![(0, 1), (1, 0), (1, 1), (1, 2)]);
+    }
+
+    #[test_case(RotationSystemType::Ars, Some(1); "ars kicks right")]
+    #[test_case(RotationSystemType::Classic, None; "classic does not kick")]
+    fn test_kick_off_the_left_wall(system: RotationSystemType, expected: Option<usize>) {
+        let b = Board::new(4, 5);
+        let mut t = piece(system, PieceType::T);
+        t.try_rotate(&b);
+        t.move_left();
+
+        assert_eq!(t.try_rotate_kick(&b), expected);
+    }
+
+    #[test]
+    fn test_ars_center_column_rule() {
+        let mut b = Board::new(4, 5);
+        let mut t = piece(RotationSystemType::Ars, PieceType::T);
+
+        // a block in the center column forbids kicks
+        load_board(&mut b, "      *   ");
+        assert_eq!(t.try_rotate_kick(&b), None);
+
+        // a block in a side column allows them
+        let mut b = Board::new(4, 5);
+        load_board(&mut b, "     *    ");
+        assert_eq!(t.try_rotate_kick(&b), Some(1));
+    }
+}
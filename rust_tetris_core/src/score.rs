@@ -2,19 +2,23 @@ use std::mem;
 
 use log::{debug, trace};
 
-use crate::{
-    board::Board,
-    constants::{C, R},
-    enums::PieceType,
-    pieces::PieceWithPosition,
-};
-
-#[derive(Debug, Clone, Copy)]
+use crate::{board::Board, enums::PieceType, pieces::PieceWithPosition};
+
+/// Index of the SRS kick which upgrades a T-spin mini to a full T-spin
+/// (the 1x2 kick used by TST and fin setups).
+const TST_KICK_INDEX: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ScoreType {
+    /// T-spin without line clears
+    TSpin,
     TSpinSingle,
     TSpinDouble,
     TSpinTriple,
+    /// Mini T-spin without line clears
     TSpinMini,
+    TSpinMiniSingle,
+    TSpinMiniDouble,
     Tetris,
     AllClear,
     Single,
@@ -22,6 +26,30 @@ pub enum ScoreType {
     Triple,
 }
 
+impl ScoreType {
+    /// T-spins from the plainest to the strongest.
+    pub const T_SPINS: [ScoreType; 7] = [
+        ScoreType::TSpinMini,
+        ScoreType::TSpinMiniSingle,
+        ScoreType::TSpinMiniDouble,
+        ScoreType::TSpin,
+        ScoreType::TSpinSingle,
+        ScoreType::TSpinDouble,
+        ScoreType::TSpinTriple,
+    ];
+
+    /// Plain line clear, without any spin or all clear bonus.
+    pub fn from_lines(rows: u32) -> Option<ScoreType> {
+        match rows {
+            1 => Some(ScoreType::Single),
+            2 => Some(ScoreType::Double),
+            3 => Some(ScoreType::Triple),
+            4 => Some(ScoreType::Tetris),
+            _ => None,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub enum Moves {
     Fall,
@@ -34,13 +62,27 @@ pub enum Moves {
 pub struct ScoreUpdater {
     current_combo: ComboState,
     last_score: Option<ScoreType>,
+    /// score of the last piece which cleared lines, used to chain back-to-backs
+    last_clear: Option<ScoreType>,
     last_move: Moves,
+    /// kick used by the last rotation, `None` for 180 degree rotations
+    last_kick: Option<usize>,
     back_to_back: u32,
+    score: u32,
+    /// multiplier for line clear points
+    level: u32,
 }
 
 impl ScoreUpdater {
     pub fn set_last_move(&mut self, last_move: Moves) {
         self.last_move = last_move;
+        self.last_kick = Some(0);
+    }
+
+    /// Records a successful rotation with the index of the kick used.
+    pub fn set_last_rotation(&mut self, kick_index: usize, half_turn: bool) {
+        self.last_move = Moves::Rotate;
+        self.last_kick = if half_turn { None } else { Some(kick_index) };
     }
 
     pub fn get_last_score(&self) -> Option<ScoreType> {
@@ -55,9 +97,25 @@ impl ScoreUpdater {
         self.current_combo
     }
 
-    pub fn finalized_piece(&mut self, piece_with_position: &PieceWithPosition, board: &mut Board) {
-        let completed_rows_ranges = board.completed_rows();
-        let completed_rows = completed_rows_ranges
+    /// Guideline point total
+    pub fn get_score(&self) -> u32 {
+        self.score
+    }
+
+    /// Sets the level which multiplies the points of the next clears.
+    pub fn set_level(&mut self, level: u32) {
+        self.level = level;
+    }
+
+    /// Awards drop points: 1 per soft dropped row, 2 per hard dropped row.
+    pub fn dropped(&mut self, rows: u32, hard: bool) {
+        self.score += if hard { 2 * rows } else { rows };
+    }
+
+    /// Scores a locked piece. The completed rows are left on the board.
+    pub fn finalized_piece(&mut self, piece_with_position: &PieceWithPosition, board: &Board) {
+        let completed_rows = board
+            .completed_rows()
             .iter()
             .map(|r| (r.0 - r.1) as u32)
             .sum();
@@ -65,75 +123,103 @@ impl ScoreUpdater {
         let cur = mem::take(&mut self.current_combo);
         self.current_combo = cur.next(completed_rows);
 
-        let last = self.last_score.take();
-
-        if piece_with_position.tetris_piece_ref().piece_type == PieceType::T {
-            // detect T-spin
-
-            if completed_rows > 0 && self.last_move == Moves::Rotate {
-                let center_r = piece_with_position.row() + 1;
-                let center_c = piece_with_position.col() + 1;
-                let mut occupied = 0;
+        self.last_score = None;
 
-                debug!("Maybe t-spin detected");
+        if piece_with_position.tetris_piece_ref().piece_type == PieceType::T
+            && self.last_move == Moves::Rotate
+        {
+            self.last_score = self.detect_t_spin(piece_with_position, board, completed_rows);
+            debug!("Score computed: {:?}", self.last_score);
+        }
 
-                for i in &[-1, 1] {
-                    for j in &[-1, 1] {
-                        let ei = center_r + i;
-                        let ej = center_c + j;
+        if self.last_score.is_none() {
+            self.last_score = ScoreType::from_lines(completed_rows);
+            if completed_rows == 4 {
+                debug!("Tetris detected");
+            }
+        }
 
-                        if !(0..R).contains(&ei) || !(0..C).contains(&ej) || board.is_set(ei, ej) {
-                            occupied += 1;
-                        }
-                    }
-                }
+        let mut points = 0;
 
-                debug!("{} corners occupied", occupied);
+        if let Some(score_type) = self.last_score.filter(|_| completed_rows == 0) {
+            // T-spins without lines neither break nor extend combos and back-to-backs
+            points = line_clear_points(score_type);
+        } else if let Some(score_type) = self.last_score {
+            let is_b2b = is_b2b_worth(score_type) && self.last_clear.is_some_and(is_b2b_worth);
+            trace!("B2B detected? {}", is_b2b);
 
-                if occupied >= 3 {
-                    self.last_score = match completed_rows {
-                        1 => Some(ScoreType::TSpinSingle),
-                        2 => Some(ScoreType::TSpinDouble),
-                        3 => Some(ScoreType::TSpinTriple),
-                        _ => None,
-                    };
+            if is_b2b {
+                self.back_to_back += 1;
+            } else {
+                self.back_to_back = 0;
+            }
 
-                    debug!("Score computed: {:?}", self.last_score);
-                }
+            if self.back_to_back > 0 {
+                debug!("B2B level: {}", self.back_to_back);
             }
-        } else if (2..=4).contains(&completed_rows) {
-            self.last_score = match completed_rows {
-                2 => Some(ScoreType::Double),
-                3 => Some(ScoreType::Triple),
-                4 => {
-                    debug!("Tetris detected");
-                    Some(ScoreType::Tetris)
-                }
-                _ => unreachable!(),
+
+            points = line_clear_points(score_type);
+            if is_b2b {
+                points = points * 3 / 2;
             }
+            points += 50 * self.current_combo.value().unwrap_or(0);
+            self.last_clear = Some(score_type);
         }
 
-        let is_b2b = completed_rows > 0
-            && last.is_some()
-            && self.last_score.is_some()
-            && is_b2b_worth(self.last_score.unwrap());
-        trace!("B2B detected? {}", is_b2b);
-
-        if is_b2b {
-            self.back_to_back += 1;
-        } else {
+        if board.is_all_clear() {
+            points += all_clear_points(completed_rows, self.back_to_back > 0);
             self.back_to_back = 0;
+            self.last_score = Some(ScoreType::AllClear);
         }
 
-        if self.back_to_back > 0 {
-            debug!("B2B level: {}", self.back_to_back);
-        }
+        self.score += points * self.level;
+    }
 
-        board.remove_ranges(completed_rows_ranges);
+    /// Guideline 3-corner T-spin check, the piece must already be on the board.
+    fn detect_t_spin(
+        &self,
+        piece_with_position: &PieceWithPosition,
+        board: &Board,
+        completed_rows: u32,
+    ) -> Option<ScoreType> {
+        let piece = piece_with_position.tetris_piece_ref();
+        let (ci, cj) = piece.system().t_spin_center(piece.rotation);
+        let center_r = piece_with_position.row() + ci;
+        let center_c = piece_with_position.col() + cj;
+        let occupied = |(i, j): (isize, isize)| {
+            let ei = center_r + i;
+            let ej = center_c + j;
+            !board.is_in_bounds(ei, ej) || board.is_set(ei, ej)
+        };
+
+        // the T points to the one block next to its center that has no block across
+        let pointing = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .into_iter()
+            .find(|&(i, j)| {
+                piece.board.is_set(ci + i, cj + j) && !piece.board.is_set(ci - i, cj - j)
+            })?;
+
+        let (front, back) = t_corners(pointing);
+        let front = front.into_iter().filter(|&c| occupied(c)).count();
+        let back = back.into_iter().filter(|&c| occupied(c)).count();
+
+        debug!("{} front and {} back corners occupied", front, back);
+
+        if front + back < 3 {
+            return None;
+        }
 
-        if board.is_empty() {
-            self.back_to_back = 0;
-            self.last_score = Some(ScoreType::AllClear);
+        let mini = front < 2 && self.last_kick != Some(TST_KICK_INDEX);
+
+        match (mini, completed_rows) {
+            (false, 0) => Some(ScoreType::TSpin),
+            (false, 1) => Some(ScoreType::TSpinSingle),
+            (false, 2) => Some(ScoreType::TSpinDouble),
+            (false, 3) => Some(ScoreType::TSpinTriple),
+            (true, 0) => Some(ScoreType::TSpinMini),
+            (true, 1) => Some(ScoreType::TSpinMiniSingle),
+            (true, 2) => Some(ScoreType::TSpinMiniDouble),
+            _ => None,
         }
     }
 }
@@ -144,16 +230,61 @@ impl Default for ScoreUpdater {
             current_combo: ComboState::Empty,
             last_move: Moves::Fall,
             last_score: None,
+            last_clear: None,
+            last_kick: Some(0),
             back_to_back: 0,
+            score: 0,
+            level: 1,
         }
     }
 }
 
+type Corners = [(isize, isize); 2];
+
+/// Corners around the T center as (row, col) offsets for a T pointing
+/// towards `(i, j)`: the two on the pointing side first, then the two behind.
+fn t_corners((i, j): (isize, isize)) -> (Corners, Corners) {
+    (
+        [(i + j, j + i), (i - j, j - i)],
+        [(j - i, i - j), (-i - j, -j - i)],
+    )
+}
+
 pub fn is_b2b_worth(s: ScoreType) -> bool {
     use ScoreType::*;
     !matches!(s, Single | Double | Triple)
 }
 
+/// Guideline points for a line clear, before back-to-back and combo bonuses.
+pub fn line_clear_points(s: ScoreType) -> u32 {
+    use ScoreType::*;
+    match s {
+        Single => 100,
+        Double => 300,
+        Triple => 500,
+        Tetris => 800,
+        TSpin => 400,
+        TSpinMini => 100,
+        TSpinMiniSingle => 200,
+        TSpinMiniDouble => 400,
+        TSpinSingle => 800,
+        TSpinDouble => 1200,
+        TSpinTriple => 1600,
+        AllClear => 0,
+    }
+}
+
+fn all_clear_points(completed_rows: u32, b2b: bool) -> u32 {
+    match completed_rows {
+        1 => 800,
+        2 => 1200,
+        3 => 1800,
+        4 if b2b => 3200,
+        4 => 2000,
+        _ => 0,
+    }
+}
+
 #[derive(Default, Clone, Copy, Eq, PartialEq, Debug)]
 pub enum ComboState {
     #[default]
@@ -188,6 +319,238 @@ mod tests {
     use super::ComboState::*;
     use super::ScoreType::*;
     use super::*;
+    use crate::{
+        enums::Rotation,
+        pieces::{Piece, PieceWithPosition},
+        rotation::Ars,
+        test_utils::load_board,
+    };
+    use test_case::test_case;
+
+    fn finalize(updater: &mut ScoreUpdater, piece: &PieceWithPosition, board: &mut Board) {
+        updater.finalized_piece(piece, board);
+        board.remove_ranges(board.completed_rows());
+    }
+
+    fn tsd_board() -> Board {
+        let mut board = Board::new(20, 10);
+        load_board(&mut board, "**** ********   ****   *      ");
+        board
+    }
+
+    fn tsd_piece() -> PieceWithPosition {
+        PieceWithPosition::new(0, 3, Piece::with_rotation(PieceType::T, Rotation::R180))
+    }
+
+    #[test]
+    fn test_t_spin_double() {
+        let mut board = tsd_board();
+        let piece = tsd_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_move(Moves::Rotate);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(TSpinDouble));
+        assert_eq!(updater.get_score(), 1200);
+        assert_eq!(updater.get_combo(), Started);
+    }
+
+    #[test]
+    fn test_t_spin_zero() {
+        let mut board = Board::new(20, 10);
+        load_board(&mut board, "**** **** **    ****   *      ");
+        let piece = tsd_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_rotation(2, false);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(TSpin));
+        assert_eq!(updater.get_score(), 400);
+        assert_eq!(updater.get_combo(), Empty);
+        assert_eq!(updater.get_b2b(), 0);
+    }
+
+    fn mini_board() -> Board {
+        let mut board = Board::new(20, 10);
+        load_board(&mut board, " *********");
+        board
+    }
+
+    fn mini_piece() -> PieceWithPosition {
+        // pointing right against the left wall, only the lower front corner is filled
+        PieceWithPosition::new(0, -1, Piece::with_rotation(PieceType::T, Rotation::R90))
+    }
+
+    #[test]
+    fn test_t_spin_mini() {
+        let mut board = mini_board();
+        let piece = mini_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_rotation(1, false);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(TSpinMiniSingle));
+        assert_eq!(updater.get_score(), 200);
+    }
+
+    #[test]
+    fn test_tst_kick_upgrades_mini() {
+        let mut board = mini_board();
+        let piece = mini_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_rotation(4, false);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(TSpinSingle));
+    }
+
+    #[test]
+    fn test_half_turn_does_not_upgrade_mini() {
+        let mut board = mini_board();
+        let piece = mini_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_rotation(4, true);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(TSpinMiniSingle));
+    }
+
+    #[test]
+    fn test_t_spin_uses_board_size() {
+        // pointing left against the right wall of a 6 wide board
+        let mut board = Board::new(8, 6);
+        load_board(&mut board, "***** ");
+        let piece =
+            PieceWithPosition::new(0, 4, Piece::with_rotation(PieceType::T, Rotation::R270));
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_rotation(0, false);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(TSpinMiniSingle));
+    }
+
+    #[test_case(Rotation::R0, "**** ********   ****   *      ", TSpinDouble; "pointing down")]
+    #[test_case(Rotation::R180, "***   ****   *      ", TSpinMiniSingle; "pointing up")]
+    fn test_ars_t_spin(rotation: Rotation, rows: &str, expected: ScoreType) {
+        // ARS shapes point down at spawn and rest on the bottom of their box
+        let mut board = Board::new(20, 10);
+        load_board(&mut board, rows);
+        let mut t = Piece::with_system(PieceType::T, &Ars);
+        t.set_rotation(rotation);
+        let piece = PieceWithPosition::new(0, 3, t);
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_rotation(0, false);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(expected));
+    }
+
+    #[test]
+    fn test_t_spin_needs_rotation() {
+        let mut board = mini_board();
+        let piece = mini_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_move(Moves::Side);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(Single));
+    }
+
+    #[test]
+    fn test_t_without_spin_is_plain_clear() {
+        let mut board = tsd_board();
+        let piece = tsd_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_last_move(Moves::Down);
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(Double));
+        assert_eq!(updater.get_score(), 300);
+    }
+
+    #[test]
+    fn test_b2b_survives_pieces_without_clear() {
+        let piece = PieceWithPosition::new(10, 0, Piece::new(PieceType::I));
+        let tetris = format!("********* {}", "*".repeat(40));
+        let mut updater = ScoreUpdater::default();
+
+        let mut board = Board::new(20, 10);
+        load_board(&mut board, &tetris);
+        finalize(&mut updater, &piece, &mut board);
+        assert_eq!(updater.get_last_score(), Some(Tetris));
+        assert_eq!(updater.get_b2b(), 0);
+
+        finalize(&mut updater, &piece, &mut board);
+        assert_eq!(updater.get_last_score(), None);
+        assert_eq!(updater.get_combo(), Empty);
+
+        load_board(&mut board, &tetris);
+        finalize(&mut updater, &piece, &mut board);
+        assert_eq!(updater.get_last_score(), Some(Tetris));
+        assert_eq!(updater.get_b2b(), 1);
+        assert_eq!(updater.get_score(), 800 + 1200);
+
+        // a plain single breaks the chain
+        load_board(&mut board, &format!("********* {}", "*".repeat(10)));
+        finalize(&mut updater, &piece, &mut board);
+        assert_eq!(updater.get_last_score(), Some(Single));
+        assert_eq!(updater.get_b2b(), 0);
+    }
+
+    #[test]
+    fn test_all_clear() {
+        let piece = PieceWithPosition::new(10, 0, Piece::new(PieceType::I));
+        let mut board = Board::new(20, 10);
+        load_board(&mut board, &"*".repeat(20));
+
+        let mut updater = ScoreUpdater::default();
+        finalize(&mut updater, &piece, &mut board);
+
+        assert_eq!(updater.get_last_score(), Some(AllClear));
+        assert_eq!(updater.get_score(), 300 + 1200);
+    }
+
+    #[test]
+    fn test_level_multiplier() {
+        let mut board = tsd_board();
+        let piece = tsd_piece();
+        piece.finalize_on(&mut board);
+
+        let mut updater = ScoreUpdater::default();
+        updater.set_level(3);
+        updater.dropped(2, true);
+        updater.set_last_rotation(0, false);
+        finalize(&mut updater, &piece, &mut board);
+
+        // drop points are not multiplied
+        assert_eq!(updater.get_score(), 4 + 1200 * 3);
+    }
+
+    #[test]
+    fn test_drop_points() {
+        let mut updater = ScoreUpdater::default();
+        updater.dropped(3, false);
+        updater.dropped(10, true);
+        assert_eq!(updater.get_score(), 23);
+    }
 
     #[test]
     fn test_combo_next() {
@@ -228,6 +591,8 @@ mod tests {
         assert!(is_b2b_worth(TSpinDouble));
         assert!(is_b2b_worth(TSpinTriple));
         assert!(is_b2b_worth(TSpinMini));
+        assert!(is_b2b_worth(TSpinMiniSingle));
+        assert!(is_b2b_worth(TSpinMiniDouble));
         assert!(is_b2b_worth(Tetris));
         assert!(is_b2b_worth(AllClear));
         assert!(!is_b2b_worth(Single));
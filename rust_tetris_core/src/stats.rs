@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::{enums::PieceType, game::GameEvent, input::Input, score::ScoreType};
+
+/// Collects gameplay statistics from the events emitted by a `Game`.
+#[derive(Debug, Default, Clone)]
+pub struct Statistics {
+    pub pieces: u32,
+    pub lines: u32,
+    pub moves: u32,
+    pub rotations: u32,
+    pub holds: u32,
+    /// Pieces placed with more keys than needed
+    pub finesse_faults: u32,
+    /// Keys pressed while playing
+    pub inputs: u32,
+    /// Lines sent by clears, counting those cancelling pending garbage
+    pub attack: u32,
+    pub max_combo: u32,
+    pub max_b2b: u32,
+    /// Locked pieces of each type
+    pub piece_counts: HashMap<PieceType, u32>,
+    /// T-spins scored of each type
+    pub t_spins: HashMap<ScoreType, u32>,
+}
+
+impl Statistics {
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Locked(piece_type) => {
+                self.pieces += 1;
+                *self.piece_counts.entry(*piece_type).or_default() += 1;
+            }
+            GameEvent::LinesCleared {
+                rows,
+                score_type,
+                combo,
+                b2b,
+                attack,
+            } => {
+                self.lines += rows;
+                self.attack += attack;
+                self.max_combo = self.max_combo.max(*combo);
+                self.max_b2b = self.max_b2b.max(*b2b);
+                if let Some(t_spin) = score_type.filter(|s| ScoreType::T_SPINS.contains(s)) {
+                    *self.t_spins.entry(t_spin).or_default() += 1;
+                }
+            }
+            GameEvent::Moved { .. } => self.moves += 1,
+            GameEvent::Rotated { .. } => self.rotations += 1,
+            GameEvent::Hold => self.holds += 1,
+            GameEvent::FinesseFault { .. } => self.finesse_faults += 1,
+            _ => {}
+        }
+    }
+
+    pub fn handle_input(&mut self, input: &Input) {
+        self.inputs += input.pressed_count();
+    }
+
+    /// Pieces per second over `time` seconds.
+    pub fn pps(&self, time: f32) -> f32 {
+        if time > 0. {
+            self.pieces as f32 / time
+        } else {
+            0.
+        }
+    }
+
+    /// Lines cleared per minute over `time` seconds.
+    pub fn lpm(&self, time: f32) -> f32 {
+        per_minute(self.lines, time)
+    }
+
+    /// Attack per minute over `time` seconds.
+    pub fn apm(&self, time: f32) -> f32 {
+        per_minute(self.attack, time)
+    }
+
+    /// Locked pieces of `piece_type`.
+    pub fn piece_count(&self, piece_type: PieceType) -> u32 {
+        self.piece_counts.get(&piece_type).copied().unwrap_or(0)
+    }
+
+    /// T-spins scored as `score_type`.
+    pub fn t_spin_count(&self, score_type: ScoreType) -> u32 {
+        self.t_spins.get(&score_type).copied().unwrap_or(0)
+    }
+
+    /// Key presses per piece.
+    pub fn kpp(&self) -> f32 {
+        if self.pieces > 0 {
+            self.inputs as f32 / self.pieces as f32
+        } else {
+            0.
+        }
+    }
+}
+
+fn per_minute(count: u32, time: f32) -> f32 {
+    if time > 0. {
+        count as f32 * 60. / time
+    } else {
+        0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Action;
+
+    #[test]
+    fn test_handle_event() {
+        let mut stats = Statistics::default();
+        for e in [
+            GameEvent::PieceSpawned(PieceType::T),
+            GameEvent::Moved { cells: 3 },
+            GameEvent::Rotated { kick_index: 0 },
+            GameEvent::Hold,
+            GameEvent::Locked(PieceType::T),
+            GameEvent::FinesseFault {
+                piece: PieceType::T,
+                inputs: 3,
+                minimal: 1,
+                cells: [(0, 0), (0, 1), (0, 2), (1, 1)],
+            },
+            GameEvent::LinesCleared {
+                rows: 2,
+                score_type: Some(ScoreType::Double),
+                combo: 0,
+                b2b: 0,
+                attack: 1,
+            },
+            GameEvent::Locked(PieceType::T),
+            GameEvent::LinesCleared {
+                rows: 2,
+                score_type: Some(ScoreType::TSpinDouble),
+                combo: 1,
+                b2b: 1,
+                attack: 6,
+            },
+        ] {
+            stats.handle_event(&e);
+        }
+
+        assert_eq!(stats.pieces, 2);
+        assert_eq!(stats.lines, 4);
+        assert_eq!(stats.moves, 1);
+        assert_eq!(stats.rotations, 1);
+        assert_eq!(stats.holds, 1);
+        assert_eq!(stats.finesse_faults, 1);
+        assert_eq!(stats.attack, 7);
+        assert_eq!((stats.max_combo, stats.max_b2b), (1, 1));
+        assert_eq!(stats.piece_count(PieceType::T), 2);
+        assert_eq!(stats.piece_count(PieceType::I), 0);
+        assert_eq!(stats.t_spin_count(ScoreType::TSpinDouble), 1);
+        assert_eq!(stats.t_spin_count(ScoreType::TSpinSingle), 0);
+    }
+
+    #[test]
+    fn test_rates() {
+        let mut stats = Statistics::default();
+        assert_eq!(stats.pps(0.), 0.);
+        assert_eq!(stats.kpp(), 0.);
+
+        for _ in 0..4 {
+            stats.handle_input(&Input::default().press(Action::Left).press(Action::HardDrop));
+            stats.handle_event(&GameEvent::Locked(PieceType::O));
+        }
+        stats.handle_input(&Input::default().hold(Action::Left));
+
+        assert_eq!(stats.inputs, 8);
+        assert_eq!(stats.pps(2.), 2.);
+        assert_eq!(stats.kpp(), 2.);
+
+        stats.lines = 10;
+        stats.attack = 5;
+        assert_eq!(stats.lpm(30.), 20.);
+        assert_eq!(stats.apm(30.), 10.);
+        assert_eq!(stats.apm(0.), 0.);
+    }
+}
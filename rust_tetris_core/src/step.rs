@@ -0,0 +1,145 @@
+use crate::input::Input;
+
+/// Frames per second, the unit of every timer of the game.
+pub const FRAME_RATE: u32 = 60;
+
+/// Splits variable frame times into fixed ticks so that a game only depends
+/// on the tick rate and the input of each tick.
+#[derive(Clone, Debug)]
+pub struct FixedStep {
+    tick_rate: u32,
+    /// Duration of a tick in frames
+    tick_frames: f32,
+    accumulator: f32,
+    pending: Input,
+}
+
+impl FixedStep {
+    /// A step of `tick_rate` ticks per second, sub-stepping frames above 60 Hz.
+    pub fn new(tick_rate: u32) -> Self {
+        Self {
+            tick_rate,
+            tick_frames: FRAME_RATE as f32 / tick_rate as f32,
+            accumulator: 0.,
+            pending: Input::default(),
+        }
+    }
+
+    pub fn tick_rate(&self) -> u32 {
+        self.tick_rate
+    }
+
+    /// Duration of a tick in frames, to pass to `Game::tick`.
+    pub fn tick_frames(&self) -> f32 {
+        self.tick_frames
+    }
+
+    /// Adds `frames` of elapsed time and the input sampled over them.
+    pub fn advance(&mut self, frames: f32, input: Input) {
+        self.accumulator += frames;
+        self.pending = self.pending.merge(input);
+    }
+
+    /// Returns the input of the next due tick, presses only go to the first one.
+    pub fn next_tick(&mut self) -> Option<Input> {
+        if self.accumulator < self.tick_frames {
+            return None;
+        }
+
+        self.accumulator -= self.tick_frames;
+        let input = self.pending;
+        self.pending = input.held();
+        Some(input)
+    }
+
+    /// Progress towards the next tick, from 0 to 1, to render between ticks.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.tick_frames).clamp(0., 1.)
+    }
+
+    /// Drops the elapsed time and the input not consumed yet.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.tick_rate);
+    }
+}
+
+impl Default for FixedStep {
+    fn default() -> Self {
+        Self::new(FRAME_RATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Action;
+    use test_case::test_case;
+
+    fn ticks(step: &mut FixedStep) -> Vec<Input> {
+        std::iter::from_fn(|| step.next_tick()).collect()
+    }
+
+    #[test]
+    fn test_splits_frames_into_ticks() {
+        let mut step = FixedStep::default();
+
+        step.advance(0.5, Input::default());
+        assert_eq!(ticks(&mut step).len(), 0);
+
+        step.advance(2.75, Input::default());
+        assert_eq!(ticks(&mut step).len(), 3);
+
+        step.advance(0.75, Input::default());
+        assert_eq!(ticks(&mut step).len(), 1);
+    }
+
+    #[test_case(60, 1; "at 60 Hz")]
+    #[test_case(120, 2; "at 120 Hz")]
+    #[test_case(240, 4; "at 240 Hz")]
+    fn test_sub_steps(tick_rate: u32, expected: usize) {
+        let mut step = FixedStep::new(tick_rate);
+
+        step.advance(1., Input::default());
+        assert_eq!(ticks(&mut step).len(), expected);
+        assert_eq!(step.tick_frames() * expected as f32, 1.);
+    }
+
+    #[test]
+    fn test_alpha() {
+        let mut step = FixedStep::new(120);
+
+        step.advance(0.75, Input::default());
+        ticks(&mut step);
+        assert_eq!(step.alpha(), 0.5);
+
+        step.reset();
+        assert_eq!(step.alpha(), 0.);
+        assert_eq!(step.tick_rate(), 120);
+    }
+
+    #[test]
+    fn test_presses_wait_for_a_tick() {
+        let mut step = FixedStep::default();
+
+        step.advance(0.5, Input::default().press(Action::HardDrop));
+        assert_eq!(ticks(&mut step).len(), 0);
+
+        step.advance(0.5, Input::default());
+        let inputs = ticks(&mut step);
+        assert_eq!(inputs.len(), 1);
+        assert!(inputs[0].is_pressed(Action::HardDrop));
+    }
+
+    #[test]
+    fn test_presses_go_to_the_first_tick() {
+        let mut step = FixedStep::default();
+
+        step.advance(2., Input::default().press(Action::Left));
+        let inputs = ticks(&mut step);
+
+        assert!(inputs[0].is_pressed(Action::Left));
+        assert!(inputs[0].is_down(Action::Left));
+        assert!(!inputs[1].is_pressed(Action::Left));
+        assert!(inputs[1].is_down(Action::Left));
+    }
+}
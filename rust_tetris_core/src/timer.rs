@@ -0,0 +1,63 @@
+/// A countdown timer measured in frames (1/60 of a second).
+#[derive(Clone, Debug)]
+pub struct FrameTimer {
+    duration: f32,
+    left: f32,
+}
+
+impl FrameTimer {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            left: duration,
+        }
+    }
+
+    pub fn tick(&mut self, frame: f32) {
+        self.left -= frame;
+    }
+
+    pub fn done(&self) -> bool {
+        self.left <= 0.
+    }
+
+    pub fn reset(&mut self) {
+        self.left = self.duration
+    }
+
+    /// Elapsed fraction of the duration, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0. {
+            1.
+        } else {
+            (1. - self.left / self.duration).clamp(0., 1.)
+        }
+    }
+
+    pub fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn get_duration_mut(&mut self) -> &mut f32 {
+        &mut self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_and_reset() {
+        let mut t = FrameTimer::new(3.);
+        assert!(!t.done());
+        t.tick(1.5);
+        assert!(!t.done());
+        assert_eq!(t.progress(), 0.5);
+        t.tick(1.5);
+        assert!(t.done());
+        t.reset();
+        assert!(!t.done());
+        assert_eq!(t.get_duration(), 3.);
+    }
+}
@@ -0,0 +1,35 @@
+use std::{env, process, thread, time::Duration};
+
+use rust_tetris_core::net::DEFAULT_RELAY_ADDR;
+use rust_tetris_relay::headless::HeadlessClient;
+
+/// Ticks between two hard drops, about two pieces per second
+const DROP_INTERVAL: u32 = 30;
+const TICK: Duration = Duration::from_micros(16_667);
+
+/// Usage: `headless [ROOM] [ADDR]`, plays one match and prints its outcome.
+fn main() {
+    let mut args = env::args().skip(1);
+    let room = args.next().unwrap_or_else(|| "1".to_string());
+    let addr = args
+        .next()
+        .unwrap_or_else(|| DEFAULT_RELAY_ADDR.to_string());
+
+    let result = HeadlessClient::connect(&addr, &room, DROP_INTERVAL).and_then(|mut client| {
+        println!("joined room {} on {}", room, addr);
+        while client.outcome.is_none() {
+            client.step()?;
+            thread::sleep(TICK);
+        }
+        Ok(client.outcome)
+    });
+
+    match result {
+        Ok(Some(outcome)) => println!("{:?}", outcome),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
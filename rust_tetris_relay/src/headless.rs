@@ -0,0 +1,186 @@
+use std::{io, net::ToSocketAddrs};
+
+use rust_tetris_core::{
+    game::{Game, GameConfig, GameEvent},
+    input::{Action, Input},
+    net::Connection,
+    protocol::{Message, Snapshot},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    OpponentLeft,
+}
+
+/// A client without window nor keyboard, hard dropping every piece where it
+/// spawns, to play matches against the relay from tests and scripts.
+pub struct HeadlessClient {
+    connection: Connection,
+    /// Ticks between two hard drops
+    drop_interval: u32,
+    ticks: u32,
+    pub game: Option<Game>,
+    /// Last field received from the opponent
+    pub opponent: Option<Snapshot>,
+    /// Players in the room, counting this one
+    pub players: u32,
+    pub garbage_received: u32,
+    pub outcome: Option<Outcome>,
+}
+
+impl HeadlessClient {
+    /// Joins `room` and tells the relay it is ready to start.
+    pub fn connect(addr: impl ToSocketAddrs, room: &str, drop_interval: u32) -> io::Result<Self> {
+        let mut connection = Connection::connect(addr)?;
+        connection.send(&Message::Join {
+            room: room.to_string(),
+        })?;
+        connection.send(&Message::Ready)?;
+
+        Ok(Self {
+            connection,
+            drop_interval: drop_interval.max(1),
+            ticks: 0,
+            game: None,
+            opponent: None,
+            players: 0,
+            garbage_received: 0,
+            outcome: None,
+        })
+    }
+
+    /// Handles the messages received since the last step, then runs one tick
+    /// of the game once the match started.
+    pub fn step(&mut self) -> io::Result<()> {
+        for message in self.connection.poll()? {
+            self.handle(message)?;
+        }
+        if self.outcome.is_some() {
+            return Ok(());
+        }
+
+        let Some(game) = &mut self.game else { return Ok(()) };
+        self.ticks += 1;
+        let input = if self.ticks.is_multiple_of(self.drop_interval) {
+            Input::default().press(Action::HardDrop)
+        } else {
+            Input::default()
+        };
+        game.tick(1., &input);
+
+        let mut field_changed = false;
+        for event in game.drain_events() {
+            match event {
+                GameEvent::GarbageSent { rows } => {
+                    self.connection.send(&Message::Garbage { rows })?
+                }
+                GameEvent::Locked(_)
+                | GameEvent::GarbageInserted { .. }
+                | GameEvent::PieceSpawned(_) => field_changed = true,
+                _ => {}
+            }
+        }
+
+        if field_changed {
+            self.connection
+                .send(&Message::Snapshot(Snapshot::from_game(game)))?;
+        }
+        if game.is_over() {
+            self.connection.send(&Message::TopOut)?;
+            self.outcome = Some(Outcome::Lost);
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, message: Message) -> io::Result<()> {
+        match message {
+            Message::Joined { players } => self.players = players,
+            Message::RoomFull => return Err(io::Error::other("room is full")),
+            Message::Start { seed } => {
+                self.game = Some(Game::new(GameConfig::default(), Some(seed)));
+                self.opponent = None;
+                self.garbage_received = 0;
+                self.outcome = None;
+                self.ticks = 0;
+            }
+            Message::Garbage { rows } => {
+                if let Some(game) = &mut self.game {
+                    game.queue_garbage(rows);
+                    self.garbage_received += rows;
+                }
+            }
+            Message::Snapshot(snapshot) => self.opponent = Some(snapshot),
+            Message::TopOut if self.game.is_some() && self.outcome.is_none() => {
+                self.outcome = Some(Outcome::Won)
+            }
+            Message::Left if self.outcome.is_none() => self.outcome = Some(Outcome::OpponentLeft),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relay::Relay;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn test_match_on_localhost() {
+        let mut relay = Relay::bind("127.0.0.1:0").unwrap();
+        let addr = relay.local_addr().unwrap();
+        thread::spawn(move || relay.run());
+
+        // the fast dropper tops out long before the slow one
+        let mut fast = HeadlessClient::connect(addr, "test", 2).unwrap();
+        let mut slow = HeadlessClient::connect(addr, "test", 30).unwrap();
+
+        for _ in 0..10_000 {
+            fast.step().unwrap();
+            slow.step().unwrap();
+            if fast.outcome.is_some() && slow.outcome.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(fast.players, 2);
+        assert_eq!(fast.outcome, Some(Outcome::Lost));
+        assert_eq!(slow.outcome, Some(Outcome::Won));
+
+        let field = slow.opponent.as_ref().unwrap().board();
+        assert!((0..field.cols).any(|col| field.is_set(0, col)));
+    }
+
+    #[test]
+    fn test_opponent_leaving() {
+        let mut relay = Relay::bind("127.0.0.1:0").unwrap();
+        let addr = relay.local_addr().unwrap();
+        thread::spawn(move || relay.run());
+
+        let mut a = HeadlessClient::connect(addr, "test", 10).unwrap();
+        let mut b = HeadlessClient::connect(addr, "test", 10).unwrap();
+        for _ in 0..1000 {
+            a.step().unwrap();
+            b.step().unwrap();
+            if a.game.is_some() && b.game.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        drop(b);
+
+        for _ in 0..1000 {
+            a.step().unwrap();
+            if a.outcome.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(a.outcome, Some(Outcome::OpponentLeft));
+    }
+}
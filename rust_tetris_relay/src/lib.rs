@@ -0,0 +1,3 @@
+//! Relay server of online versus matches, with a headless client to test it.
+pub mod headless;
+pub mod relay;
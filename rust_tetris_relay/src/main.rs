@@ -0,0 +1,25 @@
+use std::{env, process};
+
+use rust_tetris_core::net::DEFAULT_RELAY_ADDR;
+use rust_tetris_relay::relay::Relay;
+
+/// Usage: `rust_tetris_relay [ADDR]`
+fn main() {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_RELAY_ADDR.to_string());
+
+    let mut relay = match Relay::bind(&addr) {
+        Ok(relay) => relay,
+        Err(e) => {
+            eprintln!("cannot listen on {}: {}", addr, e);
+            process::exit(1);
+        }
+    };
+    println!("relay listening on {}", addr);
+
+    if let Err(e) = relay.run() {
+        eprintln!("relay stopped: {}", e);
+        process::exit(1);
+    }
+}
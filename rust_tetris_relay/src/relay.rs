@@ -0,0 +1,333 @@
+use std::{
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rust_tetris_core::{net::Connection, protocol::Message};
+
+/// Players of a room
+const ROOM_SIZE: usize = 2;
+/// Pause between two polls of the clients
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+/// Too many open files, in the process or the system, the same on Linux and macOS
+const EMFILE: i32 = 24;
+const ENFILE: i32 = 23;
+
+struct Client {
+    connection: Connection,
+    room: Option<String>,
+    /// Waiting for the next match of the room to start
+    ready: bool,
+}
+
+/// Pairs clients in rooms, starts their matches with a common seed and
+/// forwards their garbage, fields and top outs to each other.
+pub struct Relay {
+    listener: TcpListener,
+    clients: Vec<Client>,
+    matches: u64,
+}
+
+impl Relay {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+            matches: 0,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves clients until the listener fails for good.
+    pub fn run(&mut self) -> io::Result<()> {
+        loop {
+            self.poll()?;
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Accepts new clients and handles the messages received since the last poll.
+    pub fn poll(&mut self) -> io::Result<()> {
+        self.accept()?;
+
+        let mut i = 0;
+        while i < self.clients.len() {
+            match self.clients[i].connection.poll() {
+                Ok(messages) => {
+                    for message in messages {
+                        self.handle(i, message);
+                    }
+                    i += 1;
+                }
+                Err(_) => self.disconnect(i),
+            }
+        }
+        Ok(())
+    }
+
+    fn accept(&mut self) -> io::Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    // a client gone before being set up is not worth stopping for
+                    let Ok(connection) = Connection::from_stream(stream) else { continue };
+                    self.clients.push(Client {
+                        connection,
+                        room: None,
+                        ready: false,
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                // the rooms keep playing, accepting is tried again on the next poll
+                Err(e) if is_transient(&e) => {
+                    eprintln!("cannot accept a client: {}", e);
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn handle(&mut self, i: usize, message: Message) {
+        match message {
+            Message::Join { room } => self.join(i, room),
+            Message::Ready => self.ready(i),
+            Message::Garbage { .. } | Message::Snapshot(_) | Message::TopOut => {
+                for j in self.opponents(i) {
+                    self.send(j, &message);
+                }
+            }
+            // only the relay sends the others
+            _ => {}
+        }
+    }
+
+    fn join(&mut self, i: usize, room: String) {
+        if self.clients[i].room.is_some() {
+            return;
+        }
+
+        let members = self.members(&room);
+        if members.len() >= ROOM_SIZE {
+            self.send(i, &Message::RoomFull);
+            return;
+        }
+
+        self.clients[i].room = Some(room);
+        let joined = Message::Joined {
+            players: members.len() as u32 + 1,
+        };
+        for j in members.into_iter().chain([i]) {
+            self.send(j, &joined);
+        }
+    }
+
+    fn ready(&mut self, i: usize) {
+        let Some(room) = self.clients[i].room.clone() else { return };
+        self.clients[i].ready = true;
+
+        let members = self.members(&room);
+        if members.len() < ROOM_SIZE || !members.iter().all(|&j| self.clients[j].ready) {
+            return;
+        }
+
+        let start = Message::Start {
+            seed: self.next_seed(),
+        };
+        for j in members {
+            self.clients[j].ready = false;
+            self.send(j, &start);
+        }
+    }
+
+    fn disconnect(&mut self, i: usize) {
+        let client = self.clients.remove(i);
+        let Some(room) = client.room else { return };
+
+        for j in self.members(&room) {
+            self.clients[j].ready = false;
+            self.send(j, &Message::Left);
+        }
+    }
+
+    fn members(&self, room: &str) -> Vec<usize> {
+        (0..self.clients.len())
+            .filter(|&j| self.clients[j].room.as_deref() == Some(room))
+            .collect()
+    }
+
+    fn opponents(&self, i: usize) -> Vec<usize> {
+        let Some(room) = &self.clients[i].room else { return vec![] };
+        self.members(room).into_iter().filter(|&j| j != i).collect()
+    }
+
+    /// Failures are noticed when polling the client next.
+    fn send(&mut self, i: usize, message: &Message) {
+        let _ = self.clients[i].connection.send(message);
+    }
+
+    fn next_seed(&mut self) -> u64 {
+        self.matches += 1;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+
+        nanos ^ self.matches.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+}
+
+/// Accept errors caused by a single client or by a shortage of resources,
+/// rather than by the listener.
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionReset
+            | ErrorKind::Interrupted
+            | ErrorKind::TimedOut
+            | ErrorKind::OutOfMemory
+    ) || matches!(e.raw_os_error(), Some(EMFILE | ENFILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relay() -> Relay {
+        Relay::bind("127.0.0.1:0").unwrap()
+    }
+
+    fn join(relay: &mut Relay, room: &str) -> Connection {
+        let mut client = Connection::connect(relay.local_addr().unwrap()).unwrap();
+        client
+            .send(&Message::Join {
+                room: room.to_string(),
+            })
+            .unwrap();
+        client
+    }
+
+    /// Polls the relay until `client` received `count` messages.
+    fn receive(relay: &mut Relay, client: &mut Connection, count: usize) -> Vec<Message> {
+        let mut messages = Vec::new();
+        for _ in 0..1000 {
+            relay.poll().unwrap();
+            messages.extend(client.poll().unwrap());
+            if messages.len() >= count {
+                break;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        messages
+    }
+
+    #[test]
+    fn test_transient_accept_errors() {
+        assert!(is_transient(&ErrorKind::ConnectionAborted.into()));
+        assert!(is_transient(&ErrorKind::Interrupted.into()));
+        assert!(is_transient(&io::Error::from_raw_os_error(EMFILE)));
+        assert!(!is_transient(&ErrorKind::InvalidInput.into()));
+    }
+
+    /// Two clients in `room`, with their join notifications received.
+    fn pair(relay: &mut Relay, room: &str) -> (Connection, Connection) {
+        let mut a = join(relay, room);
+        receive(relay, &mut a, 1);
+        let mut b = join(relay, room);
+        receive(relay, &mut b, 1);
+        receive(relay, &mut a, 1);
+        (a, b)
+    }
+
+    #[test]
+    fn test_join_counts_players() {
+        let mut relay = relay();
+
+        let mut a = join(&mut relay, "room");
+        assert_eq!(
+            receive(&mut relay, &mut a, 1),
+            vec![Message::Joined { players: 1 }]
+        );
+
+        let mut b = join(&mut relay, "room");
+        assert_eq!(
+            receive(&mut relay, &mut b, 1),
+            vec![Message::Joined { players: 2 }]
+        );
+        assert_eq!(
+            receive(&mut relay, &mut a, 1),
+            vec![Message::Joined { players: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_third_player_is_refused() {
+        let mut relay = relay();
+        let _pair = pair(&mut relay, "room");
+
+        let mut c = join(&mut relay, "room");
+        assert_eq!(receive(&mut relay, &mut c, 1), vec![Message::RoomFull]);
+
+        let mut d = join(&mut relay, "other");
+        assert_eq!(
+            receive(&mut relay, &mut d, 1),
+            vec![Message::Joined { players: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_start_once_both_ready() {
+        let mut relay = relay();
+        let (mut a, mut b) = pair(&mut relay, "room");
+
+        a.send(&Message::Ready).unwrap();
+        relay.poll().unwrap();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(receive(&mut relay, &mut a, 0), vec![]);
+
+        b.send(&Message::Ready).unwrap();
+        let start_a = receive(&mut relay, &mut a, 1);
+        let start_b = receive(&mut relay, &mut b, 1);
+
+        assert!(matches!(start_a[..], [Message::Start { .. }]));
+        assert_eq!(start_a, start_b);
+    }
+
+    #[test]
+    fn test_forwards_to_the_opponent() {
+        let mut relay = relay();
+        let (mut a, mut b) = pair(&mut relay, "room");
+
+        a.send(&Message::Garbage { rows: 3 }).unwrap();
+        a.send(&Message::TopOut).unwrap();
+
+        assert_eq!(
+            receive(&mut relay, &mut b, 2),
+            vec![Message::Garbage { rows: 3 }, Message::TopOut]
+        );
+        assert_eq!(receive(&mut relay, &mut a, 0), vec![]);
+    }
+
+    #[test]
+    fn test_leaving_notifies_the_opponent() {
+        let mut relay = relay();
+        let (a, mut b) = pair(&mut relay, "room");
+        drop(a);
+
+        assert_eq!(receive(&mut relay, &mut b, 1), vec![Message::Left]);
+
+        let mut c = join(&mut relay, "room");
+        assert_eq!(
+            receive(&mut relay, &mut c, 1),
+            vec![Message::Joined { players: 2 }]
+        );
+    }
+}
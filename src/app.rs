@@ -1,18 +1,39 @@
+use std::cell::Cell;
+
 use lazy_static::lazy_static;
-use macroquad::{logging, prelude::*};
-use rust_tetris_core::{
-    board::Board,
-    holder::{HoldPiece, Swappable},
-    pieces::{Piece, PieceWithPosition},
+use macroquad::{
+    prelude::*,
+    window::{get_internal_gl, InternalGlContext},
 };
 
 use crate::{
     asset::Assets,
-    constants::MENU_POS,
-    game_data::{load_user_settings, save_user_settings, GameData, GameState, MoveState},
+    constants::{
+        COUNTDOWN_FRAMES, MENU_POS, RELAY_ADDR_MAX_LEN, REPLAY_FAST_FORWARD, STATS_BEST_TIMES,
+        TREND_GAMES, VERSUS_MENU_POS,
+    },
+    focus,
+    game_data::{
+        history_json, load_history, load_records, load_replays, load_user_settings, save_history,
+        save_records, save_replays, save_user_settings, Command, Conflict, GameData, GameState,
+    },
+    gamepad,
     menu::*,
-    renderer::{text, Renderer},
+    online::{Online, OnlineStatus},
+    renderer::{score::time_to_string, text, Renderer},
     sound::SoundAssets,
+    versus::Versus,
+};
+use rust_tetris_core::{
+    fumen::{self, FumenError, Page},
+    game::GameEvent,
+    history::{self, GameRecord},
+    input::Input,
+    lock::LockDelayPolicy,
+    mode::GameMode,
+    piece_bag::RandomizerType,
+    rotation::RotationSystemType,
+    timer::FrameTimer,
 };
 
 pub struct App {
@@ -20,6 +41,17 @@ pub struct App {
     game_data: GameData,
     sounds: SoundAssets,
     menu_ctx: MenuCtx,
+    pause_ctx: MenuCtx,
+    countdown: FrameTimer,
+    replay_paused: bool,
+    /// Why the last pasted fumen could not be played
+    fumen_error: Option<&'static str>,
+    /// Why the relay could not be joined
+    online_error: Option<&'static str>,
+    /// Confirms the history was copied to the clipboard
+    export_notice: Option<&'static str>,
+    /// The window lost the focus since the last frame
+    focus_lost: bool,
     time_elapsed: f32,
     assets: Assets,
 }
@@ -33,12 +65,22 @@ impl App {
         let mut game_data = GameData::new();
 
         load_user_settings(&mut game_data);
+        load_records(&mut game_data);
+        load_replays(&mut game_data);
+        load_history(&mut game_data);
 
         App {
             game_data,
             renderer,
             sounds,
             menu_ctx: MenuCtx::new(),
+            pause_ctx: MenuCtx::new(),
+            countdown: FrameTimer::new(COUNTDOWN_FRAMES),
+            replay_paused: false,
+            fumen_error: None,
+            online_error: None,
+            export_notice: None,
+            focus_lost: false,
             time_elapsed: 0.,
             assets,
         }
@@ -47,6 +89,8 @@ impl App {
     pub async fn run(&mut self) {
         loop {
             self.time_elapsed += get_frame_time();
+            gamepad::update(self.game_data.pad_deadzone);
+            self.focus_lost = focus::lost();
 
             self.tick();
             self.renderer.render(&self.game_data);
@@ -55,10 +99,21 @@ impl App {
                 GameState::Menu => {
                     self.draw_menu();
                 }
+                GameState::Paused => {
+                    self.draw_pause_menu();
+                }
+                GameState::Resuming => {
+                    self.draw_countdown();
+                }
                 GameState::GameOver => {
                     self.draw_gameover();
                 }
-                _ => (),
+                GameState::Replay => {
+                    self.draw_replay_status();
+                }
+                GameState::Playing => {
+                    self.draw_online_status();
+                }
             }
 
             next_frame().await;
@@ -66,78 +121,712 @@ impl App {
     }
 
     fn tick(&mut self) {
+        if self.game_data.state != GameState::Menu && self.game_data.pressed(Command::Stats) {
+            self.game_data.show_stats = !self.game_data.show_stats;
+            save_user_settings(&self.game_data);
+        }
+
         match self.game_data.state {
-            GameState::Menu => {}
+            GameState::Menu => {
+                self.game_data.start_demo();
+                if let Some(demo) = &mut self.game_data.demo {
+                    demo.advance(relative_frame());
+                }
+            }
             GameState::Playing => {
-                if self.game_data.keybind.restart.is_pressed() {
+                // online matches cannot wait for one side, nor restart on their own
+                if self.game_data.online.is_some() {
+                    self.online_tick();
+                    return;
+                }
+
+                if self.game_data.pressed(Command::Pause) || self.focus_lost {
+                    self.game_pause();
+                    return;
+                }
+
+                if self.game_data.pressed(Command::Restart) {
                     self.game_restart();
                     return;
                 }
 
-                if self.game_data.keybind.escape.is_pressed() {
+                if self.game_data.pressed(Command::Escape) {
                     self.game_stop();
                     return;
                 }
 
-                self.game_data.time += get_frame_time();
+                if self.game_data.versus.is_some() {
+                    self.versus_tick();
+                    return;
+                }
 
-                if self.game_data.curr_piece.is_none() {
-                    self.game_data.spawn_delay_timer.tick(relative_frame());
-                    if self.game_data.spawn_delay_timer.done() {
-                        if let Some(p) = self.spawn_piece() {
-                            self.game_data.curr_piece = Some(p);
-                        } else {
-                            self.game_over();
-                        }
-                        self.game_data.spawn_delay_timer.reset();
-                    } else {
-                        self.handle_irs();
+                let input = self.game_data.input();
+                self.game_data.step.advance(relative_frame(), input);
+                while let Some(input) = self.game_data.step.next_tick() {
+                    if let Some(recording) = &mut self.game_data.recording {
+                        recording.record(input);
                     }
-                }
 
-                self.handle_move();
-                self.handle_hold();
-                self.handle_rotate();
-                self.handle_gravity();
-                self.handle_freeze();
+                    if self.game_tick(&input) {
+                        self.game_over();
+                        return;
+                    }
+                    if self.game_data.finesse_trainer && self.game_data.stats.finesse_faults > 0 {
+                        self.game_restart();
+                        return;
+                    }
+                }
+            }
+            GameState::Paused => {
+                if self.game_data.pressed(Command::Pause) {
+                    self.game_resume();
+                }
+            }
+            GameState::Resuming => {
+                self.countdown.tick(relative_frame());
+                if self.countdown.done() {
+                    self.sounds.go.play();
+                    self.game_data.state = GameState::Playing;
+                }
             }
             GameState::GameOver => {
-                if self.game_data.keybind.restart.is_pressed() {
+                if self.game_data.pressed(Command::Restart) {
                     self.game_restart();
                     return;
                 }
 
-                if self.game_data.keybind.escape.is_pressed() {
+                if self.game_data.pressed(Command::Escape) {
+                    self.game_stop();
+                    return;
+                }
+            }
+            GameState::Replay => {
+                if self.game_data.pressed(Command::Escape) {
                     self.game_stop();
                     return;
                 }
+
+                if self.game_data.pressed(Command::Restart) {
+                    self.game_data.restart_replay();
+                    return;
+                }
+
+                if self.game_data.pressed(Command::Pause) {
+                    self.replay_paused = !self.replay_paused;
+                }
+                if self.replay_paused {
+                    return;
+                }
+
+                let speed = if is_key_down(KeyCode::Right) {
+                    REPLAY_FAST_FORWARD
+                } else {
+                    1.
+                };
+                self.game_data
+                    .step
+                    .advance(relative_frame() * speed, Input::default());
+                while self.game_data.step.next_tick().is_some() {
+                    let Some(player) = &mut self.game_data.player else { return };
+                    let Some(input) = player.next_input() else { return };
+
+                    if self.game_tick(&input) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Runs one tick of the game, returns whether it is over.
+    fn game_tick(&mut self, input: &Input) -> bool {
+        self.game_data.stats.handle_input(input);
+
+        let Some(game) = &mut self.game_data.game else { return true };
+        self.game_data.prev_piece_pos = game.curr_piece.as_ref().map(|p| (p.row(), p.col()));
+        game.tick(self.game_data.step.tick_frames(), input);
+
+        for event in game.drain_events() {
+            if let GameEvent::PieceSpawned(_) | GameEvent::Hold = event {
+                self.game_data.prev_piece_pos = None;
             }
+            self.sounds.handle_event(&event);
+            self.renderer.handle_event(&event);
+            self.game_data.stats.handle_event(&event);
+        }
+
+        game.is_over()
+    }
+
+    /// Runs the due ticks of both versus players, the match is over once one tops out.
+    fn versus_tick(&mut self) {
+        let inputs = [self.game_data.input(), self.game_data.keybind_p2.input()];
+        let Some(versus) = &mut self.game_data.versus else { return };
+
+        let sounds = &self.sounds;
+        versus.advance(relative_frame(), inputs, |_, event| {
+            sounds.handle_event(event)
+        });
+
+        if versus.is_over() {
+            self.game_data.state = GameState::GameOver;
+        }
+    }
+
+    /// Runs the due ticks of the local player while the match is on, and
+    /// follows the relay while waiting for it.
+    fn online_tick(&mut self) {
+        if self.game_data.pressed(Command::Escape) {
+            self.game_stop();
+            return;
+        }
+
+        let input = self.game_data.input();
+        let Some(online) = &mut self.game_data.online else { return };
+        let sounds = &self.sounds;
+        online.advance(relative_frame(), input, |event| sounds.handle_event(event));
+
+        if online.is_over() {
+            self.game_data.state = GameState::GameOver;
         }
     }
 
     pub fn draw_gameover(&self) {
+        if let Some(versus) = &self.game_data.versus {
+            self.draw_versus_over(versus);
+            return;
+        }
+        if let Some(online) = &self.game_data.online {
+            self.draw_online_over(online);
+            return;
+        }
+
+        let ctx = &mut MenuCtx::new();
+        let results = self.results();
+        let mut menu = Menu::new(ctx, &self.renderer.text_renderer);
+
+        if results.is_empty() {
+            menu.add_widget(Label::new("GAME OVER!", text::Color::Yellow));
+        } else {
+            menu.add_widget(Label::new("FINISHED!", text::Color::Yellow));
+            menu.add_widget(Margin);
+            for line in results.iter() {
+                menu.add_widget(Label::new(line, text::Color::White));
+            }
+        }
+
+        if self.game_data.new_record {
+            menu.add_widget(Margin);
+            menu.add_widget(Label::new("NEW BEST!", text::Color::Cream));
+        }
+
+        menu.draw(*MENU_POS);
+    }
+
+    fn draw_versus_over(&self, versus: &Versus) {
+        let result = match versus.winner() {
+            Some(winner) => format!("{} WINS!", versus.player_name(winner)),
+            None => "DRAW!".to_string(),
+        };
+
+        let ctx = &mut MenuCtx::new();
+        let mut menu = Menu::new(ctx, &self.renderer.text_renderer);
+        menu.add_widget(Label::new(&result, text::Color::Yellow));
+
+        menu.draw(VERSUS_MENU_POS);
+    }
+
+    fn draw_online_over(&self, online: &Online) {
+        let result = match online.status {
+            OnlineStatus::Won => "YOU WIN!",
+            OnlineStatus::Lost => "YOU LOSE!",
+            OnlineStatus::Closed(reason) => reason,
+            OnlineStatus::Waiting | OnlineStatus::Playing => return,
+        };
+
+        let ctx = &mut MenuCtx::new();
+        let mut menu = Menu::new(ctx, &self.renderer.text_renderer);
+        menu.add_widget(Label::new(result, text::Color::Yellow));
+
+        menu.draw(VERSUS_MENU_POS);
+    }
+
+    /// Shown until the relay starts the match.
+    pub fn draw_online_status(&self) {
+        let Some(online) = &self.game_data.online else { return };
+        if online.status != OnlineStatus::Waiting {
+            return;
+        }
+        let players = format!("PLAYERS {}/2", online.players);
+
+        let ctx = &mut MenuCtx::new();
+        let mut menu = Menu::new(ctx, &self.renderer.text_renderer);
+        menu.add_widget(Label::new("WAITING", text::Color::Yellow));
+        menu.add_widget(Label::new(&players, text::Color::White));
+
+        menu.draw(VERSUS_MENU_POS);
+    }
+
+    pub fn draw_pause_menu(&mut self) {
+        let choice = Cell::new(None);
+        self.pause_ctx.pad_bind = self.game_data.pad_bind;
+        let mut menu = Menu::new(&mut self.pause_ctx, &self.renderer.text_renderer);
+
+        menu.add_widget(Label::new("PAUSED", text::Color::Yellow));
+        menu.add_widget(Margin);
+        menu.add_widget(Button::new("RESUME", || {
+            choice.set(Some(PauseChoice::Resume))
+        }));
+        menu.add_widget(Button::new("RESTART", || {
+            choice.set(Some(PauseChoice::Restart))
+        }));
+        menu.add_widget(Button::new("QUIT", || choice.set(Some(PauseChoice::Quit))));
+
+        menu.draw(*MENU_POS);
+        let menu_len = menu.len();
+        drop(menu);
+
+        self.pause_ctx.move_pointer(menu_len);
+        // skip the label and the margin
+        self.pause_ctx.curr_pointer = self.pause_ctx.curr_pointer.max(2);
+
+        if self.pause_ctx.is_pressed(MenuKey::Back) {
+            choice.set(Some(PauseChoice::Resume));
+        }
+
+        match choice.get() {
+            Some(PauseChoice::Resume) => self.game_resume(),
+            Some(PauseChoice::Restart) => self.game_restart(),
+            Some(PauseChoice::Quit) => self.game_stop(),
+            None => {}
+        }
+    }
+
+    pub fn draw_countdown(&self) {
+        let ctx = &mut MenuCtx::new();
+        let mut menu = Menu::new(ctx, &self.renderer.text_renderer);
+
+        menu.add_widget(Label::new("READY", text::Color::Yellow));
+        menu.draw(*MENU_POS);
+    }
+
+    pub fn draw_replay_status(&self) {
         let ctx = &mut MenuCtx::new();
         let mut menu = Menu::new(ctx, &self.renderer.text_renderer);
 
-        menu.add_widget(Label::new("GAME OVER!", text::Color::Yellow));
+        let done = match (&self.game_data.player, &self.game_data.game) {
+            (Some(player), Some(game)) => player.is_done() || game.is_over(),
+            _ => true,
+        };
+        let status = if done {
+            "END"
+        } else if self.replay_paused {
+            "PAUSED"
+        } else if is_key_down(KeyCode::Right) {
+            "FAST"
+        } else {
+            ""
+        };
+
+        menu.add_widget(Label::new("REPLAY", text::Color::Yellow));
+        menu.add_widget(Label::new(status, text::Color::White));
         menu.draw(*MENU_POS);
     }
 
+    /// Lines of the results screen, empty unless the game reached its goal.
+    fn results(&self) -> Vec<String> {
+        let Some(game) = &self.game_data.game else {
+            return vec![];
+        };
+        if !game.is_finished() {
+            return vec![];
+        }
+
+        let stats = &self.game_data.stats;
+        let mut lines = vec![
+            format!("{:<7}{:>9}", "TIME", time_to_string(game.time)),
+            format!("{:<7}{:>9}", "PIECES", stats.pieces),
+            format!("{:<7}{:>9.2}", "PPS", stats.pps(game.time)),
+            format!("{:<7}{:>9.2}", "KPP", stats.kpp()),
+            format!("{:<7}{:>9}", "FAULTS", stats.finesse_faults),
+        ];
+
+        match game.config.mode {
+            GameMode::Sprint { lines: goal } => {
+                if let Some(best) = self.game_data.records.sprint_best(goal) {
+                    lines.push(format!("{:<7}{:>9}", "BEST", time_to_string(best.time)));
+                }
+            }
+            GameMode::CheeseRace { lines: goal } => {
+                if let Some(best) = self.game_data.records.cheese_best(goal) {
+                    lines.push(format!("{:<7}{:>9}", "BEST", time_to_string(best.time)));
+                }
+            }
+            GameMode::Marathon { .. } => {
+                lines.insert(
+                    0,
+                    format!("{:<7}{:>9}", "SCORE", game.score_updater.get_score()),
+                );
+            }
+            GameMode::Endless => {}
+        }
+
+        lines
+    }
+
     pub fn draw_menu(&mut self) {
         let ctx = &mut self.menu_ctx;
-
-        let mut next_state = ctx.curr_state();
+        ctx.pad_bind = self.game_data.pad_bind;
+        let was_modifying = ctx.modifying;
+        let was_typing = ctx.typing;
+
+        let curr_state = ctx.curr_state();
+        let next_state = Cell::new(curr_state);
+        // modes are started once the menu releases their settings
+        let start = Cell::new(false);
+        let watch = Cell::new(None);
+        let paste = Cell::new(false);
+        let connect = Cell::new(false);
+        let finesse = Cell::new(false);
+        let export = Cell::new(None);
+        let best_label;
+        let replay_labels: Vec<String>;
+        let stats_labels: Vec<String>;
+        let best_labels: Vec<String>;
+        let trend_labels: Vec<String>;
+        let conflict_labels: Vec<String>;
+        // widgets past it are warnings the pointer skips
+        let mut selectable_len = None;
         let mut menu = Menu::new(ctx, &self.renderer.text_renderer);
-        match next_state {
+        match curr_state {
             MenuState::Main => {
                 menu.add_widget(Button::new("START", || {
+                    self.game_data.mode = GameMode::Endless;
                     self.game_data.start();
                 }));
+                menu.add_widget(Button::new("SPRINT", || {
+                    next_state.set(MenuState::Sprint);
+                }));
+                menu.add_widget(Button::new("MARATHON", || {
+                    next_state.set(MenuState::Marathon);
+                }));
+                menu.add_widget(Button::new("CHEESE", || {
+                    next_state.set(MenuState::CheeseRace);
+                }));
+                menu.add_widget(Button::new("VERSUS", || {
+                    next_state.set(MenuState::Versus);
+                }));
+                menu.add_widget(Button::new("ONLINE", || {
+                    next_state.set(MenuState::Online);
+                }));
+                menu.add_widget(Button::new("FINESSE", || {
+                    finesse.set(true);
+                }));
+                menu.add_widget(Button::new("FUMEN", || {
+                    paste.set(true);
+                }));
+                menu.add_widget(Button::new("REPLAYS", || {
+                    next_state.set(MenuState::Replays);
+                }));
+                menu.add_widget(Button::new("STATS", || {
+                    self.export_notice = None;
+                    next_state.set(MenuState::Stats);
+                }));
                 menu.add_widget(Button::new("SETTINGS", || {
-                    next_state = MenuState::Settings;
+                    next_state.set(MenuState::Settings);
+                }));
+                if let Some(error) = self.fumen_error {
+                    menu.add_widget(Label::new(error, text::Color::Red));
+                }
+            }
+            MenuState::Replays => {
+                replay_labels = self
+                    .game_data
+                    .replays
+                    .iter()
+                    .map(|replay| {
+                        let time = time_to_string(replay.duration());
+                        format!("{:<8}{:>10}", mode_label(replay.config.mode), time)
+                    })
+                    .collect();
+
+                if replay_labels.is_empty() {
+                    menu.add_widget(Label::new("NO REPLAYS", text::Color::Navyblue));
+                }
+                for (i, label) in replay_labels.iter().enumerate() {
+                    let watch = &watch;
+                    menu.add_widget(Button::new(label, move || watch.set(Some(i))));
+                }
+            }
+            MenuState::Stats => {
+                let career = &self.game_data.history;
+                let totals = career.totals();
+                stats_labels = vec![
+                    format!("{:<8}{:>10}", "GAMES", totals.games),
+                    format!("{:<8}{:>10}", "FINISHED", totals.finished),
+                    format!("{:<8}{:>10}", "PLAYED", time_to_string(totals.time)),
+                    format!("{:<8}{:>10}", "LINES", totals.lines),
+                ];
+                best_labels = career
+                    .best_times()
+                    .iter()
+                    .take(STATS_BEST_TIMES)
+                    .map(|&(mode, time)| {
+                        format!("{:<8}{:>10}", mode_label(mode), time_to_string(time))
+                    })
+                    .collect();
+
+                // averages of the last games, with the change from the games before them
+                let (recent, before) = career.recent(TREND_GAMES);
+                let trend = |label: &str, value: fn(&GameRecord) -> f32| {
+                    let average = history::average(recent, value).unwrap_or(0.);
+                    let change = history::average(before, value)
+                        .map(|before| format!("{:+.2}", average - before))
+                        .unwrap_or_default();
+                    format!("{:<6}{:>6.2}{:>6}", label, average, change)
+                };
+                trend_labels = vec![
+                    trend("PPS", |game| game.pps),
+                    trend("LINES", |game| game.lines as f32),
+                ];
+
+                menu.add_widget(Button::new("EXPORT CSV", || {
+                    export.set(Some(ExportFormat::Csv));
+                }));
+                menu.add_widget(Button::new("EXPORT JSON", || {
+                    export.set(Some(ExportFormat::Json));
+                }));
+                if let Some(notice) = self.export_notice {
+                    menu.add_widget(Label::new(notice, text::Color::Cream));
+                }
+
+                menu.add_widget(Margin);
+                for label in &stats_labels {
+                    menu.add_widget(Label::new(label, text::Color::White));
+                }
+                if !best_labels.is_empty() {
+                    menu.add_widget(Margin);
+                    menu.add_widget(Label::new("BEST", text::Color::Yellow));
+                    for label in &best_labels {
+                        menu.add_widget(Label::new(label, text::Color::White));
+                    }
+                }
+                if !recent.is_empty() {
+                    menu.add_widget(Margin);
+                    menu.add_widget(Label::new("LAST GAMES", text::Color::Yellow));
+                    for label in &trend_labels {
+                        menu.add_widget(Label::new(label, text::Color::White));
+                    }
+                }
+            }
+            MenuState::Sprint => {
+                let best = match self
+                    .game_data
+                    .records
+                    .sprint_best(self.game_data.sprint_lines)
+                {
+                    Some(record) => time_to_string(record.time),
+                    None => "--:--:---".to_string(),
+                };
+                best_label = format!("{:<7}{:>9}", "BEST", best);
+
+                menu.add_widget(Selector::new(
+                    "LINES",
+                    &mut self.game_data.sprint_lines,
+                    &SPRINT_LINES_VALUES,
+                    &SPRINT_LINES_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "LOCK",
+                    &mut self.game_data.sprint_lock_delay,
+                    &LOCK_DELAY_VALUES,
+                    &LOCK_DELAY_LABELS,
+                ));
+                menu.add_widget(Label::new(&best_label, text::Color::Navyblue));
+                menu.add_widget(Margin);
+                menu.add_widget(Button::new("START", || {
+                    start.set(true);
+                }));
+            }
+            MenuState::Marathon => {
+                menu.add_widget(Selector::new(
+                    "ENDLESS",
+                    &mut self.game_data.marathon_endless,
+                    &[false, true],
+                    &SWITCH_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "LOCK",
+                    &mut self.game_data.marathon_lock_delay,
+                    &LOCK_DELAY_VALUES,
+                    &LOCK_DELAY_LABELS,
+                ));
+                menu.add_widget(Margin);
+                menu.add_widget(Button::new("START", || {
+                    start.set(true);
+                }));
+            }
+            MenuState::CheeseRace => {
+                let best = match self
+                    .game_data
+                    .records
+                    .cheese_best(self.game_data.cheese_lines)
+                {
+                    Some(record) => time_to_string(record.time),
+                    None => "--:--:---".to_string(),
+                };
+                best_label = format!("{:<7}{:>9}", "BEST", best);
+
+                menu.add_widget(Selector::new(
+                    "LINES",
+                    &mut self.game_data.cheese_lines,
+                    &CHEESE_LINES_VALUES,
+                    &CHEESE_LINES_LABELS,
+                ));
+                menu.add_widget(Label::new(&best_label, text::Color::Navyblue));
+                menu.add_widget(Margin);
+                menu.add_widget(Button::new("START", || {
+                    start.set(true);
+                }));
+            }
+            MenuState::Versus => {
+                menu.add_widget(Selector::new(
+                    "OPPONENT",
+                    &mut self.game_data.versus_bot,
+                    &[false, true],
+                    &OPPONENT_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "BOT PPS",
+                    &mut self.game_data.bot_pps,
+                    &BOT_PPS_VALUES,
+                    &BOT_PPS_LABELS,
+                ));
+                menu.add_widget(Button::new("2P KEYS", || {
+                    next_state.set(MenuState::VersusKeys);
                 }));
+                menu.add_widget(Margin);
+                menu.add_widget(Button::new("START", || {
+                    start.set(true);
+                }));
+            }
+            MenuState::Online => {
+                menu.add_widget(TextField::new(
+                    "RELAY",
+                    &mut self.game_data.relay_addr,
+                    RELAY_ADDR_MAX_LEN,
+                ));
+                menu.add_widget(Selector::new(
+                    "ROOM",
+                    &mut self.game_data.online_room,
+                    &ROOM_VALUES,
+                    &ROOM_LABELS,
+                ));
+                menu.add_widget(Margin);
+                menu.add_widget(Button::new("CONNECT", || {
+                    connect.set(true);
+                }));
+                if let Some(error) = self.online_error {
+                    menu.add_widget(Label::new(error, text::Color::Red));
+                }
+                // browsers cannot open the TCP connections of the relay
+                if cfg!(target_arch = "wasm32") {
+                    menu.add_widget(Label::new("DESKTOP ONLY", text::Color::Navyblue));
+                }
+            }
+            MenuState::VersusKeys => {
+                conflict_labels = conflict_labels_of(
+                    self.game_data
+                        .keybind_p2
+                        .conflicts(&self.game_data.keybind, "P1"),
+                    |key| key_label(key.code()),
+                );
+                let keybind = &mut self.game_data.keybind_p2;
+                menu.add_widget(KeyBind::new(&mut keybind.left, "LEFT"));
+                menu.add_widget(KeyBind::new(&mut keybind.right, "RIGHT"));
+                menu.add_widget(KeyBind::new(&mut keybind.soft_drop, "SOFT DROP"));
+                menu.add_widget(KeyBind::new(&mut keybind.hard_drop, "HARD DROP"));
+                menu.add_widget(KeyBind::new(&mut keybind.rotate_cw, "ROTATE CW"));
+                menu.add_widget(KeyBind::new(&mut keybind.rotate_ccw, "ROTATE CCW"));
+                menu.add_widget(KeyBind::new(&mut keybind.rotate_180, "ROTATE 180"));
+                menu.add_widget(KeyBind::new(&mut keybind.hold, "HOLD"));
+                selectable_len = Some(menu.len());
+                for label in &conflict_labels {
+                    menu.add_widget(Label::new(label, text::Color::Red));
+                }
             }
             MenuState::Settings => {
+                menu.add_widget(Button::new("KEYS", || {
+                    next_state.set(MenuState::Keys);
+                }));
+                menu.add_widget(Button::new("GAMEPAD", || {
+                    next_state.set(MenuState::Gamepad);
+                }));
+
+                menu.add_widget(Margin);
+
+                menu.add_widget(Selector::new(
+                    "DAS",
+                    &mut self.game_data.config.das,
+                    &DAS_VALUES,
+                    &DAS_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "ARR",
+                    &mut self.game_data.config.arr,
+                    &ARR_VALUES,
+                    &ARR_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "SOFTDROP",
+                    &mut self.game_data.config.soft_drop_gravity,
+                    &SOFT_DROP_VALUES,
+                    &SOFT_DROP_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "LINE ARE",
+                    &mut self.game_data.config.line_clear_delay,
+                    &LINE_CLEAR_DELAY_VALUES,
+                    &LINE_CLEAR_DELAY_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "LOCK",
+                    &mut self.game_data.config.lock_delay_policy,
+                    &LOCK_DELAY_VALUES,
+                    &LOCK_DELAY_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "ROTATION",
+                    &mut self.game_data.config.rotation_system,
+                    &ROTATION_SYSTEM_VALUES,
+                    &ROTATION_SYSTEM_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "RANDOM",
+                    &mut self.game_data.config.randomizer,
+                    &RANDOMIZER_VALUES,
+                    &RANDOMIZER_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "TICK",
+                    &mut self.game_data.tick_rate,
+                    &TICK_RATE_VALUES,
+                    &TICK_RATE_LABELS,
+                ));
+
+                menu.add_widget(Margin);
+
+                menu.add_widget(Button::new("BOARD", || {
+                    next_state.set(MenuState::Board);
+                }));
+            }
+            MenuState::Keys => {
+                conflict_labels = conflict_labels_of(
+                    self.game_data
+                        .keybind
+                        .conflicts(&self.game_data.keybind_p2, "P2"),
+                    |key| key_label(key.code()),
+                );
                 menu.add_widget(KeyBind::new(&mut self.game_data.keybind.left, "LEFT"));
                 menu.add_widget(KeyBind::new(&mut self.game_data.keybind.right, "RIGHT"));
                 menu.add_widget(KeyBind::new(
@@ -162,297 +851,275 @@ impl App {
                 ));
                 menu.add_widget(KeyBind::new(&mut self.game_data.keybind.hold, "HOLD"));
                 menu.add_widget(KeyBind::new(&mut self.game_data.keybind.restart, "RESTART"));
+                menu.add_widget(KeyBind::new(&mut self.game_data.keybind.escape, "QUIT"));
+                menu.add_widget(KeyBind::new(&mut self.game_data.keybind.pause, "PAUSE"));
+                menu.add_widget(KeyBind::new(&mut self.game_data.keybind.stats, "STATS"));
+                selectable_len = Some(menu.len());
+                for label in &conflict_labels {
+                    menu.add_widget(Label::new(label, text::Color::Red));
+                }
+            }
+            MenuState::Gamepad => {
+                conflict_labels = conflict_labels_of(self.game_data.pad_bind.conflicts(), |b| {
+                    button_label(b).to_string()
+                });
+                let pad_bind = &mut self.game_data.pad_bind;
+                menu.add_widget(PadBind::new(&mut pad_bind.left, "LEFT"));
+                menu.add_widget(PadBind::new(&mut pad_bind.right, "RIGHT"));
+                menu.add_widget(PadBind::new(&mut pad_bind.soft_drop, "SOFT DROP"));
+                menu.add_widget(PadBind::new(&mut pad_bind.hard_drop, "HARD DROP"));
+                menu.add_widget(PadBind::new(&mut pad_bind.rotate_cw, "ROTATE CW"));
+                menu.add_widget(PadBind::new(&mut pad_bind.rotate_ccw, "ROTATE CCW"));
+                menu.add_widget(PadBind::new(&mut pad_bind.rotate_180, "ROTATE 180"));
+                menu.add_widget(PadBind::new(&mut pad_bind.hold, "HOLD"));
+                menu.add_widget(PadBind::new(&mut pad_bind.restart, "RESTART"));
+                menu.add_widget(PadBind::new(&mut pad_bind.escape, "QUIT"));
+                menu.add_widget(PadBind::new(&mut pad_bind.pause, "PAUSE"));
+                menu.add_widget(PadBind::new(&mut pad_bind.stats, "STATS"));
 
                 menu.add_widget(Margin);
 
+                menu.add_widget(PadBind::new(&mut pad_bind.menu_up, "MENU UP"));
+                menu.add_widget(PadBind::new(&mut pad_bind.menu_down, "MENU DOWN"));
+                menu.add_widget(PadBind::new(&mut pad_bind.menu_left, "MENU LEFT"));
+                menu.add_widget(PadBind::new(&mut pad_bind.menu_right, "MENU RIGHT"));
+                menu.add_widget(PadBind::new(&mut pad_bind.menu_confirm, "CONFIRM"));
+                menu.add_widget(PadBind::new(&mut pad_bind.menu_back, "BACK"));
                 menu.add_widget(Selector::new(
-                    "DAS",
-                    self.game_data.das_timer.get_duration_mut(),
-                    &DAS_VALUES,
-                    &DAS_LABELS,
+                    "DEADZONE",
+                    &mut self.game_data.pad_deadzone,
+                    &PAD_DEADZONE_VALUES,
+                    &PAD_DEADZONE_LABELS,
                 ));
+                selectable_len = Some(menu.len());
+                for label in &conflict_labels {
+                    menu.add_widget(Label::new(label, text::Color::Red));
+                }
+            }
+            MenuState::Board => {
+                let board_size = &mut self.game_data.config.board_size;
                 menu.add_widget(Selector::new(
-                    "ARR",
-                    &mut self.game_data.arr,
-                    &ARR_VALUES,
-                    &ARR_LABELS,
+                    "WIDTH",
+                    &mut board_size.cols,
+                    &BOARD_COLS_VALUES,
+                    &BOARD_COLS_LABELS,
                 ));
                 menu.add_widget(Selector::new(
-                    "SOFTDROP",
-                    &mut self.game_data.soft_drop_gravity,
-                    &SOFT_DROP_VALUES,
-                    &SOFT_DROP_LABELS,
+                    "HEIGHT",
+                    &mut board_size.visible_rows,
+                    &VISIBLE_ROWS_VALUES,
+                    &VISIBLE_ROWS_LABELS,
+                ));
+                menu.add_widget(Selector::new(
+                    "BUFFER",
+                    &mut board_size.buffer_rows,
+                    &BUFFER_ROWS_VALUES,
+                    &BUFFER_ROWS_LABELS,
                 ));
             }
         }
 
         menu.draw(*MENU_POS);
-        let menu_len = menu.len();
+        let menu_len = selectable_len.unwrap_or(menu.len());
         drop(menu);
 
-        ctx.push_state(next_state);
-
-        if get_last_key_pressed().is_some() && ctx.modifying {
-            let k = get_last_key_pressed().unwrap();
-            if k != KeyCode::Enter {
-                ctx.modifying = false;
-            }
-        } else if is_key_pressed(KeyCode::Down) {
-            ctx.curr_pointer += 1;
-        } else if is_key_pressed(KeyCode::Up) {
-            ctx.curr_pointer -= 1;
-        } else if is_key_pressed(KeyCode::Escape) {
-            if let MenuState::Settings = ctx.curr_state() {
-                save_user_settings(&self.game_data);
-            }
-            ctx.pop_state();
+        ctx.push_state(next_state.get());
+
+        if start.get() && curr_state == MenuState::Versus {
+            self.game_data.start_versus();
+        } else if start.get() {
+            self.game_data.mode = match curr_state {
+                MenuState::Sprint => GameMode::Sprint {
+                    lines: self.game_data.sprint_lines,
+                },
+                MenuState::Marathon => GameMode::Marathon {
+                    endless: self.game_data.marathon_endless,
+                },
+                MenuState::CheeseRace => GameMode::CheeseRace {
+                    lines: self.game_data.cheese_lines,
+                },
+                _ => GameMode::Endless,
+            };
+            self.game_data.start();
         }
 
-        ctx.curr_pointer = ctx.curr_pointer.clamp(0, menu_len as i32 - 1);
-    }
-
-    fn handle_gravity(&mut self) {
-        let Some(piece) = &mut self.game_data.curr_piece else {return};
-        let gravity = if self.game_data.keybind.soft_drop.is_down() {
-            self.game_data.soft_drop_gravity.max(self.game_data.gravity)
-        } else {
-            self.game_data.gravity
-        };
-
-        let touch_ground_before = piece.collides_down(&self.game_data.board);
-        self.game_data.accumulated_down += gravity * relative_frame();
-        if self.game_data.accumulated_down >= 1.0 {
-            let step = self.game_data.accumulated_down.floor() as usize;
-            self.game_data.accumulated_down -= step as f32;
-            for _ in 0..step {
-                if piece.collides_down(&self.game_data.board) {
-                    break;
-                } else {
-                    piece.move_down();
-                }
-            }
+        if finesse.get() {
+            self.game_data.start_finesse_trainer();
         }
 
-        if !touch_ground_before && piece.collides_down(&self.game_data.board) {
-            self.sounds.mino_touch_ground.play();
+        if let Some(format) = export.get() {
+            let history = &self.game_data.history;
+            let data = match format {
+                ExportFormat::Csv => history.to_csv(),
+                ExportFormat::Json => history_json(history),
+            };
+            copy_to_clipboard(&data);
+            self.export_notice = Some("COPIED TO CLIPBOARD");
         }
-    }
 
-    fn handle_freeze(&mut self) {
-        let Some(piece) = &mut self.game_data.curr_piece else {return};
-        let freeze_timer = &mut self.game_data.freeze_timer;
-        if piece.collides_down(&self.game_data.board) {
-            freeze_timer.tick(relative_frame());
-            if freeze_timer.done() {
-                self.freeze_piece();
-            }
-        } else {
-            freeze_timer.reset();
+        if connect.get() {
+            self.online_error = self.game_data.start_online().err();
         }
-    }
 
-    fn handle_move(&mut self) {
-        if self.game_data.keybind.hard_drop.is_pressed() {
-            self.hard_drop();
-            return;
+        if paste.get() {
+            self.fumen_error = match paste_fumen() {
+                Ok(page) => {
+                    self.game_data.start_practice(page);
+                    None
+                }
+                Err(error) => Some(error),
+            };
         }
 
-        let first_move = if self.game_data.keybind.left.is_pressed() {
-            self.change_move_state(MoveState::Left);
-            true
-        } else if self.game_data.keybind.right.is_pressed() {
-            self.change_move_state(MoveState::Right);
-            true
-        } else {
-            false
-        };
-
-        if let Some(piece) = &mut self.game_data.curr_piece {
-            if first_move {
-                piece_move_step(
-                    piece,
-                    self.game_data.move_state,
-                    1,
-                    &self.game_data.board,
-                    &self.sounds,
-                );
-            }
+        if let Some(index) = watch.get() {
+            self.replay_paused = false;
+            self.game_data.watch(index);
         }
 
-        match self.game_data.move_state {
-            MoveState::Left if self.game_data.keybind.left.is_down() => self.handle_das(),
-            MoveState::Right if self.game_data.keybind.right.is_down() => self.handle_das(),
-            _ => {
-                self.change_move_state(MoveState::No);
+        if ctx.modifying {
+            // the button that started modifying is not the one bound
+            let key = get_last_key_pressed().filter(|k| *k != KeyCode::Enter);
+            let button = gamepad::get_last_button_pressed().filter(|_| was_modifying);
+            if key.is_some() || button.is_some() {
+                ctx.modifying = false;
             }
-        }
-    }
-
-    fn handle_hold(&mut self) {
-        if self.game_data.keybind.hold.is_pressed() {
-            if self.game_data.hold_piece.can_swap() {
-                // hold success
-                let Some(piece) = self.game_data.curr_piece.take() else { return };
-
-                self.game_data.curr_piece = if let Some(p) = self.game_data.hold_piece.take() {
-                    self.init_piece(p.piece)
-                } else {
-                    self.spawn_piece()
-                };
-
-                let mut hp = HoldPiece::new(piece.tetris_piece());
-                hp.set_hold();
-                self.game_data.hold_piece = Some(hp);
-                self.sounds.mino_hold.play();
-            } else {
-                // hold failed
-                self.sounds.mino_holdfail.play();
+        } else if was_typing {
+            // the keys went to the text field
+        } else if ctx.is_pressed(MenuKey::Down) {
+            ctx.curr_pointer += 1;
+        } else if ctx.is_pressed(MenuKey::Up) {
+            ctx.curr_pointer -= 1;
+        } else if ctx.is_pressed(MenuKey::Back) {
+            if let MenuState::Settings
+            | MenuState::Keys
+            | MenuState::Gamepad
+            | MenuState::Board
+            | MenuState::Sprint
+            | MenuState::Marathon
+            | MenuState::CheeseRace
+            | MenuState::Versus
+            | MenuState::VersusKeys
+            | MenuState::Online = ctx.curr_state()
+            {
+                save_user_settings(&self.game_data);
             }
+            ctx.pop_state();
         }
-    }
-
-    fn handle_rotate(&mut self) {
-        let Some(piece) = &mut self.game_data.curr_piece else {return};
-
-        let kb = &self.game_data.keybind;
-        let touch_ground_before = piece.collides_down(&self.game_data.board);
-
-        let rotated = if kb.rotate_cw.is_pressed() {
-            piece.try_rotate(&self.game_data.board)
-        } else if kb.rotate_ccw.is_pressed() {
-            piece.try_rotate_prev(&self.game_data.board)
-        } else if kb.rotate_180.is_pressed() {
-            piece.try_rotate_180(&self.game_data.board)
-        } else {
-            false
-        };
 
-        if rotated && touch_ground_before {
-            self.sounds.mino_touch_ground.play()
-        }
+        ctx.curr_pointer = ctx.curr_pointer.clamp(0, menu_len as i32 - 1);
     }
 
-    /// handle rotation input during spawn delay
-    fn handle_irs(&mut self) {
-        let next_piece = self
-            .game_data
-            .piece_bag
-            .as_mut()
-            .unwrap()
-            .next_piece_ref_mut();
-        let kb = &self.game_data.keybind;
-        if kb.rotate_cw.is_pressed() {
-            next_piece.rotate_piece()
-        } else if kb.rotate_ccw.is_pressed() {
-            next_piece.rotate_piece_prev()
-        } else if kb.rotate_180.is_pressed() {
-            next_piece.rotate_piece_180()
-        };
+    fn game_stop(&mut self) {
+        self.game_data.clear();
+        self.game_data.practice = None;
+        self.game_data.finesse_trainer = false;
+        self.game_data.state = GameState::Menu;
     }
 
-    fn hard_drop(&mut self) {
-        let Some(piece) = &mut self.game_data.curr_piece else {return};
-
-        while !piece.collides_down(&self.game_data.board) {
-            piece.move_down();
+    fn game_pause(&mut self) {
+        self.game_data.step.reset();
+        if let Some(versus) = &mut self.game_data.versus {
+            versus.reset_steps();
         }
-
-        self.freeze_piece();
-        self.game_data.curr_piece = None;
-
-        self.sounds.mino_touch_ground.play();
+        self.sounds.pause.play();
+        self.pause_ctx = MenuCtx::new();
+        self.game_data.state = GameState::Paused;
     }
 
-    fn change_move_state(&mut self, state: MoveState) {
-        self.game_data.move_state = state;
-        self.game_data.das_timer.reset();
-        self.game_data.accumulated_move = 0.;
+    fn game_resume(&mut self) {
+        self.sounds.ready.play();
+        self.countdown.reset();
+        self.game_data.state = GameState::Resuming;
     }
 
-    fn handle_das(&mut self) {
-        if self.game_data.das_timer.done() {
-            self.game_data.accumulated_move += relative_frame() / self.game_data.arr.max(0.000001);
-            if let Some(piece) = &mut self.game_data.curr_piece {
-                let step = self.game_data.accumulated_move.floor() as usize;
-                self.game_data.accumulated_move = self.game_data.accumulated_move.fract();
-                piece_move_step(
-                    piece,
-                    self.game_data.move_state,
-                    step,
-                    &self.game_data.board,
-                    &self.sounds,
-                );
-            }
-        } else {
-            self.game_data.das_timer.tick(relative_frame());
+    fn game_over(&mut self) {
+        if self.game_data.submit_result() {
+            self.game_data.new_record = true;
+            save_records(&self.game_data);
         }
+        self.game_data.submit_history();
+        save_history(&self.game_data);
+        self.game_data.keep_recording();
+        save_replays(&self.game_data);
+        self.game_data.step.reset();
+        self.game_data.state = GameState::GameOver;
     }
 
-    fn handle_clear(&mut self) {
-        let board = &mut self.game_data.board;
-        let ranges = board.completed_rows();
-        if !ranges.is_empty() {
-            for (from, to) in ranges.iter() {
-                self.game_data.lines += (from - to) as u32;
+    fn game_restart(&mut self) {
+        // the room stays joined, the next match starts once both sides are ready
+        if let Some(online) = &mut self.game_data.online {
+            if let OnlineStatus::Closed(_) = online.status {
+                return;
             }
-
-            board.remove_ranges(ranges);
-            self.sounds.mino_clear.play();
+            online.rematch();
+            self.game_data.state = GameState::Playing;
+            return;
         }
-    }
 
-    fn spawn_piece(&mut self) -> Option<PieceWithPosition> {
-        if let Some(hp) = &mut self.game_data.hold_piece {
-            hp.reset_hold();
+        let versus = self.game_data.versus.is_some();
+        self.game_data.clear();
+        if versus {
+            self.game_data.start_versus();
+        } else {
+            self.game_data.start();
         }
-        let p = self.game_data.piece_bag.as_mut().unwrap().next_piece();
-        logging::debug!("Spawned piece: {:?}", &p.piece_type);
-
-        self.sounds.mino_spawn.play();
-        self.init_piece(p)
     }
 
-    fn init_piece(&mut self, p: Piece) -> Option<PieceWithPosition> {
-        let init_pos = match p.piece_type {
-            rust_tetris_core::enums::PieceType::I => (3, 18),
-            _ => (3, 19),
-        };
-
-        let p = PieceWithPosition::new(init_pos.1, init_pos.0, p);
-        if p.collides(&self.game_data.board) {
-            None
-        } else {
-            Some(p)
-        }
+    fn game_start(&mut self) {
+        self.game_data.start();
     }
+}
 
-    fn freeze_piece(&mut self) {
-        let piece = self.game_data.curr_piece.as_mut().unwrap();
-        piece.finalize_on(&mut self.game_data.board);
+#[derive(Clone, Copy)]
+enum PauseChoice {
+    Resume,
+    Restart,
+    Quit,
+}
 
-        self.game_data.curr_piece = None;
-        self.game_data.freeze_timer.reset();
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
 
-        self.sounds.mino_lock.play();
-        self.handle_clear();
+/// Short name of a mode and its goal, for menus.
+fn mode_label(mode: GameMode) -> String {
+    match mode {
+        GameMode::Endless => "ENDLESS".to_string(),
+        GameMode::Sprint { lines } => format!("{}L", lines),
+        GameMode::Marathon { .. } => "MARATHON".to_string(),
+        GameMode::CheeseRace { lines } => format!("{}C", lines),
     }
+}
 
-    fn game_stop(&mut self) {
-        self.game_data.clear();
-        self.game_data.state = GameState::Menu;
-    }
+/// A warning per key bound to two actions, on two lines to fit the menu.
+fn conflict_labels_of<K>(conflicts: Vec<Conflict<K>>, label: impl Fn(K) -> String) -> Vec<String> {
+    conflicts
+        .into_iter()
+        .flat_map(|c| {
+            [
+                format!("{} {}", label(c.key), c.actions.0),
+                format!("& {}", c.actions.1),
+            ]
+        })
+        .collect()
+}
 
-    fn game_over(&mut self) {
-        self.game_data.state = GameState::GameOver;
-    }
+fn copy_to_clipboard(data: &str) {
+    let InternalGlContext { quad_context, .. } = unsafe { get_internal_gl() };
+    quad_context.clipboard_set(data);
+}
 
-    fn game_restart(&mut self) {
-        self.game_data.clear();
-        self.game_data.start();
-    }
+/// Reads the first page of the fumen in the clipboard.
+fn paste_fumen() -> Result<Page, &'static str> {
+    let InternalGlContext { quad_context, .. } = unsafe { get_internal_gl() };
+    let data = quad_context.clipboard_get().ok_or("EMPTY CLIPBOARD")?;
 
-    fn game_start(&mut self) {
-        self.game_data.start();
+    match fumen::decode(&data) {
+        Ok(pages) => pages.into_iter().next().ok_or("EMPTY FUMEN"),
+        Err(FumenError::Version) => Err("NOT A FUMEN"),
+        Err(_) => Err("INVALID FUMEN"),
     }
 }
 
@@ -460,46 +1127,84 @@ fn relative_frame() -> f32 {
     get_frame_time() * 60.
 }
 
-fn piece_move_step(
-    piece: &mut PieceWithPosition,
-    move_state: MoveState,
-    mut step: usize,
-    board: &Board,
-    sounds: &SoundAssets,
-) {
-    let mut moved = false;
-    let touch_ground_before = piece.collides_down(board);
-
-    match move_state {
-        MoveState::Left => {
-            while step != 0 && !piece.collides_left(board) {
-                step -= 1;
-                piece.move_left();
-                moved = true;
-            }
-        }
-        MoveState::Right => {
-            while step != 0 && !piece.collides_right(board) {
-                step -= 1;
-                piece.move_right();
-                moved = true;
-            }
-        }
-        _ => {}
-    }
-
-    let touch_groud_after = piece.collides_down(board);
-
-    if moved && touch_ground_before && touch_groud_after {
-        sounds.mino_touch_ground.play();
-    }
-}
-
 lazy_static! {
     static ref DAS_VALUES: Vec<f32> = (1..=15).map(|x| x as f32).collect();
     static ref DAS_LABELS: Vec<String> = DAS_VALUES.iter().map(|x| x.to_string()).collect();
     static ref ARR_VALUES: Vec<f32> = (0..=10).map(|x| x as f32).collect();
     static ref ARR_LABELS: Vec<String> = ARR_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref LINE_CLEAR_DELAY_VALUES: Vec<f32> = (0..=8).map(|x| x as f32 * 5.).collect();
+    static ref LINE_CLEAR_DELAY_LABELS: Vec<String> = LINE_CLEAR_DELAY_VALUES
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    static ref LOCK_DELAY_VALUES: Vec<LockDelayPolicy> = vec![
+        LockDelayPolicy::MoveReset,
+        LockDelayPolicy::Classic,
+        LockDelayPolicy::Infinity,
+    ];
+    static ref LOCK_DELAY_LABELS: Vec<String> =
+        vec!["MOVE".to_string(), "NONE".to_string(), "INF".to_string(),];
+    static ref SWITCH_LABELS: Vec<String> = vec!["OFF".to_string(), "ON".to_string()];
+    static ref ROTATION_SYSTEM_VALUES: Vec<RotationSystemType> = vec![
+        RotationSystemType::Srs,
+        RotationSystemType::SrsPlus,
+        RotationSystemType::Ars,
+        RotationSystemType::Classic,
+        RotationSystemType::SrsNo180,
+    ];
+    static ref ROTATION_SYSTEM_LABELS: Vec<String> = vec![
+        "SRS".to_string(),
+        "SRS+".to_string(),
+        "ARS".to_string(),
+        "NES".to_string(),
+        "NO180".to_string(),
+    ];
+    static ref RANDOMIZER_VALUES: Vec<RandomizerType> = vec![
+        RandomizerType::SevenBag,
+        RandomizerType::FourteenBag,
+        RandomizerType::Random,
+        RandomizerType::Tgm1,
+        RandomizerType::Tgm2,
+        RandomizerType::Tgm3,
+        RandomizerType::Nes,
+    ];
+    static ref RANDOMIZER_LABELS: Vec<String> = vec![
+        "7BAG".to_string(),
+        "14BAG".to_string(),
+        "RANDOM".to_string(),
+        "TGM1".to_string(),
+        "TGM2".to_string(),
+        "TGM3".to_string(),
+        "NES".to_string(),
+    ];
+    static ref TICK_RATE_VALUES: Vec<u32> = vec![60, 120, 240];
+    static ref TICK_RATE_LABELS: Vec<String> = TICK_RATE_VALUES
+        .iter()
+        .map(|x| format!("{}HZ", x))
+        .collect();
+    static ref BOARD_COLS_VALUES: Vec<isize> = (4..=20).collect();
+    static ref BOARD_COLS_LABELS: Vec<String> =
+        BOARD_COLS_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref VISIBLE_ROWS_VALUES: Vec<isize> = (10..=30).collect();
+    static ref VISIBLE_ROWS_LABELS: Vec<String> =
+        VISIBLE_ROWS_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref BUFFER_ROWS_VALUES: Vec<isize> = vec![4, 8, 12, 16, 20];
+    static ref BUFFER_ROWS_LABELS: Vec<String> =
+        BUFFER_ROWS_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref SPRINT_LINES_VALUES: Vec<u32> = vec![20, 40, 100];
+    static ref SPRINT_LINES_LABELS: Vec<String> =
+        SPRINT_LINES_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref CHEESE_LINES_VALUES: Vec<u32> = vec![10, 18, 100];
+    static ref CHEESE_LINES_LABELS: Vec<String> =
+        CHEESE_LINES_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref OPPONENT_LABELS: Vec<String> = vec!["2P".to_string(), "BOT".to_string()];
+    static ref BOT_PPS_VALUES: Vec<f32> = vec![0.5, 1., 1.5, 2., 3., 4., 5.];
+    static ref BOT_PPS_LABELS: Vec<String> = BOT_PPS_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref PAD_DEADZONE_VALUES: Vec<f32> = (1..=8).map(|x| x as f32 / 10.).collect();
+    static ref PAD_DEADZONE_LABELS: Vec<String> =
+        PAD_DEADZONE_VALUES.iter().map(|x| x.to_string()).collect();
+    static ref ROOM_VALUES: Vec<u32> = (1..=9).collect();
+    static ref ROOM_LABELS: Vec<String> = ROOM_VALUES.iter().map(|x| x.to_string()).collect();
     static ref SOFT_DROP_VALUES: Vec<f32> = vec![
         1. / 64.,
         1. / 32.,
@@ -9,6 +9,68 @@ pub struct Assets {
     #[asset(Image, path = "res/graphics/font_big_sheet.png")]
     pub text_img: Image,
 
+    // block break animations, one per block color, each split in two sheets
+    #[asset(Image, path = "res/graphics/break0_0.png")]
+    pub break0_0: Image,
+
+    #[asset(Image, path = "res/graphics/break0_1.png")]
+    pub break0_1: Image,
+
+    #[asset(Image, path = "res/graphics/break1_0.png")]
+    pub break1_0: Image,
+
+    #[asset(Image, path = "res/graphics/break1_1.png")]
+    pub break1_1: Image,
+
+    #[asset(Image, path = "res/graphics/break2_0.png")]
+    pub break2_0: Image,
+
+    #[asset(Image, path = "res/graphics/break2_1.png")]
+    pub break2_1: Image,
+
+    #[asset(Image, path = "res/graphics/break3_0.png")]
+    pub break3_0: Image,
+
+    #[asset(Image, path = "res/graphics/break3_1.png")]
+    pub break3_1: Image,
+
+    #[asset(Image, path = "res/graphics/break4_0.png")]
+    pub break4_0: Image,
+
+    #[asset(Image, path = "res/graphics/break4_1.png")]
+    pub break4_1: Image,
+
+    #[asset(Image, path = "res/graphics/break5_0.png")]
+    pub break5_0: Image,
+
+    #[asset(Image, path = "res/graphics/break5_1.png")]
+    pub break5_1: Image,
+
+    #[asset(Image, path = "res/graphics/break6_0.png")]
+    pub break6_0: Image,
+
+    #[asset(Image, path = "res/graphics/break6_1.png")]
+    pub break6_1: Image,
+
+    #[asset(Image, path = "res/graphics/break7_0.png")]
+    pub break7_0: Image,
+
+    #[asset(Image, path = "res/graphics/break7_1.png")]
+    pub break7_1: Image,
+
+    // line erase flashes, by number of cleared rows
+    #[asset(Image, path = "res/graphics/perase1.png")]
+    pub perase1: Image,
+
+    #[asset(Image, path = "res/graphics/perase2.png")]
+    pub perase2: Image,
+
+    #[asset(Image, path = "res/graphics/perase3.png")]
+    pub perase3: Image,
+
+    #[asset(Image, path = "res/graphics/perase4.png")]
+    pub perase4: Image,
+
     #[asset(Sound, path = "res/se/lock.wav")]
     pub mino_lock: Sound,
 
@@ -21,6 +83,15 @@ pub struct Assets {
     #[asset(Sound, path = "res/se/holdfail.wav")]
     pub mino_holdfail: Sound,
 
+    #[asset(Sound, path = "res/se/pause.wav")]
+    pub pause: Sound,
+
+    #[asset(Sound, path = "res/se/ready.wav")]
+    pub ready: Sound,
+
+    #[asset(Sound, path = "res/se/go.wav")]
+    pub go: Sound,
+
     #[asset(Sound, path = "res/se/move.wav")]
     pub mino_touch_ground: Sound,
 
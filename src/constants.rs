@@ -23,7 +23,37 @@ pub const BOARD_POS: Vec3 = Vec3 {
 
 pub const SCORE_POS: Vec2 = Vec2 {
     x: 0.,
-    y: -FONT_SIZE * 5.,
+    y: -FONT_SIZE * 2.,
+};
+
+/// Statistics panel, from the top left corner of the board
+pub const STATS_OFFSET: Vec2 = Vec2 {
+    x: -8. * BLOCK_SIZE,
+    y: 0.,
+};
+pub const STATS_FONT_SIZE: f32 = FONT_SIZE * 0.75;
+
+/// Boards of the versus players, left to right
+pub const VERSUS_BOARD_POS: [Vec3; 2] = [
+    Vec3 {
+        x: -14.5 * BLOCK_SIZE,
+        y: -10. * BLOCK_SIZE,
+        z: 0.,
+    },
+    Vec3 {
+        x: 5.5 * BLOCK_SIZE,
+        y: -10. * BLOCK_SIZE,
+        z: 0.,
+    },
+];
+
+/// Bot game of the title screen, right of the menu
+pub const DEMO_BOARD_POS: Vec3 = VERSUS_BOARD_POS[1];
+
+/// Results of a versus match, between the boards
+pub const VERSUS_MENU_POS: Vec2 = Vec2 {
+    x: -4. * BLOCK_SIZE,
+    y: 7.5 * BLOCK_SIZE,
 };
 
 lazy_static! {
@@ -34,13 +64,51 @@ lazy_static! {
         };
 }
 
+// Pause
+
+/// Frames counted down before playing again
+pub const COUNTDOWN_FRAMES: f32 = 90.;
+/// Seconds between two frames after which desktop builds pause the game,
+/// as they are told nothing when the window loses the focus
+pub const FOCUS_LOST_FRAME_TIME: f32 = 0.5;
+
+// Bot
+
+/// Pieces per second of the bot playing on the title screen
+pub const DEMO_PPS: f32 = 2.;
+
+// Online
+
+/// Longest relay address typed in the online menu
+pub const RELAY_ADDR_MAX_LEN: usize = 24;
+
+// Replay
+
+/// Replays kept in storage, older ones are dropped
+pub const MAX_REPLAYS: usize = 10;
+/// Playback speed while fast-forwarding
+pub const REPLAY_FAST_FORWARD: f32 = 4.;
+
+// History
+
+/// Games kept in the career history, older ones are dropped
+pub const MAX_HISTORY: usize = 1000;
+/// Games averaged by the trends of the stats menu
+pub const TREND_GAMES: usize = 10;
+/// Personal bests listed by the stats menu
+pub const STATS_BEST_TIMES: usize = 5;
+
 /// Default Settings
 
 pub const DAS_DEFAULT: f32 = 8.;
 pub const ARR_DEFAULT: f32 = 4.;
 pub const SOFT_DROP_DEFAULT: f32 = 0.5;
-
-/// Assets
-
-pub const FIELD_COLS: usize = 10;
-pub const FIELD_ROWS: usize = 20;
+pub const LINE_CLEAR_DELAY_DEFAULT: f32 = 15.;
+pub const SPRINT_LINES_DEFAULT: u32 = 40;
+pub const CHEESE_LINES_DEFAULT: u32 = 10;
+pub const TICK_RATE_DEFAULT: u32 = 60;
+pub const BOT_PPS_DEFAULT: f32 = 1.5;
+pub const PAD_DEADZONE_DEFAULT: f32 = 0.3;
+/// Range of the deadzone selector, past it the stick is always or never pressed
+pub const PAD_DEADZONE_MIN: f32 = 0.1;
+pub const PAD_DEADZONE_MAX: f32 = 0.8;
@@ -0,0 +1,42 @@
+use macroquad::prelude::get_time;
+use rust_tetris_core::{
+    bot::{Bot, Weights},
+    game::GameConfig,
+    input::Input,
+};
+
+use crate::{constants::DEMO_PPS, versus::Player};
+
+/// A game played by the bot on the title screen, started over once it tops out.
+pub struct Demo {
+    pub player: Player,
+    bot: Bot,
+    config: GameConfig,
+    tick_rate: u32,
+}
+
+impl Demo {
+    pub fn new(config: GameConfig, tick_rate: u32) -> Self {
+        Self {
+            player: Player::new(config.clone(), tick_rate, (get_time() * 1000.) as u64),
+            bot: Bot::new(Weights::default(), DEMO_PPS),
+            config,
+            tick_rate,
+        }
+    }
+
+    /// Runs the ticks due after `frames` more frames, silently.
+    pub fn advance(&mut self, frames: f32) {
+        self.player.step.advance(frames, Input::default());
+        while self.player.step.next_tick().is_some() {
+            if self.player.game.is_over() {
+                *self = Self::new(self.config.clone(), self.tick_rate);
+            }
+
+            let input = self
+                .bot
+                .next_input(&self.player.game, self.player.step.tick_frames());
+            self.player.tick(&input, &mut |_| {});
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Focus changes of the window, to pause the game once it loses the focus.
+#[cfg(not(target_arch = "wasm32"))]
+use macroquad::time::get_frame_time;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::constants::FOCUS_LOST_FRAME_TIME;
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    /// Set by the blur and visibilitychange listeners of web/focus.js
+    fn take_focus_lost() -> u32;
+}
+
+/// The window lost the focus since the last call, to be called once per frame.
+#[cfg(target_arch = "wasm32")]
+pub fn lost() -> bool {
+    unsafe { take_focus_lost() != 0 }
+}
+
+/// Desktop windows report no focus changes, a long stall between frames is
+/// the only hint that the window was hidden or minimized.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn lost() -> bool {
+    get_frame_time() > FOCUS_LOST_FRAME_TIME
+}
@@ -1,52 +1,47 @@
 pub use nanoserde::DeJsonErr;
 use nanoserde::{DeJson, SerJson};
 use rust_tetris_core::{
-    board::Board, holder::HoldPiece, piece_bag::PieceBag, pieces::PieceWithPosition,
+    board::BoardSize,
+    bot::{Bot, Weights},
+    enums::PieceType,
+    fumen::Page,
+    game::{Game, GameConfig},
+    gamepad::Button,
+    garbage::GarbagePattern,
+    history::{date_to_string, GameRecord, GameResult, History},
+    input::{Action, Input},
+    lock::LockDelayPolicy,
+    mode::GameMode,
+    net::{DEFAULT_RELAY_ADDR, DEFAULT_RELAY_PORT},
+    piece_bag::RandomizerType,
+    replay::{Replay, ReplayPlayer},
+    rotation::RotationSystemType,
+    stats::Statistics,
+    step::{FixedStep, FRAME_RATE},
 };
 
-use macroquad::prelude::*;
+use macroquad::{miniquad::date, prelude::*};
 
-use crate::constants::{ARR_DEFAULT, DAS_DEFAULT, SOFT_DROP_DEFAULT};
-
-pub struct FrameTimer {
-    duration: f32,
-    left: f32,
-}
-
-impl FrameTimer {
-    pub fn new(duration: f32) -> Self {
-        Self {
-            duration,
-            left: duration,
-        }
-    }
-
-    pub fn tick(&mut self, frame: f32) {
-        self.left -= frame;
-    }
-
-    pub fn done(&self) -> bool {
-        self.left <= 0.
-    }
-
-    pub fn reset(&mut self) {
-        self.left = self.duration
-    }
-
-    pub fn get_duration(&self) -> f32 {
-        self.duration
-    }
-
-    pub fn get_duration_mut(&mut self) -> &mut f32 {
-        &mut self.duration
-    }
-}
+use crate::constants::{
+    ARR_DEFAULT, BOT_PPS_DEFAULT, CHEESE_LINES_DEFAULT, DAS_DEFAULT, LINE_CLEAR_DELAY_DEFAULT,
+    MAX_HISTORY, MAX_REPLAYS, PAD_DEADZONE_DEFAULT, PAD_DEADZONE_MAX, PAD_DEADZONE_MIN,
+    SOFT_DROP_DEFAULT, SPRINT_LINES_DEFAULT, TICK_RATE_DEFAULT,
+};
+use crate::demo::Demo;
+use crate::gamepad::{self, is_button_pressed};
+use crate::online::Online;
+use crate::versus::{Versus, VERSUS_GARBAGE};
 
 #[derive(PartialEq, Eq)]
 pub enum GameState {
     Menu,
     Playing,
+    Paused,
+    /// Counting down before playing again after a pause
+    Resuming,
     GameOver,
+    /// Watching a recorded game
+    Replay,
 }
 
 #[derive(Eq, PartialEq, Clone, Copy)]
@@ -70,112 +65,677 @@ impl Key {
     }
 }
 
+/// Every key bound to an action, any of them triggers it.
+#[derive(Eq, PartialEq, Clone, Default)]
+pub struct Keys(Vec<Key>);
+
+impl Keys {
+    pub fn new(codes: &[KeyCode]) -> Self {
+        Self(codes.iter().copied().map(Key).collect())
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Binds the key, or unbinds it if it is bound already. The last key of
+    /// an action stays bound, so that it can still be played.
+    pub fn toggle(&mut self, key: Key) {
+        match self.0.iter().position(|k| *k == key) {
+            Some(_) if self.0.len() == 1 => {}
+            Some(index) => {
+                self.0.remove(index);
+            }
+            None => self.0.push(key),
+        }
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.0.iter().any(Key::is_pressed)
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.0.iter().any(Key::is_down)
+    }
+}
+
 #[derive(SerJson, DeJson, Clone)]
 pub struct KeyBind {
-    pub left: Key,
-    pub right: Key,
-    pub soft_drop: Key,
-    pub hard_drop: Key,
+    pub left: Keys,
+    pub right: Keys,
+    pub soft_drop: Keys,
+    pub hard_drop: Keys,
+
+    pub rotate_cw: Keys,
+    pub rotate_ccw: Keys,
+    pub rotate_180: Keys,
+    pub hold: Keys,
+
+    pub restart: Keys,
+    pub escape: Keys,
+    #[nserde(default)]
+    pub pause: Keys,
+    /// Shows or hides the statistics panel
+    #[nserde(default)]
+    pub stats: Keys,
+}
+
+/// A key or button bound to two actions at once.
+pub struct Conflict<K> {
+    pub key: K,
+    pub actions: (String, String),
+}
+
+/// Labels of actions and what they are bound to.
+type Actions<K> = Vec<(String, Vec<K>)>;
+
+/// Keys bound to both actions of each pair.
+fn find_conflicts<'a, K: Copy + PartialEq + 'a>(
+    pairs: impl IntoIterator<Item = (&'a (String, Vec<K>), &'a (String, Vec<K>))>,
+) -> Vec<Conflict<K>> {
+    let mut conflicts = Vec::new();
+    for ((action, keys), (other, other_keys)) in pairs {
+        for key in keys.iter().filter(|k| other_keys.contains(k)) {
+            conflicts.push(Conflict {
+                key: *key,
+                actions: (action.clone(), other.clone()),
+            });
+        }
+    }
+    conflicts
+}
+
+/// Each pair of `actions` once.
+fn pairs<T>(actions: &[T]) -> impl Iterator<Item = (&T, &T)> {
+    actions
+        .iter()
+        .enumerate()
+        .flat_map(move |(i, action)| actions[i + 1..].iter().map(move |other| (action, other)))
+}
 
-    pub rotate_cw: Key,
-    pub rotate_ccw: Key,
-    pub rotate_180: Key,
-    pub hold: Key,
+impl KeyBind {
+    /// Samples the gameplay keys into an input snapshot.
+    pub fn input(&self) -> Input {
+        let mut input = Input::default();
+        for (action, keys) in [
+            (Action::Left, &self.left),
+            (Action::Right, &self.right),
+            (Action::SoftDrop, &self.soft_drop),
+            (Action::HardDrop, &self.hard_drop),
+            (Action::RotateCw, &self.rotate_cw),
+            (Action::RotateCcw, &self.rotate_ccw),
+            (Action::Rotate180, &self.rotate_180),
+            (Action::Hold, &self.hold),
+        ] {
+            input.set(action, keys.is_pressed(), keys.is_down());
+        }
+        input
+    }
+
+    fn actions(&self) -> Actions<Key> {
+        [
+            ("LEFT", &self.left),
+            ("RIGHT", &self.right),
+            ("SOFT DROP", &self.soft_drop),
+            ("HARD DROP", &self.hard_drop),
+            ("ROTATE CW", &self.rotate_cw),
+            ("ROTATE CCW", &self.rotate_ccw),
+            ("ROTATE 180", &self.rotate_180),
+            ("HOLD", &self.hold),
+            ("RESTART", &self.restart),
+            ("QUIT", &self.escape),
+            ("PAUSE", &self.pause),
+            ("STATS", &self.stats),
+        ]
+        .into_iter()
+        .map(|(action, keys)| (action.to_string(), keys.keys().to_vec()))
+        .collect()
+    }
+
+    /// Keys bound to two of these actions, or to one of them and one of the
+    /// other player, whose actions are labeled with `other_player` first.
+    pub fn conflicts(&self, other: &KeyBind, other_player: &str) -> Vec<Conflict<Key>> {
+        let actions = self.actions();
+        let others: Actions<Key> = other
+            .actions()
+            .into_iter()
+            .map(|(action, keys)| (format!("{} {}", other_player, action), keys))
+            .collect();
+
+        let between = actions
+            .iter()
+            .flat_map(|action| others.iter().map(move |other| (action, other)));
+        find_conflicts(pairs(&actions).chain(between))
+    }
+}
 
-    pub restart: Key,
-    pub escape: Key,
+impl KeyBind {
+    /// Gameplay keys of the right side of the keyboard, for the second
+    /// versus player. Pausing and quitting stay with the first player.
+    pub fn second_player() -> Self {
+        Self {
+            left: Keys::new(&[KeyCode::Left]),
+            right: Keys::new(&[KeyCode::Right]),
+            soft_drop: Keys::new(&[KeyCode::Down]),
+            hard_drop: Keys::new(&[KeyCode::Up]),
+            rotate_cw: Keys::new(&[KeyCode::Period]),
+            rotate_ccw: Keys::new(&[KeyCode::Comma]),
+            rotate_180: Keys::new(&[KeyCode::Slash]),
+            hold: Keys::new(&[KeyCode::RightShift]),
+            restart: Keys::default(),
+            escape: Keys::default(),
+            pause: Keys::default(),
+            stats: Keys::default(),
+        }
+    }
 }
 
 impl Default for KeyBind {
     fn default() -> Self {
         Self {
-            left: Key(KeyCode::A),
-            right: Key(KeyCode::D),
-            soft_drop: Key(KeyCode::S),
-            hard_drop: Key(KeyCode::W),
-            rotate_cw: Key(KeyCode::K),
-            rotate_ccw: Key(KeyCode::J),
-            rotate_180: Key(KeyCode::C),
-            hold: Key(KeyCode::L),
-            restart: Key(KeyCode::R),
-            escape: Key(KeyCode::Escape),
+            left: Keys::new(&[KeyCode::A]),
+            right: Keys::new(&[KeyCode::D]),
+            soft_drop: Keys::new(&[KeyCode::S]),
+            hard_drop: Keys::new(&[KeyCode::W]),
+            rotate_cw: Keys::new(&[KeyCode::K]),
+            rotate_ccw: Keys::new(&[KeyCode::J]),
+            rotate_180: Keys::new(&[KeyCode::C]),
+            hold: Keys::new(&[KeyCode::L]),
+            restart: Keys::new(&[KeyCode::R]),
+            escape: Keys::new(&[KeyCode::Escape]),
+            pause: Keys::new(&[KeyCode::P]),
+            stats: Keys::new(&[KeyCode::Tab]),
         }
     }
 }
 
+/// A gamepad button, or none when the action is left to the keyboard.
+#[derive(Eq, PartialEq, Clone, Copy, Default)]
+pub struct PadKey(Option<Button>);
+
+impl PadKey {
+    pub fn new(button: Button) -> Self {
+        Self(Some(button))
+    }
+
+    pub fn button(&self) -> Option<Button> {
+        self.0
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.0.is_some_and(is_button_pressed)
+    }
+}
+
+/// Gamepad buttons of the first player, menus included.
+#[derive(SerJson, DeJson, Clone, Copy)]
+pub struct PadBind {
+    pub left: PadKey,
+    pub right: PadKey,
+    pub soft_drop: PadKey,
+    pub hard_drop: PadKey,
+
+    pub rotate_cw: PadKey,
+    pub rotate_ccw: PadKey,
+    pub rotate_180: PadKey,
+    pub hold: PadKey,
+
+    pub restart: PadKey,
+    pub escape: PadKey,
+    pub pause: PadKey,
+    pub stats: PadKey,
+
+    pub menu_up: PadKey,
+    pub menu_down: PadKey,
+    pub menu_left: PadKey,
+    pub menu_right: PadKey,
+    pub menu_confirm: PadKey,
+    pub menu_back: PadKey,
+}
+
+impl PadBind {
+    /// Buttons bound to two actions of the game, or to two actions of the
+    /// menus. The menus may share the buttons of the game.
+    pub fn conflicts(&self) -> Vec<Conflict<Button>> {
+        let actions = |actions: &[(&str, PadKey)]| -> Actions<Button> {
+            actions
+                .iter()
+                .map(|(action, key)| (action.to_string(), key.button().into_iter().collect()))
+                .collect()
+        };
+        let game = actions(&[
+            ("LEFT", self.left),
+            ("RIGHT", self.right),
+            ("SOFT DROP", self.soft_drop),
+            ("HARD DROP", self.hard_drop),
+            ("ROTATE CW", self.rotate_cw),
+            ("ROTATE CCW", self.rotate_ccw),
+            ("ROTATE 180", self.rotate_180),
+            ("HOLD", self.hold),
+            ("RESTART", self.restart),
+            ("QUIT", self.escape),
+            ("PAUSE", self.pause),
+            ("STATS", self.stats),
+        ]);
+        let menu = actions(&[
+            ("MENU UP", self.menu_up),
+            ("MENU DOWN", self.menu_down),
+            ("MENU LEFT", self.menu_left),
+            ("MENU RIGHT", self.menu_right),
+            ("CONFIRM", self.menu_confirm),
+            ("BACK", self.menu_back),
+        ]);
+
+        find_conflicts(pairs(&game).chain(pairs(&menu)))
+    }
+
+    /// Samples the gameplay buttons into an input snapshot.
+    pub fn input(&self) -> Input {
+        gamepad::input([
+            (Action::Left, self.left.button()),
+            (Action::Right, self.right.button()),
+            (Action::SoftDrop, self.soft_drop.button()),
+            (Action::HardDrop, self.hard_drop.button()),
+            (Action::RotateCw, self.rotate_cw.button()),
+            (Action::RotateCcw, self.rotate_ccw.button()),
+            (Action::Rotate180, self.rotate_180.button()),
+            (Action::Hold, self.hold.button()),
+        ])
+    }
+}
+
+impl Default for PadBind {
+    fn default() -> Self {
+        Self {
+            left: PadKey::new(Button::DPadLeft),
+            right: PadKey::new(Button::DPadRight),
+            soft_drop: PadKey::new(Button::DPadDown),
+            hard_drop: PadKey::new(Button::DPadUp),
+            rotate_cw: PadKey::new(Button::East),
+            rotate_ccw: PadKey::new(Button::South),
+            rotate_180: PadKey::new(Button::North),
+            hold: PadKey::new(Button::LeftBumper),
+            restart: PadKey::new(Button::Select),
+            escape: PadKey::default(),
+            pause: PadKey::new(Button::Start),
+            stats: PadKey::new(Button::RightBumper),
+            menu_up: PadKey::new(Button::DPadUp),
+            menu_down: PadKey::new(Button::DPadDown),
+            menu_left: PadKey::new(Button::DPadLeft),
+            menu_right: PadKey::new(Button::DPadRight),
+            menu_confirm: PadKey::new(Button::South),
+            menu_back: PadKey::new(Button::East),
+        }
+    }
+}
+
+/// Actions outside of the game itself, bound on the keyboard and the gamepad.
+#[derive(Clone, Copy)]
+pub enum Command {
+    Restart,
+    Escape,
+    Pause,
+    Stats,
+}
+
 pub struct GameData {
     pub keybind: KeyBind,
-    pub board: Board,
-    pub piece_bag: Option<PieceBag>,
-    pub hold_piece: Option<HoldPiece>,
-    pub curr_piece: Option<PieceWithPosition>,
+    /// Keys of the second player in versus matches
+    pub keybind_p2: KeyBind,
+    /// Gamepad buttons of the first player
+    pub pad_bind: PadBind,
+    /// Stick deflection below which it counts as centered
+    pub pad_deadzone: f32,
+    pub config: GameConfig,
+    pub game: Option<Game>,
+    /// Seed of the pieces of the game being played
+    pub seed: u64,
+    pub stats: Statistics,
+    /// The statistics panel is shown while playing
+    pub show_stats: bool,
 
-    pub gravity: f32,
-    pub arr: f32,
-    pub soft_drop_gravity: f32,
-    pub das_timer: FrameTimer,
-    pub freeze_timer: FrameTimer,
-    pub spawn_delay_timer: FrameTimer,
+    pub mode: GameMode,
+    pub sprint_lines: u32,
+    pub marathon_endless: bool,
+    /// Garbage rows of cheese races
+    pub cheese_lines: u32,
+    pub sprint_lock_delay: LockDelayPolicy,
+    pub marathon_lock_delay: LockDelayPolicy,
+    /// Ticks per second of the simulation
+    pub tick_rate: u32,
+    pub records: Records,
+    /// The last finished game set a personal best
+    pub new_record: bool,
+    /// Every game played to its end
+    pub history: History,
 
-    pub accumulated_down: f32,
-    pub accumulated_move: f32,
-    pub move_state: MoveState,
+    /// Replays of the last games, newest first
+    pub replays: Vec<Replay>,
+    /// Inputs of the game being played
+    pub recording: Option<Replay>,
+    /// Playback of the replay being watched
+    pub player: Option<ReplayPlayer>,
+    /// Fumen page the practice game starts from
+    pub practice: Option<Page>,
+    /// Games start over on every finesse fault
+    pub finesse_trainer: bool,
+    /// Local versus match, played instead of `game`
+    pub versus: Option<Versus>,
+    /// The second versus side is played by the bot
+    pub versus_bot: bool,
+    /// Pieces per second of the versus bot
+    pub bot_pps: f32,
+    /// Bot game shown on the title screen
+    pub demo: Option<Demo>,
+    /// Online versus match, played instead of `game`
+    pub online: Option<Online>,
+    /// Room joined on the relay for online matches
+    pub online_room: u32,
+    /// Host of the relay, with its port unless it is the default one
+    pub relay_addr: String,
 
-    pub state: GameState,
-    pub lines: u32,
-    pub time: f32,
-}
+    /// Splits frames into ticks of the game being played or watched
+    pub step: FixedStep,
+    /// Position of the current piece at the previous tick, to render between ticks
+    pub prev_piece_pos: Option<(isize, isize)>,
 
-#[derive(Clone, Copy, Debug)]
-pub enum MoveState {
-    Left,
-    Right,
-    No,
+    pub state: GameState,
 }
 
 impl GameData {
     pub fn new() -> GameData {
         GameData {
             keybind: KeyBind::default(),
-            board: Board::new(40, 10),
-            piece_bag: None,
-            hold_piece: None,
-            curr_piece: None,
+            keybind_p2: KeyBind::second_player(),
+            pad_bind: PadBind::default(),
+            pad_deadzone: PAD_DEADZONE_DEFAULT,
+            config: GameConfig {
+                das: DAS_DEFAULT,
+                arr: ARR_DEFAULT,
+                soft_drop_gravity: SOFT_DROP_DEFAULT,
+                line_clear_delay: LINE_CLEAR_DELAY_DEFAULT,
+                ..Default::default()
+            },
+            game: None,
+            seed: 0,
+            stats: Statistics::default(),
+            show_stats: false,
+            mode: GameMode::Endless,
+            sprint_lines: SPRINT_LINES_DEFAULT,
+            marathon_endless: false,
+            cheese_lines: CHEESE_LINES_DEFAULT,
+            sprint_lock_delay: LockDelayPolicy::default(),
+            marathon_lock_delay: LockDelayPolicy::default(),
+            tick_rate: TICK_RATE_DEFAULT,
+            records: Records::default(),
+            new_record: false,
+            history: History::default(),
+            replays: Vec::new(),
+            recording: None,
+            player: None,
+            versus: None,
+            versus_bot: false,
+            bot_pps: BOT_PPS_DEFAULT,
+            demo: None,
+            online: None,
+            online_room: 1,
+            relay_addr: DEFAULT_RELAY_ADDR.to_string(),
+            practice: None,
+            finesse_trainer: false,
+            step: FixedStep::default(),
+            prev_piece_pos: None,
+            state: GameState::Menu,
+        }
+    }
 
-            gravity: 0.0156,
-            soft_drop_gravity: SOFT_DROP_DEFAULT,
-            accumulated_down: 0.,
+    /// Samples the keyboard and the gamepad of the first player.
+    pub fn input(&self) -> Input {
+        self.keybind.input().union(self.pad_bind.input())
+    }
 
-            freeze_timer: FrameTimer::new(200.),
-            spawn_delay_timer: FrameTimer::new(10.),
+    /// The command was pressed on the keyboard or the gamepad.
+    pub fn pressed(&self, command: Command) -> bool {
+        let (keys, pad_key) = match command {
+            Command::Restart => (&self.keybind.restart, self.pad_bind.restart),
+            Command::Escape => (&self.keybind.escape, self.pad_bind.escape),
+            Command::Pause => (&self.keybind.pause, self.pad_bind.pause),
+            Command::Stats => (&self.keybind.stats, self.pad_bind.stats),
+        };
+        keys.is_pressed() || pad_key.is_pressed()
+    }
 
-            das_timer: FrameTimer::new(DAS_DEFAULT),
+    pub fn clear(&mut self) {
+        self.game = None;
+        self.recording = None;
+        self.player = None;
+        self.versus = None;
+        self.online = None;
+        self.demo = None;
+        self.prev_piece_pos = None;
+        self.step.reset();
+    }
 
-            arr: ARR_DEFAULT,
-            accumulated_move: 0.,
-            move_state: MoveState::No,
-            state: GameState::Menu,
-            lines: 0,
-            time: 0.,
+    pub fn start(&mut self) {
+        self.state = GameState::Playing;
+        self.stats = Statistics::default();
+        self.new_record = false;
+
+        let replay = Replay::new(
+            (get_time() * 1000.) as u64,
+            GameConfig {
+                mode: self.mode,
+                lock_delay_policy: self.lock_delay_policy(),
+                randomizer: self.randomizer(),
+                board_size: self.board_size(),
+                garbage: self.garbage(),
+                ..self.config.clone()
+            },
+            self.tick_rate,
+        );
+        let mut game = replay.new_game();
+        self.seed = replay.seed;
+        self.step = replay.step();
+        self.prev_piece_pos = None;
+
+        // replays only know the seed, practice fields are not recorded
+        match &self.practice {
+            Some(page) => game.load_page(page),
+            None => self.recording = Some(replay),
         }
+        self.game = Some(game);
     }
 
-    pub fn clear(&mut self) {
-        self.board.clear_all();
-        self.piece_bag = None;
-        self.hold_piece = None;
-        self.curr_piece = None;
-        self.lines = 0;
-        self.time = 0.;
+    /// Starts a versus match on the standard board, neither recorded nor
+    /// counting towards records.
+    pub fn start_versus(&mut self) {
+        self.state = GameState::Playing;
+        self.mode = GameMode::Endless;
+
+        let mut versus = Versus::new(
+            self.versus_config(),
+            self.tick_rate,
+            (get_time() * 1000.) as u64,
+        );
+        if self.versus_bot {
+            versus.bot = Some(Bot::new(Weights::default(), self.bot_pps));
+        }
+        self.versus = Some(versus);
     }
 
-    pub fn start(&mut self) {
-        self.das_timer.reset();
-        self.freeze_timer.reset();
-        self.spawn_delay_timer.tick(99999.);
+    /// Joins the online room on the relay, the match starts once an opponent is ready.
+    pub fn start_online(&mut self) -> Result<(), &'static str> {
+        let online = Online::connect(
+            self.relay_socket_addr().as_str(),
+            &self.online_room.to_string(),
+            self.versus_config(),
+            self.tick_rate,
+        )
+        .map_err(|_| "NO RELAY")?;
+
         self.state = GameState::Playing;
-        self.piece_bag = Some(PieceBag::new(6, Some((get_time() * 1000.) as u64)));
-        self.gravity = 0.0156;
+        self.mode = GameMode::Endless;
+        self.online = Some(online);
+        Ok(())
+    }
+
+    /// The relay address with its port.
+    fn relay_socket_addr(&self) -> String {
+        let addr = self.relay_addr.trim();
+        // IPv6 addresses only have a port after their closing bracket
+        let has_port = match addr.rsplit_once(']') {
+            Some((_, after)) => after.starts_with(':'),
+            None => addr.contains(':'),
+        };
+        if has_port {
+            addr.to_string()
+        } else {
+            format!("{}:{}", addr, DEFAULT_RELAY_PORT)
+        }
+    }
+
+    /// Both sides of a match are dealt the same pieces on the standard board.
+    fn versus_config(&self) -> GameConfig {
+        GameConfig {
+            mode: GameMode::Endless,
+            randomizer: RandomizerType::SevenBag,
+            board_size: BoardSize::default(),
+            garbage: VERSUS_GARBAGE,
+            ..self.config.clone()
+        }
+    }
+
+    /// Starts the bot game of the title screen, unless it is already on.
+    pub fn start_demo(&mut self) {
+        if self.demo.is_none() {
+            self.demo = Some(Demo::new(self.versus_config(), self.tick_rate));
+        }
+    }
+
+    /// Starts an endless game from the field of `page`.
+    pub fn start_practice(&mut self, page: Page) {
+        self.mode = GameMode::Endless;
+        self.practice = Some(page);
+        self.start();
+    }
+
+    /// Starts an endless game that starts over on every finesse fault.
+    pub fn start_finesse_trainer(&mut self) {
+        self.mode = GameMode::Endless;
+        self.finesse_trainer = true;
+        self.start();
+    }
+
+    /// The randomizer of the current mode, records are only set with the 7-bag.
+    pub fn randomizer(&self) -> RandomizerType {
+        match self.mode {
+            GameMode::Sprint { .. } | GameMode::Marathon { .. } | GameMode::CheeseRace { .. } => {
+                RandomizerType::SevenBag
+            }
+            GameMode::Endless => self.config.randomizer.clone(),
+        }
+    }
+
+    /// The garbage of the current mode, cheese races start with messy garbage.
+    pub fn garbage(&self) -> GarbagePattern {
+        match self.mode {
+            GameMode::CheeseRace { .. } => GarbagePattern::Messy,
+            _ => self.config.garbage,
+        }
+    }
+
+    /// The board size of the current mode, records are only set on the standard board.
+    pub fn board_size(&self) -> BoardSize {
+        match self.mode {
+            GameMode::Sprint { .. } | GameMode::Marathon { .. } | GameMode::CheeseRace { .. } => {
+                BoardSize::default()
+            }
+            GameMode::Endless => self.config.board_size,
+        }
+    }
+
+    /// Starts the playback of the replay at `index`.
+    pub fn watch(&mut self, index: usize) {
+        let Some(replay) = self.replays.get(index) else { return };
+
+        self.state = GameState::Replay;
+        self.stats = Statistics::default();
+        self.new_record = false;
+        self.game = Some(replay.new_game());
+        self.step = replay.step();
+        self.prev_piece_pos = None;
+        self.player = Some(ReplayPlayer::new(replay.clone()));
+    }
+
+    /// Watches the current replay again from the start.
+    pub fn restart_replay(&mut self) {
+        let Some(player) = &mut self.player else { return };
+
+        player.rewind();
+        self.stats = Statistics::default();
+        self.game = Some(player.replay().new_game());
+        self.step.reset();
+        self.prev_piece_pos = None;
+    }
+
+    /// Keeps the replay of the game that just ended.
+    pub fn keep_recording(&mut self) {
+        let Some(replay) = self.recording.take() else { return };
+
+        self.replays.insert(0, replay);
+        self.replays.truncate(MAX_REPLAYS);
+    }
+
+    /// The lock delay policy chosen for the current mode.
+    pub fn lock_delay_policy(&self) -> LockDelayPolicy {
+        match self.mode {
+            GameMode::Sprint { .. } => self.sprint_lock_delay,
+            GameMode::Marathon { .. } => self.marathon_lock_delay,
+            GameMode::CheeseRace { .. } | GameMode::Endless => self.config.lock_delay_policy,
+        }
+    }
+
+    /// Records the result of a finished game, returns whether it is a new personal best.
+    pub fn submit_result(&mut self) -> bool {
+        let Some(game) = &self.game else { return false };
+        if !game.is_finished() {
+            return false;
+        }
+
+        let record = |lines| TimeRecord {
+            lines,
+            time: game.time,
+            pieces: self.stats.pieces,
+        };
+        match self.mode {
+            GameMode::Sprint { lines } => self.records.submit_sprint(record(lines)),
+            GameMode::CheeseRace { lines } => self.records.submit_cheese(record(lines)),
+            _ => false,
+        }
+    }
+
+    /// Appends the game that just ended to the career history.
+    pub fn submit_history(&mut self) {
+        let Some(game) = &self.game else { return };
+
+        let record = GameRecord {
+            mode: game.config.mode,
+            date: date::now() as u64,
+            seed: self.seed,
+            time: game.time,
+            lines: game.lines,
+            score: game.score_updater.get_score(),
+            pps: self.stats.pps(game.time),
+            result: if game.is_finished() {
+                GameResult::Finished
+            } else {
+                GameResult::ToppedOut
+            },
+        };
+        self.history.push(record, MAX_HISTORY);
     }
 }
 
@@ -184,15 +744,35 @@ impl GameData {
 #[derive(SerJson, DeJson)]
 pub struct UserSettings {
     pub keybind: Option<KeyBind>,
+    pub keybind_p2: Option<KeyBind>,
+    pub pad_bind: Option<PadBind>,
+    pub pad_deadzone: Option<f32>,
     pub arr: Option<f32>,
     pub das: Option<f32>,
     pub soft_drop_gravity: Option<f32>,
+    pub line_clear_delay: Option<f32>,
+    pub sprint_lines: Option<u32>,
+    pub marathon_endless: Option<bool>,
+    pub cheese_lines: Option<u32>,
+    pub versus_bot: Option<bool>,
+    pub bot_pps: Option<f32>,
+    pub relay_addr: Option<String>,
+    pub show_stats: Option<bool>,
+    pub lock_delay: Option<SerLockDelayPolicy>,
+    pub sprint_lock_delay: Option<SerLockDelayPolicy>,
+    pub marathon_lock_delay: Option<SerLockDelayPolicy>,
+    pub tick_rate: Option<u32>,
+    pub randomizer: Option<SerRandomizerType>,
+    pub rotation_system: Option<SerRotationSystemType>,
+    pub board_cols: Option<i32>,
+    pub visible_rows: Option<i32>,
+    pub buffer_rows: Option<i32>,
 }
 
 macro_rules! override_if_some {
-    ($game_data:ident, $user_settings:ident, $field:ident) => {
+    ($target:expr, $user_settings:ident, $field:ident) => {
         if let Some(value) = $user_settings.$field {
-            $game_data.$field = value;
+            $target.$field = value;
         }
     };
 }
@@ -203,12 +783,51 @@ pub fn load_user_settings(game_data: &mut GameData) {
 
     if let Ok(user_settings) = UserSettings::deserialize_json(&json) {
         override_if_some!(game_data, user_settings, keybind);
-        override_if_some!(game_data, user_settings, arr);
-        override_if_some!(game_data, user_settings, soft_drop_gravity);
+        override_if_some!(game_data, user_settings, keybind_p2);
+        override_if_some!(game_data, user_settings, pad_bind);
+        override_if_some!(game_data, user_settings, pad_deadzone);
+        game_data.pad_deadzone = game_data
+            .pad_deadzone
+            .clamp(PAD_DEADZONE_MIN, PAD_DEADZONE_MAX);
+        if game_data.keybind.pause.is_empty() {
+            game_data.keybind.pause = KeyBind::default().pause;
+        }
+        if game_data.keybind.stats.is_empty() {
+            game_data.keybind.stats = KeyBind::default().stats;
+        }
+        override_if_some!(game_data.config, user_settings, arr);
+        override_if_some!(game_data.config, user_settings, das);
+        override_if_some!(game_data.config, user_settings, soft_drop_gravity);
+        override_if_some!(game_data.config, user_settings, line_clear_delay);
+        override_if_some!(game_data, user_settings, sprint_lines);
+        override_if_some!(game_data, user_settings, marathon_endless);
+        override_if_some!(game_data, user_settings, cheese_lines);
+        override_if_some!(game_data, user_settings, versus_bot);
+        override_if_some!(game_data, user_settings, bot_pps);
+        override_if_some!(game_data, user_settings, relay_addr);
+        override_if_some!(game_data, user_settings, show_stats);
+        override_if_some!(game_data, user_settings, tick_rate);
 
-        if let Some(value) = user_settings.das {
-            *game_data.das_timer.get_duration_mut() = value;
+        if let Some(policy) = user_settings.lock_delay {
+            game_data.config.lock_delay_policy = policy.into();
+        }
+        if let Some(policy) = user_settings.sprint_lock_delay {
+            game_data.sprint_lock_delay = policy.into();
         }
+        if let Some(policy) = user_settings.marathon_lock_delay {
+            game_data.marathon_lock_delay = policy.into();
+        }
+        if let Some(randomizer) = user_settings.randomizer {
+            game_data.config.randomizer = randomizer_from(randomizer, None);
+        }
+        if let Some(system) = user_settings.rotation_system {
+            game_data.config.rotation_system = system.into();
+        }
+        game_data.config.board_size = board_size_from(
+            user_settings.board_cols,
+            user_settings.visible_rows,
+            user_settings.buffer_rows,
+        );
     }
 }
 
@@ -218,14 +837,526 @@ pub fn save_user_settings(game_data: &GameData) {
         "user_settings",
         &UserSettings {
             keybind: Some(game_data.keybind.clone()),
-            arr: Some(game_data.arr),
-            das: Some(game_data.das_timer.get_duration()),
-            soft_drop_gravity: Some(game_data.soft_drop_gravity),
+            keybind_p2: Some(game_data.keybind_p2.clone()),
+            pad_bind: Some(game_data.pad_bind),
+            pad_deadzone: Some(game_data.pad_deadzone),
+            arr: Some(game_data.config.arr),
+            das: Some(game_data.config.das),
+            soft_drop_gravity: Some(game_data.config.soft_drop_gravity),
+            line_clear_delay: Some(game_data.config.line_clear_delay),
+            sprint_lines: Some(game_data.sprint_lines),
+            marathon_endless: Some(game_data.marathon_endless),
+            cheese_lines: Some(game_data.cheese_lines),
+            versus_bot: Some(game_data.versus_bot),
+            bot_pps: Some(game_data.bot_pps),
+            relay_addr: Some(game_data.relay_addr.clone()),
+            show_stats: Some(game_data.show_stats),
+            lock_delay: Some(game_data.config.lock_delay_policy.into()),
+            sprint_lock_delay: Some(game_data.sprint_lock_delay.into()),
+            marathon_lock_delay: Some(game_data.marathon_lock_delay.into()),
+            tick_rate: Some(game_data.tick_rate),
+            randomizer: Some((&game_data.config.randomizer).into()),
+            rotation_system: Some(game_data.config.rotation_system.into()),
+            board_cols: Some(game_data.config.board_size.cols as i32),
+            visible_rows: Some(game_data.config.board_size.visible_rows as i32),
+            buffer_rows: Some(game_data.config.board_size.buffer_rows as i32),
         }
         .serialize_json(),
     );
 }
 
+/// Fastest time to clear `lines` lines, garbage rows in cheese races.
+#[derive(SerJson, DeJson, Clone)]
+pub struct TimeRecord {
+    pub lines: u32,
+    /// Completion time in seconds
+    pub time: f32,
+    pub pieces: u32,
+}
+
+/// Personal bests, one per mode and goal.
+#[derive(SerJson, DeJson, Default)]
+pub struct Records {
+    pub sprint: Vec<TimeRecord>,
+    #[nserde(default)]
+    pub cheese: Vec<TimeRecord>,
+}
+
+impl Records {
+    pub fn sprint_best(&self, lines: u32) -> Option<&TimeRecord> {
+        self.sprint.iter().find(|r| r.lines == lines)
+    }
+
+    pub fn cheese_best(&self, lines: u32) -> Option<&TimeRecord> {
+        self.cheese.iter().find(|r| r.lines == lines)
+    }
+
+    /// Keeps the record if it beats the current best, returns whether it did.
+    pub fn submit_sprint(&mut self, record: TimeRecord) -> bool {
+        submit_time(&mut self.sprint, record)
+    }
+
+    /// Keeps the record if it beats the current best, returns whether it did.
+    pub fn submit_cheese(&mut self, record: TimeRecord) -> bool {
+        submit_time(&mut self.cheese, record)
+    }
+}
+
+fn submit_time(records: &mut Vec<TimeRecord>, record: TimeRecord) -> bool {
+    match records.iter_mut().find(|r| r.lines == record.lines) {
+        Some(best) if best.time <= record.time => false,
+        Some(best) => {
+            *best = record;
+            true
+        }
+        None => {
+            records.push(record);
+            true
+        }
+    }
+}
+
+pub fn load_records(game_data: &mut GameData) {
+    let store = quad_storage::STORAGE.lock().unwrap();
+    let json = store.get("records").unwrap_or_default();
+
+    if let Ok(records) = Records::deserialize_json(&json) {
+        game_data.records = records;
+    }
+}
+
+pub fn save_records(game_data: &GameData) {
+    let mut store = quad_storage::STORAGE.lock().unwrap();
+    store.set("records", &game_data.records.serialize_json());
+}
+
+pub fn load_history(game_data: &mut GameData) {
+    let store = quad_storage::STORAGE.lock().unwrap();
+    let json = store.get("history").unwrap_or_default();
+
+    if let Ok(games) = Vec::<SerGameRecord>::deserialize_json(&json) {
+        game_data.history.games = games.into_iter().map(GameRecord::from).collect();
+    }
+}
+
+pub fn save_history(game_data: &GameData) {
+    let mut store = quad_storage::STORAGE.lock().unwrap();
+    let games: Vec<SerGameRecord> = game_data
+        .history
+        .games
+        .iter()
+        .map(SerGameRecord::from)
+        .collect();
+    store.set("history", &games.serialize_json());
+}
+
+/// The history to export, with the columns of the CSV export.
+pub fn history_json(history: &History) -> String {
+    let games: Vec<ExportedGameRecord> =
+        history.games.iter().map(ExportedGameRecord::from).collect();
+    games.serialize_json()
+}
+
+#[derive(SerJson)]
+pub struct ExportedGameRecord {
+    /// ISO 8601, in UTC
+    pub date: String,
+    pub mode: String,
+    pub goal: Option<u32>,
+    pub seed: u64,
+    pub time: f32,
+    pub lines: u32,
+    pub score: u32,
+    pub pps: f32,
+    pub result: String,
+}
+
+impl From<&GameRecord> for ExportedGameRecord {
+    fn from(r: &GameRecord) -> Self {
+        let (mode, goal) = r.mode_name();
+        Self {
+            date: date_to_string(r.date),
+            mode: mode.to_string(),
+            goal,
+            seed: r.seed,
+            time: r.time,
+            lines: r.lines,
+            score: r.score,
+            pps: r.pps,
+            result: r.result_name().to_string(),
+        }
+    }
+}
+
+/// A game of the history as stored, exports use `ExportedGameRecord`.
+#[derive(SerJson, DeJson)]
+pub struct SerGameRecord {
+    pub sprint_lines: Option<u32>,
+    pub marathon_endless: Option<bool>,
+    pub cheese_lines: Option<u32>,
+    /// Seconds since the Unix epoch
+    pub date: u64,
+    pub seed: u64,
+    pub time: f32,
+    pub lines: u32,
+    pub score: u32,
+    pub pps: f32,
+    pub finished: bool,
+}
+
+impl From<&GameRecord> for SerGameRecord {
+    fn from(r: &GameRecord) -> Self {
+        let (sprint_lines, marathon_endless, cheese_lines) = mode_fields(r.mode);
+        Self {
+            sprint_lines,
+            marathon_endless,
+            cheese_lines,
+            date: r.date,
+            seed: r.seed,
+            time: r.time,
+            lines: r.lines,
+            score: r.score,
+            pps: r.pps,
+            finished: r.result == GameResult::Finished,
+        }
+    }
+}
+
+impl From<SerGameRecord> for GameRecord {
+    fn from(r: SerGameRecord) -> Self {
+        Self {
+            mode: mode_from(r.sprint_lines, r.marathon_endless, r.cheese_lines),
+            date: r.date,
+            seed: r.seed,
+            time: r.time,
+            lines: r.lines,
+            score: r.score,
+            pps: r.pps,
+            result: if r.finished {
+                GameResult::Finished
+            } else {
+                GameResult::ToppedOut
+            },
+        }
+    }
+}
+
+pub fn load_replays(game_data: &mut GameData) {
+    let store = quad_storage::STORAGE.lock().unwrap();
+    let json = store.get("replays").unwrap_or_default();
+
+    if let Ok(replays) = Vec::<SerReplay>::deserialize_json(&json) {
+        game_data.replays = replays.into_iter().map(Replay::from).collect();
+    }
+}
+
+pub fn save_replays(game_data: &GameData) {
+    let replays: Vec<SerReplay> = game_data.replays.iter().map(SerReplay::from).collect();
+
+    let mut store = quad_storage::STORAGE.lock().unwrap();
+    store.set("replays", &replays.serialize_json());
+}
+
+#[derive(SerJson, DeJson)]
+pub struct SerReplay {
+    pub seed: u64,
+    pub config: SerGameConfig,
+    /// Missing from replays recorded at 60 Hz before the tick rate was configurable
+    pub tick_rate: Option<u32>,
+    /// Inputs run-length encoded, see `Input::to_bits`
+    pub inputs: Vec<u16>,
+    pub ticks: Vec<u32>,
+}
+
+impl From<&Replay> for SerReplay {
+    fn from(r: &Replay) -> Self {
+        Self {
+            seed: r.seed,
+            config: SerGameConfig::from(&r.config),
+            tick_rate: Some(r.tick_rate),
+            inputs: r.runs().iter().map(|(input, _)| input.to_bits()).collect(),
+            ticks: r.runs().iter().map(|&(_, ticks)| ticks).collect(),
+        }
+    }
+}
+
+impl From<SerReplay> for Replay {
+    fn from(r: SerReplay) -> Self {
+        let runs = r
+            .inputs
+            .into_iter()
+            .map(Input::from_bits)
+            .zip(r.ticks)
+            .collect();
+        Replay::from_runs(
+            r.seed,
+            r.config.into(),
+            r.tick_rate.unwrap_or(FRAME_RATE),
+            runs,
+        )
+    }
+}
+
+#[derive(SerJson, DeJson)]
+pub struct SerGameConfig {
+    pub gravity: f32,
+    pub soft_drop_gravity: f32,
+    pub das: f32,
+    pub arr: f32,
+    pub lock_delay: f32,
+    pub lock_delay_policy: SerLockDelayPolicy,
+    pub spawn_delay: f32,
+    pub line_clear_delay: f32,
+    pub preview_size: usize,
+    /// Missing from replays recorded before the randomizer was configurable
+    pub randomizer: Option<SerRandomizerType>,
+    /// Pieces of a fixed sequence, as letters
+    pub sequence: Option<String>,
+    /// Missing from replays recorded before the rotation system was configurable
+    pub rotation_system: Option<SerRotationSystemType>,
+    /// Missing from replays recorded before the board size was configurable
+    pub board_cols: Option<i32>,
+    pub visible_rows: Option<i32>,
+    pub buffer_rows: Option<i32>,
+    /// Missing from replays recorded before garbage existed
+    pub garbage: Option<SerGarbagePattern>,
+    /// Hole change chance of cheese garbage
+    pub cheese_percent: Option<u32>,
+    pub sprint_lines: Option<u32>,
+    pub marathon_endless: Option<bool>,
+    pub cheese_lines: Option<u32>,
+}
+
+impl From<&GameConfig> for SerGameConfig {
+    fn from(c: &GameConfig) -> Self {
+        let (sprint_lines, marathon_endless, cheese_lines) = mode_fields(c.mode);
+        let (garbage, cheese_percent) = match c.garbage {
+            GarbagePattern::Clean => (SerGarbagePattern::Clean, None),
+            GarbagePattern::Messy => (SerGarbagePattern::Messy, None),
+            GarbagePattern::Cheese(percent) => (SerGarbagePattern::Cheese, Some(percent)),
+        };
+        let sequence = match &c.randomizer {
+            RandomizerType::Sequence(pieces) => {
+                Some(pieces.iter().map(|p| format!("{:?}", p)).collect())
+            }
+            _ => None,
+        };
+
+        Self {
+            gravity: c.gravity,
+            soft_drop_gravity: c.soft_drop_gravity,
+            das: c.das,
+            arr: c.arr,
+            lock_delay: c.lock_delay,
+            lock_delay_policy: c.lock_delay_policy.into(),
+            spawn_delay: c.spawn_delay,
+            line_clear_delay: c.line_clear_delay,
+            preview_size: c.preview_size,
+            randomizer: Some((&c.randomizer).into()),
+            sequence,
+            rotation_system: Some(c.rotation_system.into()),
+            board_cols: Some(c.board_size.cols as i32),
+            visible_rows: Some(c.board_size.visible_rows as i32),
+            buffer_rows: Some(c.board_size.buffer_rows as i32),
+            garbage: Some(garbage),
+            cheese_percent,
+            sprint_lines,
+            marathon_endless,
+            cheese_lines,
+        }
+    }
+}
+
+impl From<SerGameConfig> for GameConfig {
+    fn from(c: SerGameConfig) -> Self {
+        let mode = mode_from(c.sprint_lines, c.marathon_endless, c.cheese_lines);
+
+        Self {
+            gravity: c.gravity,
+            soft_drop_gravity: c.soft_drop_gravity,
+            das: c.das,
+            arr: c.arr,
+            lock_delay: c.lock_delay,
+            lock_delay_policy: c.lock_delay_policy.into(),
+            spawn_delay: c.spawn_delay,
+            line_clear_delay: c.line_clear_delay,
+            preview_size: c.preview_size,
+            randomizer: c
+                .randomizer
+                .map_or(RandomizerType::SevenBag, |r| randomizer_from(r, c.sequence)),
+            rotation_system: c
+                .rotation_system
+                .map_or(RotationSystemType::Srs, Into::into),
+            board_size: board_size_from(c.board_cols, c.visible_rows, c.buffer_rows),
+            garbage: match c.garbage {
+                Some(SerGarbagePattern::Messy) => GarbagePattern::Messy,
+                Some(SerGarbagePattern::Cheese) => {
+                    GarbagePattern::Cheese(c.cheese_percent.unwrap_or_default())
+                }
+                _ => GarbagePattern::Clean,
+            },
+            mode,
+        }
+    }
+}
+
+/// A mode as the optional goal fields of its serialized form, all
+/// missing for endless games.
+fn mode_fields(mode: GameMode) -> (Option<u32>, Option<bool>, Option<u32>) {
+    match mode {
+        GameMode::Endless => (None, None, None),
+        GameMode::Sprint { lines } => (Some(lines), None, None),
+        GameMode::Marathon { endless } => (None, Some(endless), None),
+        GameMode::CheeseRace { lines } => (None, None, Some(lines)),
+    }
+}
+
+fn mode_from(
+    sprint_lines: Option<u32>,
+    marathon_endless: Option<bool>,
+    cheese_lines: Option<u32>,
+) -> GameMode {
+    match (sprint_lines, marathon_endless, cheese_lines) {
+        (Some(lines), _, _) => GameMode::Sprint { lines },
+        (_, Some(endless), _) => GameMode::Marathon { endless },
+        (_, _, Some(lines)) => GameMode::CheeseRace { lines },
+        _ => GameMode::Endless,
+    }
+}
+
+#[derive(SerJson, DeJson)]
+pub enum SerGarbagePattern {
+    Clean,
+    Messy,
+    Cheese,
+}
+
+/// Missing dimensions are the standard ones.
+fn board_size_from(
+    cols: Option<i32>,
+    visible_rows: Option<i32>,
+    buffer_rows: Option<i32>,
+) -> BoardSize {
+    let default = BoardSize::default();
+    BoardSize {
+        cols: cols.map_or(default.cols, |c| c as isize),
+        visible_rows: visible_rows.map_or(default.visible_rows, |r| r as isize),
+        buffer_rows: buffer_rows.map_or(default.buffer_rows, |r| r as isize),
+    }
+}
+
+#[derive(SerJson, DeJson)]
+pub enum SerRandomizerType {
+    SevenBag,
+    FourteenBag,
+    Random,
+    Tgm1,
+    Tgm2,
+    Tgm3,
+    Nes,
+    Sequence,
+}
+
+impl From<&RandomizerType> for SerRandomizerType {
+    fn from(r: &RandomizerType) -> Self {
+        match r {
+            RandomizerType::SevenBag => SerRandomizerType::SevenBag,
+            RandomizerType::FourteenBag => SerRandomizerType::FourteenBag,
+            RandomizerType::Random => SerRandomizerType::Random,
+            RandomizerType::Tgm1 => SerRandomizerType::Tgm1,
+            RandomizerType::Tgm2 => SerRandomizerType::Tgm2,
+            RandomizerType::Tgm3 => SerRandomizerType::Tgm3,
+            RandomizerType::Nes => SerRandomizerType::Nes,
+            RandomizerType::Sequence(_) => SerRandomizerType::Sequence,
+        }
+    }
+}
+
+/// Falls back to the 7-bag when a sequence has no valid piece.
+fn randomizer_from(r: SerRandomizerType, sequence: Option<String>) -> RandomizerType {
+    match r {
+        SerRandomizerType::SevenBag => RandomizerType::SevenBag,
+        SerRandomizerType::FourteenBag => RandomizerType::FourteenBag,
+        SerRandomizerType::Random => RandomizerType::Random,
+        SerRandomizerType::Tgm1 => RandomizerType::Tgm1,
+        SerRandomizerType::Tgm2 => RandomizerType::Tgm2,
+        SerRandomizerType::Tgm3 => RandomizerType::Tgm3,
+        SerRandomizerType::Nes => RandomizerType::Nes,
+        SerRandomizerType::Sequence => {
+            let pieces: Vec<PieceType> = sequence
+                .unwrap_or_default()
+                .chars()
+                .filter_map(|c| c.to_string().parse().ok())
+                .collect();
+            if pieces.is_empty() {
+                RandomizerType::SevenBag
+            } else {
+                RandomizerType::Sequence(pieces)
+            }
+        }
+    }
+}
+
+#[derive(SerJson, DeJson)]
+pub enum SerRotationSystemType {
+    Srs,
+    SrsPlus,
+    Ars,
+    Classic,
+    SrsNo180,
+}
+
+impl From<RotationSystemType> for SerRotationSystemType {
+    fn from(r: RotationSystemType) -> Self {
+        match r {
+            RotationSystemType::Srs => SerRotationSystemType::Srs,
+            RotationSystemType::SrsPlus => SerRotationSystemType::SrsPlus,
+            RotationSystemType::Ars => SerRotationSystemType::Ars,
+            RotationSystemType::Classic => SerRotationSystemType::Classic,
+            RotationSystemType::SrsNo180 => SerRotationSystemType::SrsNo180,
+        }
+    }
+}
+
+impl From<SerRotationSystemType> for RotationSystemType {
+    fn from(r: SerRotationSystemType) -> Self {
+        match r {
+            SerRotationSystemType::Srs => RotationSystemType::Srs,
+            SerRotationSystemType::SrsPlus => RotationSystemType::SrsPlus,
+            SerRotationSystemType::Ars => RotationSystemType::Ars,
+            SerRotationSystemType::Classic => RotationSystemType::Classic,
+            SerRotationSystemType::SrsNo180 => RotationSystemType::SrsNo180,
+        }
+    }
+}
+
+#[derive(SerJson, DeJson)]
+pub enum SerLockDelayPolicy {
+    MoveReset,
+    Classic,
+    Infinity,
+}
+
+impl From<LockDelayPolicy> for SerLockDelayPolicy {
+    fn from(p: LockDelayPolicy) -> Self {
+        match p {
+            LockDelayPolicy::MoveReset => SerLockDelayPolicy::MoveReset,
+            LockDelayPolicy::Classic => SerLockDelayPolicy::Classic,
+            LockDelayPolicy::Infinity => SerLockDelayPolicy::Infinity,
+        }
+    }
+}
+
+impl From<SerLockDelayPolicy> for LockDelayPolicy {
+    fn from(p: SerLockDelayPolicy) -> Self {
+        match p {
+            SerLockDelayPolicy::MoveReset => LockDelayPolicy::MoveReset,
+            SerLockDelayPolicy::Classic => LockDelayPolicy::Classic,
+            SerLockDelayPolicy::Infinity => LockDelayPolicy::Infinity,
+        }
+    }
+}
+
 impl SerJson for Key {
     fn ser_json(&self, d: usize, s: &mut nanoserde::SerJsonState) {
         SerKeyCode::from(self.0).ser_json(d, s)
@@ -242,6 +1373,120 @@ impl DeJson for Key {
     }
 }
 
+impl SerJson for Keys {
+    fn ser_json(&self, d: usize, s: &mut nanoserde::SerJsonState) {
+        self.0.ser_json(d, s)
+    }
+}
+
+/// Settings saved before actions took several keys hold a single key,
+/// `Unknown` for the ones left unbound.
+impl DeJson for Keys {
+    fn de_json(
+        state: &mut nanoserde::DeJsonState,
+        input: &mut std::str::Chars,
+    ) -> Result<Self, nanoserde::DeJsonErr> {
+        let keys = if state.tok == nanoserde::DeJsonTok::BlockOpen {
+            Vec::<Key>::de_json(state, input)?
+        } else {
+            vec![Key::de_json(state, input)?]
+        };
+        Ok(Keys(
+            keys.into_iter()
+                .filter(|k| k.code() != KeyCode::Unknown)
+                .collect(),
+        ))
+    }
+}
+
+impl SerJson for PadKey {
+    fn ser_json(&self, d: usize, s: &mut nanoserde::SerJsonState) {
+        self.0.map(SerButton::from).ser_json(d, s)
+    }
+}
+
+impl DeJson for PadKey {
+    fn de_json(
+        state: &mut nanoserde::DeJsonState,
+        input: &mut std::str::Chars,
+    ) -> Result<Self, nanoserde::DeJsonErr> {
+        let button = Option::<SerButton>::de_json(state, input)?;
+        Ok(PadKey(button.map(Button::from)))
+    }
+}
+
+#[derive(SerJson, DeJson)]
+enum SerButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    StickUp,
+    StickDown,
+    StickLeft,
+    StickRight,
+}
+
+impl From<Button> for SerButton {
+    fn from(b: Button) -> Self {
+        match b {
+            Button::South => SerButton::South,
+            Button::East => SerButton::East,
+            Button::West => SerButton::West,
+            Button::North => SerButton::North,
+            Button::LeftBumper => SerButton::LeftBumper,
+            Button::RightBumper => SerButton::RightBumper,
+            Button::LeftTrigger => SerButton::LeftTrigger,
+            Button::RightTrigger => SerButton::RightTrigger,
+            Button::Select => SerButton::Select,
+            Button::Start => SerButton::Start,
+            Button::DPadUp => SerButton::DPadUp,
+            Button::DPadDown => SerButton::DPadDown,
+            Button::DPadLeft => SerButton::DPadLeft,
+            Button::DPadRight => SerButton::DPadRight,
+            Button::StickUp => SerButton::StickUp,
+            Button::StickDown => SerButton::StickDown,
+            Button::StickLeft => SerButton::StickLeft,
+            Button::StickRight => SerButton::StickRight,
+        }
+    }
+}
+
+impl From<SerButton> for Button {
+    fn from(b: SerButton) -> Self {
+        match b {
+            SerButton::South => Button::South,
+            SerButton::East => Button::East,
+            SerButton::West => Button::West,
+            SerButton::North => Button::North,
+            SerButton::LeftBumper => Button::LeftBumper,
+            SerButton::RightBumper => Button::RightBumper,
+            SerButton::LeftTrigger => Button::LeftTrigger,
+            SerButton::RightTrigger => Button::RightTrigger,
+            SerButton::Select => Button::Select,
+            SerButton::Start => Button::Start,
+            SerButton::DPadUp => Button::DPadUp,
+            SerButton::DPadDown => Button::DPadDown,
+            SerButton::DPadLeft => Button::DPadLeft,
+            SerButton::DPadRight => Button::DPadRight,
+            SerButton::StickUp => Button::StickUp,
+            SerButton::StickDown => Button::StickDown,
+            SerButton::StickLeft => Button::StickLeft,
+            SerButton::StickRight => Button::StickRight,
+        }
+    }
+}
+
 #[derive(SerJson, DeJson)]
 enum SerKeyCode {
     Space,
@@ -0,0 +1,112 @@
+//! The gamepad, read once per frame and queried like the keyboard.
+use std::cell::RefCell;
+
+use rust_tetris_core::{
+    gamepad::{Button, Gamepad, GamepadBackend, PadReading},
+    input::{Action, Input},
+};
+
+use crate::constants::PAD_DEADZONE_DEFAULT;
+
+thread_local! {
+    static GAMEPAD: RefCell<Gamepad> = RefCell::new(Gamepad::new(backend(), PAD_DEADZONE_DEFAULT));
+}
+
+/// Reads the gamepad, before anything queries it on this frame.
+pub fn update(deadzone: f32) {
+    GAMEPAD.with(|pad| {
+        let mut pad = pad.borrow_mut();
+        pad.deadzone = deadzone;
+        pad.update();
+    });
+}
+
+pub fn is_button_pressed(button: Button) -> bool {
+    GAMEPAD.with(|pad| pad.borrow().is_pressed(button))
+}
+
+pub fn get_last_button_pressed() -> Option<Button> {
+    GAMEPAD.with(|pad| pad.borrow().last_pressed())
+}
+
+/// Samples the buttons bound to gameplay actions into an input snapshot.
+pub fn input(bindings: impl IntoIterator<Item = (Action, Option<Button>)>) -> Input {
+    GAMEPAD.with(|pad| pad.borrow().input(bindings))
+}
+
+/// Builds without the `gamepad` feature, and web builds, have no gamepad.
+struct NoGamepad;
+
+impl GamepadBackend for NoGamepad {
+    fn read(&mut self) -> Option<PadReading> {
+        None
+    }
+}
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+fn backend() -> Box<dyn GamepadBackend> {
+    match gilrs::Gilrs::new() {
+        Ok(gilrs) => Box::new(GilrsBackend(gilrs)),
+        Err(error) => {
+            macroquad::logging::warn!("No gamepad support: {}", error);
+            Box::new(NoGamepad)
+        }
+    }
+}
+
+#[cfg(not(all(feature = "gamepad", not(target_arch = "wasm32"))))]
+fn backend() -> Box<dyn GamepadBackend> {
+    Box::new(NoGamepad)
+}
+
+/// Reads the first connected gamepad.
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+struct GilrsBackend(gilrs::Gilrs);
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+impl GamepadBackend for GilrsBackend {
+    fn read(&mut self) -> Option<PadReading> {
+        use gilrs::Axis;
+
+        // the state of the gamepads is only updated by going through the events
+        while self.0.next_event().is_some() {}
+
+        let (_, pad) = self.0.gamepads().find(|(_, pad)| pad.is_connected())?;
+        let buttons = Button::ALL
+            .into_iter()
+            .filter(|button| {
+                gilrs_button(*button).is_some_and(|gilrs_button| pad.is_pressed(gilrs_button))
+            })
+            .collect();
+
+        Some(PadReading {
+            buttons,
+            stick: (pad.value(Axis::LeftStickX), pad.value(Axis::LeftStickY)),
+        })
+    }
+}
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+fn gilrs_button(button: Button) -> Option<gilrs::Button> {
+    use gilrs::Button as G;
+
+    Some(match button {
+        Button::South => G::South,
+        Button::East => G::East,
+        Button::West => G::West,
+        Button::North => G::North,
+        Button::LeftBumper => G::LeftTrigger,
+        Button::RightBumper => G::RightTrigger,
+        Button::LeftTrigger => G::LeftTrigger2,
+        Button::RightTrigger => G::RightTrigger2,
+        Button::Select => G::Select,
+        Button::Start => G::Start,
+        Button::DPadUp => G::DPadUp,
+        Button::DPadDown => G::DPadDown,
+        Button::DPadLeft => G::DPadLeft,
+        Button::DPadRight => G::DPadRight,
+        Button::StickUp | Button::StickDown | Button::StickLeft | Button::StickRight => {
+            return None
+        }
+    })
+}
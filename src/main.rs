@@ -1,10 +1,15 @@
 mod app;
 mod asset;
 mod constants;
+mod demo;
+mod focus;
 mod game_data;
+mod gamepad;
 mod menu;
+mod online;
 mod renderer;
 mod sound;
+mod versus;
 
 #[macroquad::main("Tetris")]
 async fn main() {
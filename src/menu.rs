@@ -1,8 +1,10 @@
 use macroquad::prelude::*;
+use rust_tetris_core::gamepad::Button as PadButton;
 
 use crate::{
     constants::FONT_SIZE,
-    game_data::Key,
+    game_data::{self, Key, Keys, PadKey},
+    gamepad::get_last_button_pressed,
     renderer::text::{self, TextRenderer},
 };
 
@@ -10,6 +12,10 @@ pub struct MenuCtx {
     pub states: Vec<MenuState>,
     pub curr_pointer: i32,
     pub modifying: bool,
+    /// A text field takes every key until Enter or Escape
+    pub typing: bool,
+    /// Gamepad buttons navigating the menu along with the arrow keys
+    pub pad_bind: game_data::PadBind,
 }
 
 impl MenuCtx {
@@ -18,6 +24,8 @@ impl MenuCtx {
             states: vec![MenuState::Main],
             curr_pointer: 0,
             modifying: false,
+            typing: false,
+            pad_bind: game_data::PadBind::default(),
         }
     }
 
@@ -32,6 +40,30 @@ impl MenuCtx {
         }
     }
 
+    /// The menu key was pressed on the keyboard or the gamepad.
+    pub fn is_pressed(&self, key: MenuKey) -> bool {
+        let (code, pad_key) = match key {
+            MenuKey::Up => (KeyCode::Up, self.pad_bind.menu_up),
+            MenuKey::Down => (KeyCode::Down, self.pad_bind.menu_down),
+            MenuKey::Left => (KeyCode::Left, self.pad_bind.menu_left),
+            MenuKey::Right => (KeyCode::Right, self.pad_bind.menu_right),
+            MenuKey::Confirm => (KeyCode::Enter, self.pad_bind.menu_confirm),
+            MenuKey::Back => (KeyCode::Escape, self.pad_bind.menu_back),
+        };
+        is_key_pressed(code) || pad_key.is_pressed()
+    }
+
+    /// Moves the pointer with the arrow keys within a menu of `menu_len` widgets.
+    pub fn move_pointer(&mut self, menu_len: usize) {
+        if self.is_pressed(MenuKey::Down) {
+            self.curr_pointer += 1;
+        } else if self.is_pressed(MenuKey::Up) {
+            self.curr_pointer -= 1;
+        }
+
+        self.curr_pointer = self.curr_pointer.clamp(0, menu_len as i32 - 1);
+    }
+
     pub fn push_state(&mut self, state: MenuState) {
         if state != self.curr_state() {
             self.states.push(state);
@@ -40,10 +72,37 @@ impl MenuCtx {
     }
 }
 
+#[derive(Clone, Copy)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum MenuState {
     Main,
+    Sprint,
+    Marathon,
+    CheeseRace,
+    Replays,
+    /// Career history
+    Stats,
     Settings,
+    /// Keys of the first player
+    Keys,
+    /// Gamepad buttons and deadzone
+    Gamepad,
+    /// Board size of endless games
+    Board,
+    Versus,
+    /// Keys of the second versus player
+    VersusKeys,
+    /// Room of online matches
+    Online,
 }
 
 pub struct Menu<'a> {
@@ -123,7 +182,7 @@ impl<'a> MenuWidget for Button<'a> {
     }
 
     fn handle_input(&mut self, ctx: &mut MenuCtx) {
-        if self.id == ctx.curr_pointer && is_key_pressed(KeyCode::Enter) {
+        if self.id == ctx.curr_pointer && ctx.is_pressed(MenuKey::Confirm) {
             (self.callback)();
         }
     }
@@ -205,9 +264,9 @@ impl<'a, T: PartialEq + Clone> MenuWidget for Selector<'a, T> {
 
     fn handle_input(&mut self, ctx: &mut MenuCtx) {
         if self.id == ctx.curr_pointer {
-            if is_key_pressed(KeyCode::Left) {
+            if ctx.is_pressed(MenuKey::Left) {
                 self.select_left();
-            } else if is_key_pressed(KeyCode::Right) {
+            } else if ctx.is_pressed(MenuKey::Right) {
                 self.select_right();
             }
         }
@@ -222,14 +281,15 @@ impl<'a, T: PartialEq + Clone> MenuWidget for Selector<'a, T> {
     }
 }
 
+/// Enter then a key binds it to the action, or unbinds it if it was bound already.
 pub struct KeyBind<'a> {
-    value: &'a mut Key,
+    value: &'a mut Keys,
     label: &'a str,
     id: i32,
 }
 
 impl<'a> KeyBind<'a> {
-    pub fn new(value: &'a mut Key, label: &'a str) -> Self {
+    pub fn new(value: &'a mut Keys, label: &'a str) -> Self {
         Self {
             value,
             label,
@@ -243,7 +303,15 @@ impl<'a> MenuWidget for KeyBind<'a> {
         let key = if ctx.modifying && self.id == ctx.curr_pointer {
             "WAIT".to_string()
         } else {
-            format!("{:?}", self.value.code())
+            // the first key and how many more, to fit the column
+            match self.value.keys() {
+                [] => "-".to_string(),
+                [key] => key_label(key.code()),
+                [key, more @ ..] => {
+                    let label: String = key_label(key.code()).chars().take(3).collect();
+                    format!("{}+{}", label, more.len())
+                }
+            }
         };
 
         let text = format!("{:<11}{:>5}", self.label, key);
@@ -270,11 +338,73 @@ impl<'a> MenuWidget for KeyBind<'a> {
                 match keycode {
                     KeyCode::Escape | KeyCode::Enter => {}
                     _ => {
-                        *self.value = Key::new(keycode);
+                        self.value.toggle(Key::new(keycode));
                     }
                 }
             }
-        } else if is_key_pressed(KeyCode::Enter) {
+        } else if ctx.is_pressed(MenuKey::Confirm) {
+            ctx.modifying = true;
+        }
+    }
+
+    fn get_height(&self) -> f32 {
+        FONT_SIZE
+    }
+
+    fn insert_menu(&mut self, id: i32) {
+        self.id = id;
+    }
+}
+
+pub struct PadBind<'a> {
+    value: &'a mut PadKey,
+    label: &'a str,
+    id: i32,
+}
+
+impl<'a> PadBind<'a> {
+    pub fn new(value: &'a mut PadKey, label: &'a str) -> Self {
+        Self {
+            value,
+            label,
+            id: -1,
+        }
+    }
+}
+
+impl<'a> MenuWidget for PadBind<'a> {
+    fn draw(&self, position: Vec2, text_renderer: &TextRenderer, ctx: &MenuCtx) {
+        let button = if ctx.modifying && self.id == ctx.curr_pointer {
+            "WAIT"
+        } else {
+            self.value.button().map_or("-", button_label)
+        };
+
+        let text = format!("{:<11}{:>5}", self.label, button);
+
+        text_renderer.draw_text(
+            &text,
+            position,
+            FONT_SIZE,
+            if self.id == ctx.curr_pointer {
+                text::Color::Cream
+            } else {
+                text::Color::Magenta
+            },
+        );
+    }
+
+    fn handle_input(&mut self, ctx: &mut MenuCtx) {
+        if self.id != ctx.curr_pointer {
+            return;
+        }
+
+        // any key of the keyboard cancels, every button can be bound
+        if ctx.modifying {
+            if let Some(button) = get_last_button_pressed() {
+                *self.value = PadKey::new(button);
+            }
+        } else if ctx.is_pressed(MenuKey::Confirm) {
             ctx.modifying = true;
         }
     }
@@ -288,6 +418,154 @@ impl<'a> MenuWidget for KeyBind<'a> {
     }
 }
 
+/// Names of at most five characters.
+pub fn key_label(code: KeyCode) -> String {
+    let label = match code {
+        KeyCode::Space => "SPACE",
+        KeyCode::Apostrophe => "'",
+        KeyCode::Comma => ",",
+        KeyCode::Minus => "-",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        KeyCode::Semicolon => ";",
+        KeyCode::Equal => "=",
+        KeyCode::LeftBracket => "[",
+        KeyCode::Backslash => "\\",
+        KeyCode::RightBracket => "]",
+        KeyCode::GraveAccent => "`",
+        KeyCode::Escape => "ESC",
+        KeyCode::Backspace => "BKSP",
+        KeyCode::Insert => "INS",
+        KeyCode::Delete => "DEL",
+        KeyCode::PageUp => "PGUP",
+        KeyCode::PageDown => "PGDN",
+        KeyCode::CapsLock => "CAPS",
+        KeyCode::ScrollLock => "SCRLK",
+        KeyCode::NumLock => "NUMLK",
+        KeyCode::PrintScreen => "PRTSC",
+        KeyCode::LeftShift => "LSHFT",
+        KeyCode::LeftControl => "LCTRL",
+        KeyCode::LeftAlt => "LALT",
+        KeyCode::LeftSuper => "LSUPR",
+        KeyCode::RightShift => "RSHFT",
+        KeyCode::RightControl => "RCTRL",
+        KeyCode::RightAlt => "RALT",
+        KeyCode::RightSuper => "RSUPR",
+        _ => {
+            let name = format!("{:?}", code).to_uppercase();
+            let name = name.strip_prefix("KEY").unwrap_or(&name);
+            return name.chars().take(5).collect();
+        }
+    };
+    label.to_string()
+}
+
+pub fn button_label(button: PadButton) -> &'static str {
+    match button {
+        PadButton::South => "SOUTH",
+        PadButton::East => "EAST",
+        PadButton::West => "WEST",
+        PadButton::North => "NORTH",
+        PadButton::LeftBumper => "LB",
+        PadButton::RightBumper => "RB",
+        PadButton::LeftTrigger => "LT",
+        PadButton::RightTrigger => "RT",
+        PadButton::Select => "SELECT",
+        PadButton::Start => "START",
+        PadButton::DPadUp => "D UP",
+        PadButton::DPadDown => "D DN",
+        PadButton::DPadLeft => "D LT",
+        PadButton::DPadRight => "D RT",
+        PadButton::StickUp => "S UP",
+        PadButton::StickDown => "S DN",
+        PadButton::StickLeft => "S LT",
+        PadButton::StickRight => "S RT",
+    }
+}
+
+/// Enter starts typing into the text, Enter or Escape ends it.
+pub struct TextField<'a> {
+    label: &'a str,
+    value: &'a mut String,
+    max_len: usize,
+    id: i32,
+}
+
+impl<'a> TextField<'a> {
+    pub fn new(label: &'a str, value: &'a mut String, max_len: usize) -> Self {
+        Self {
+            label,
+            value,
+            max_len,
+            id: -1,
+        }
+    }
+}
+
+impl<'a> MenuWidget for TextField<'a> {
+    fn draw(&self, position: Vec2, text_renderer: &TextRenderer, ctx: &MenuCtx) {
+        let cursor = if ctx.typing && self.id == ctx.curr_pointer {
+            "_"
+        } else {
+            ""
+        };
+        let text = format!("{:<6}{}{}", self.label, self.value, cursor);
+
+        text_renderer.draw_text(
+            &text,
+            position,
+            FONT_SIZE,
+            if self.id == ctx.curr_pointer {
+                text::Color::Cream
+            } else {
+                text::Color::Magenta
+            },
+        );
+    }
+
+    fn handle_input(&mut self, ctx: &mut MenuCtx) {
+        if self.id != ctx.curr_pointer {
+            return;
+        }
+
+        if !ctx.typing {
+            if ctx.is_pressed(MenuKey::Confirm) {
+                ctx.typing = true;
+                // characters typed before are still queued
+                while get_char_pressed().is_some() {}
+            }
+            return;
+        }
+
+        // the queue hands out the last character first
+        let mut chars = Vec::new();
+        while let Some(c) = get_char_pressed() {
+            chars.push(c);
+        }
+        for c in chars.into_iter().rev() {
+            if (c.is_ascii_alphanumeric() || ".-:[]".contains(c)) && self.value.len() < self.max_len
+            {
+                self.value.push(c);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            self.value.pop();
+        }
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+            ctx.typing = false;
+        }
+    }
+
+    fn get_height(&self) -> f32 {
+        FONT_SIZE
+    }
+
+    fn insert_menu(&mut self, id: i32) {
+        self.id = id;
+    }
+}
+
 pub struct Margin;
 
 impl MenuWidget for Margin {
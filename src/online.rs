@@ -0,0 +1,160 @@
+use std::{io, net::ToSocketAddrs};
+
+use rust_tetris_core::{
+    game::{GameConfig, GameEvent},
+    input::Input,
+    net::Connection,
+    protocol::{Message, Snapshot},
+};
+
+use crate::versus::Player;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OnlineStatus {
+    /// Waiting for an opponent to join and be ready
+    Waiting,
+    Playing,
+    Won,
+    Lost,
+    /// The match cannot go on, with the reason
+    Closed(&'static str),
+}
+
+/// A versus match against a player on another machine, through the relay.
+/// The local side is a versus player, the opponent only sends their field.
+pub struct Online {
+    connection: Connection,
+    config: GameConfig,
+    tick_rate: u32,
+    pub status: OnlineStatus,
+    /// Players in the room, counting this one
+    pub players: u32,
+    pub player: Option<Player>,
+    /// Last field received from the opponent
+    pub opponent: Option<Snapshot>,
+}
+
+impl Online {
+    /// Joins `room` on the relay at `addr`, ready to start as soon as an opponent is.
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        room: &str,
+        config: GameConfig,
+        tick_rate: u32,
+    ) -> io::Result<Self> {
+        let mut connection = Connection::connect(addr)?;
+        connection.send(&Message::Join {
+            room: room.to_string(),
+        })?;
+        connection.send(&Message::Ready)?;
+
+        Ok(Self {
+            connection,
+            config,
+            tick_rate,
+            status: OnlineStatus::Waiting,
+            players: 0,
+            player: None,
+            opponent: None,
+        })
+    }
+
+    pub fn is_over(&self) -> bool {
+        matches!(
+            self.status,
+            OnlineStatus::Won | OnlineStatus::Lost | OnlineStatus::Closed(_)
+        )
+    }
+
+    /// Asks the relay for another match in the same room.
+    pub fn rematch(&mut self) {
+        self.status = match self.connection.send(&Message::Ready) {
+            Ok(()) => OnlineStatus::Waiting,
+            Err(_) => OnlineStatus::Closed("CONNECTION LOST"),
+        };
+    }
+
+    /// Handles the messages of the relay, then runs the due ticks of the local
+    /// game and sends its garbage, field and top out.
+    pub fn advance(&mut self, frames: f32, input: Input, on_event: impl FnMut(&GameEvent)) {
+        match self.connection.poll() {
+            Ok(messages) => messages.into_iter().for_each(|m| self.handle(m)),
+            Err(_) => self.status = OnlineStatus::Closed("CONNECTION LOST"),
+        }
+
+        if self.status == OnlineStatus::Playing && self.tick(frames, input, on_event).is_err() {
+            self.status = OnlineStatus::Closed("CONNECTION LOST");
+        }
+    }
+
+    fn tick(
+        &mut self,
+        frames: f32,
+        input: Input,
+        mut on_event: impl FnMut(&GameEvent),
+    ) -> io::Result<()> {
+        let Some(player) = &mut self.player else { return Ok(()) };
+
+        player.step.advance(frames, input);
+        while let Some(input) = player.step.next_tick() {
+            let mut field_changed = false;
+            let sent = player.tick(&input, &mut |event| {
+                field_changed |= matches!(
+                    event,
+                    GameEvent::Locked(_)
+                        | GameEvent::GarbageInserted { .. }
+                        | GameEvent::PieceSpawned(_)
+                );
+                on_event(event);
+            });
+
+            if sent > 0 {
+                self.connection.send(&Message::Garbage { rows: sent })?;
+            }
+            if field_changed {
+                let snapshot = Snapshot::from_game(&player.game);
+                self.connection.send(&Message::Snapshot(snapshot))?;
+            }
+            if player.game.is_over() {
+                self.connection.send(&Message::TopOut)?;
+                self.status = OnlineStatus::Lost;
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, message: Message) {
+        match message {
+            Message::Joined { players } => self.players = players,
+            Message::RoomFull => self.status = OnlineStatus::Closed("ROOM FULL"),
+            Message::Start { seed } => {
+                self.player = Some(Player::new(self.config.clone(), self.tick_rate, seed));
+                self.opponent = None;
+                self.status = OnlineStatus::Playing;
+            }
+            Message::Garbage { rows } => {
+                if let (OnlineStatus::Playing, Some(player)) = (self.status, &mut self.player) {
+                    player.game.queue_garbage(rows);
+                }
+            }
+            Message::Snapshot(snapshot) => self.opponent = Some(snapshot),
+            Message::TopOut if self.status == OnlineStatus::Playing => {
+                self.status = OnlineStatus::Won
+            }
+            Message::Left => {
+                self.players = 1;
+                match self.status {
+                    OnlineStatus::Playing => self.status = OnlineStatus::Won,
+                    // the relay forgets who was ready once someone leaves,
+                    // failures show up at the next poll
+                    OnlineStatus::Waiting => {
+                        let _ = self.connection.send(&Message::Ready);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,45 +1,196 @@
-use crate::{constants::*, game_data::GameData};
+use crate::constants::*;
 use macroquad::prelude::*;
 use rust_tetris_core::{
-    board::{Board, Cell},
+    board::{Board, BoardSize, Cell},
     enums::{PieceType, PieceTypeColor},
+    game::Game,
     pieces::PieceWithPosition,
 };
 
 use super::{
     block::{BlockRenderer, BlockVariant},
+    effect::EffectRenderer,
     text::{self, TextRenderer},
     utils::{pop_model_matrix, push_model_matrix},
 };
-use lazy_static::lazy_static;
 
 const HOLD_NEXT_PRIMARY_SCALE: f32 = 0.8;
 const HOLD_NEXT_SECONDARY_SCALE: f32 = 0.5;
+const HOLD_X: f32 = 1.;
+const NEXT_X: f32 = 5.5;
+const FINESSE_FLASH_DURATION: f64 = 0.5;
+
+/// Transient outline around a piece placed with more keys than needed.
+pub struct FinesseFlash {
+    cells: [(isize, isize); 4],
+    since: f64,
+}
+
+impl FinesseFlash {
+    pub fn new(cells: [(isize, isize); 4]) -> Self {
+        Self {
+            cells,
+            since: get_time(),
+        }
+    }
 
+    fn progress(&self) -> f32 {
+        ((get_time() - self.since) / FINESSE_FLASH_DURATION) as f32
+    }
+}
+
+/// Draws a board with its hold and next previews, `piece_offset` moves
+/// the current piece between ticks.
 pub fn render_board(
-    game_data: &GameData,
+    game: Option<&Game>,
+    piece_offset: Vec2,
+    board_size: BoardSize,
     pos: Vec3,
     block_renderer: &BlockRenderer,
     text_renderer: &TextRenderer,
+    effect_renderer: &EffectRenderer,
 ) {
-    let board = &game_data.board;
     push_model_matrix(Mat4::from_translation(pos));
 
-    render_board_blocks(board, BLOCK_SIZE, block_renderer);
-    render_curr_piece(game_data, block_renderer);
-    render_ghost_piece(game_data, block_renderer);
+    let clearing_rows: Vec<isize> = game.iter().flat_map(|game| game.clearing_rows()).collect();
+
+    if let Some(game) = game {
+        render_board_blocks(
+            &game.board,
+            board_size.visible_rows,
+            &clearing_rows,
+            BLOCK_SIZE,
+            block_renderer,
+        );
+        render_curr_piece(game, piece_offset, block_renderer);
+        render_ghost_piece(game, block_renderer);
+    }
+
+    render_next(game, board_size, text_renderer, block_renderer);
+    render_hold(game, board_size, text_renderer, block_renderer);
+
+    render_frame(board_size, BLOCK_SIZE);
+
+    if let Some(game) = game {
+        render_garbage_meter(game.pending_garbage(), board_size, BLOCK_SIZE);
+        render_clearing_rows(game, &clearing_rows, BLOCK_SIZE, effect_renderer);
+    }
+
+    pop_model_matrix();
+}
+
+/// Cleared rows break apart while waiting to be removed.
+fn render_clearing_rows(
+    game: &Game,
+    rows: &[isize],
+    block_size: f32,
+    effect_renderer: &EffectRenderer,
+) {
+    let progress = game.line_clear_progress();
+
+    unsafe {
+        get_internal_gl().quad_gl.depth_test(false);
+    }
 
-    render_next(game_data, text_renderer, block_renderer);
-    render_hold(game_data, text_renderer, block_renderer);
+    let visible_rows = game.config.board_size.visible_rows;
+    for &row in rows.iter().filter(|&&row| row < visible_rows) {
+        for col in 0..game.board.cols {
+            let position = Vec3 {
+                x: block_size * col as f32,
+                y: block_size * row as f32,
+                z: block_size,
+            };
+
+            if let Some(variant) = cell_variant(&game.board.get(row, col)) {
+                effect_renderer.draw_break(variant, position, block_size, progress);
+            }
+            effect_renderer.draw_erase(rows.len(), position, block_size, progress);
+        }
+    }
 
-    render_frame(FIELD_ROWS, FIELD_COLS, BLOCK_SIZE);
+    unsafe {
+        get_internal_gl().quad_gl.depth_test(true);
+    }
+}
+
+/// Draws a field without pieces nor previews, such as the last one
+/// received from an online opponent.
+pub fn render_field(
+    board: &Board,
+    pending_garbage: u32,
+    board_size: BoardSize,
+    pos: Vec3,
+    block_renderer: &BlockRenderer,
+) {
+    push_model_matrix(Mat4::from_translation(pos));
+
+    render_board_blocks(
+        board,
+        board_size.visible_rows,
+        &[],
+        BLOCK_SIZE,
+        block_renderer,
+    );
+    render_frame(board_size, BLOCK_SIZE);
+    render_garbage_meter(pending_garbage, board_size, BLOCK_SIZE);
 
     pop_model_matrix();
 }
 
-fn render_ghost_piece(game_data: &GameData, block_renderer: &BlockRenderer) {
-    if let Some(mut p) = game_data.curr_piece.clone() {
-        while !p.collides_down(&game_data.board) {
+/// Outlines the cells of the flashed piece in red, fading out.
+pub fn render_finesse_flash(flash: &FinesseFlash, board_size: BoardSize, pos: Vec3) {
+    let progress = flash.progress();
+    if progress >= 1. {
+        return;
+    }
+
+    push_model_matrix(Mat4::from_translation(pos));
+
+    let color = Color::new(0.9, 0.15, 0.15, 1. - progress);
+    for &(row, col) in flash
+        .cells
+        .iter()
+        .filter(|&&(row, _)| row < board_size.visible_rows)
+    {
+        draw_cube_wires(
+            Vec3 {
+                x: BLOCK_SIZE * col as f32,
+                y: BLOCK_SIZE * row as f32,
+                z: 0.,
+            },
+            Vec3::splat(BLOCK_SIZE * 1.05),
+            color,
+        );
+    }
+
+    pop_model_matrix();
+}
+
+/// A bar left of the board, one block high per pending garbage row.
+fn render_garbage_meter(pending_garbage: u32, board_size: BoardSize, block_size: f32) {
+    let rows = (pending_garbage as isize).min(board_size.visible_rows);
+
+    for row in 0..rows {
+        draw_cube(
+            Vec3 {
+                x: -1.1 * block_size,
+                y: block_size * row as f32,
+                z: 0.,
+            },
+            Vec3 {
+                x: 0.3 * block_size,
+                y: 0.9 * block_size,
+                z: 0.3 * block_size,
+            },
+            None,
+            Color::from_rgba(230, 40, 40, 255),
+        );
+    }
+}
+
+fn render_ghost_piece(game: &Game, block_renderer: &BlockRenderer) {
+    if let Some(mut p) = game.curr_piece.clone() {
+        while !p.collides_down(&game.board) {
             p.move_down();
         }
 
@@ -47,19 +198,36 @@ fn render_ghost_piece(game_data: &GameData, block_renderer: &BlockRenderer) {
     }
 }
 
-fn render_curr_piece(game_data: &GameData, block_renderer: &BlockRenderer) {
-    if let Some(p) = &game_data.curr_piece {
+/// Offset in blocks of the current piece between its previous and current tick
+/// positions, so that it moves smoothly when rendering faster than ticking.
+/// `alpha` is the progress towards the next tick.
+pub fn piece_offset(game: &Game, prev_piece_pos: Option<(isize, isize)>, alpha: f32) -> Vec2 {
+    let Some((prev_row, prev_col)) = prev_piece_pos else { return Vec2::ZERO };
+    let Some(p) = &game.curr_piece else { return Vec2::ZERO };
+
+    // longer moves such as hard drops and instant shifts are not animated
+    let (rows, cols) = (prev_row - p.row(), prev_col - p.col());
+    if rows.abs() > 1 || cols.abs() > 1 {
+        return Vec2::ZERO;
+    }
+
+    Vec2::new(cols as f32, rows as f32) * (1. - alpha)
+}
+
+fn render_curr_piece(game: &Game, offset: Vec2, block_renderer: &BlockRenderer) {
+    if let Some(p) = &game.curr_piece {
         let piece = p.tetris_piece_ref();
+        let visible_rows = game.config.board_size.visible_rows;
         piece
             .all_cells()
             .map(|(y, x)| (x + p.col(), y + p.row(), piece.board.get(y, x)))
-            .filter(|&(_, y, _)| y < FIELD_ROWS as isize)
+            .filter(|&(_, y, _)| y < visible_rows)
             .for_each(|(x, y, cell)| {
                 render_cell(
                     &cell,
                     Vec3 {
-                        x: BLOCK_SIZE * x as f32,
-                        y: BLOCK_SIZE * y as f32,
+                        x: BLOCK_SIZE * (x as f32 + offset.x),
+                        y: BLOCK_SIZE * (y as f32 + offset.y),
                         z: 0.,
                     },
                     BLOCK_SIZE,
@@ -70,8 +238,13 @@ fn render_curr_piece(game_data: &GameData, block_renderer: &BlockRenderer) {
     }
 }
 
-fn render_next(game_data: &GameData, text_renderer: &TextRenderer, block_renderer: &BlockRenderer) {
-    push_model_matrix(*NEXT_MAT);
+fn render_next(
+    game: Option<&Game>,
+    board_size: BoardSize,
+    text_renderer: &TextRenderer,
+    block_renderer: &BlockRenderer,
+) {
+    push_model_matrix(preview_mat(NEXT_X, board_size));
 
     text_renderer.draw_text(
         "NEXT",
@@ -85,8 +258,8 @@ fn render_next(game_data: &GameData, text_renderer: &TextRenderer, block_rendere
 
     let (mut dx, mut dy) = (0, 0);
 
-    if let Some(piece_bag) = &game_data.piece_bag {
-        piece_bag
+    if let Some(game) = game {
+        game.piece_bag
             .piece_buffer()
             .iter()
             .rev()
@@ -120,8 +293,13 @@ fn get_piece_visual_size(piece: PieceType) -> (isize, isize) {
     }
 }
 
-fn render_hold(game_data: &GameData, text_renderer: &TextRenderer, block_renderer: &BlockRenderer) {
-    push_model_matrix(*HOLD_MAT);
+fn render_hold(
+    game: Option<&Game>,
+    board_size: BoardSize,
+    text_renderer: &TextRenderer,
+    block_renderer: &BlockRenderer,
+) {
+    push_model_matrix(preview_mat(HOLD_X, board_size));
 
     text_renderer.draw_text(
         "HOLD",
@@ -133,7 +311,7 @@ fn render_hold(game_data: &GameData, text_renderer: &TextRenderer, block_rendere
         text::Color::Yellow,
     );
 
-    if let Some(hp) = &game_data.hold_piece {
+    if let Some(hp) = game.and_then(|g| g.hold_piece.as_ref()) {
         render_tetrimino(
             &PieceWithPosition::new(0, 0, hp.piece.clone()),
             BLOCK_SIZE * HOLD_NEXT_PRIMARY_SCALE,
@@ -144,13 +322,19 @@ fn render_hold(game_data: &GameData, text_renderer: &TextRenderer, block_rendere
     pop_model_matrix();
 }
 
-fn render_board_blocks(board: &Board, block_size: f32, block_renderer: &BlockRenderer) {
+fn render_board_blocks(
+    board: &Board,
+    visible_rows: isize,
+    hidden_rows: &[isize],
+    block_size: f32,
+    block_renderer: &BlockRenderer,
+) {
     board
         .rows()
         .flatten()
         .enumerate()
         .map(|(ind, cell)| (ind % board.cols as usize, ind / board.cols as usize, cell))
-        .filter(|&(_, y, _)| y < FIELD_ROWS)
+        .filter(|&(_, y, _)| y < visible_rows as usize && !hidden_rows.contains(&(y as isize)))
         .for_each(|(x, y, cell)| {
             render_cell(
                 cell,
@@ -196,11 +380,8 @@ fn render_tetrimino(
         y: tetrimino.row() as f32 * block_size,
         z: 0.,
     }));
-    render_board_blocks(
-        &tetrimino.tetris_piece_ref().board,
-        block_size,
-        block_renderer,
-    );
+    let board = &tetrimino.tetris_piece_ref().board;
+    render_board_blocks(board, board.rows, &[], block_size, block_renderer);
     pop_model_matrix();
 }
 
@@ -223,25 +404,30 @@ fn render_tetrimino_wire(
 }
 
 fn render_cell(cell: &Cell, position: Vec3, size: f32, block_renderer: &BlockRenderer) {
-    if let Cell::Filled(piece) = cell {
-        let variant = match piece {
-            PieceTypeColor::Playable(piece_type) => match piece_type {
-                PieceType::T => BlockVariant::Purple,
-                PieceType::L => BlockVariant::Blue,
-                PieceType::J => BlockVariant::Orange,
-                PieceType::S => BlockVariant::Green,
-                PieceType::Z => BlockVariant::Red,
-                PieceType::O => BlockVariant::Yellow,
-                PieceType::I => BlockVariant::Cyan,
-            },
-            PieceTypeColor::NotPlayable => BlockVariant::Gray,
-        };
-
+    if let Some(variant) = cell_variant(cell) {
         block_renderer.draw_block(variant, position, size);
     }
 }
 
-fn render_frame(rows: usize, cols: usize, size: f32) {
+fn cell_variant(cell: &Cell) -> Option<BlockVariant> {
+    let Cell::Filled(piece) = cell else { return None };
+
+    Some(match piece {
+        PieceTypeColor::Playable(piece_type) => match piece_type {
+            PieceType::T => BlockVariant::Purple,
+            PieceType::L => BlockVariant::Blue,
+            PieceType::J => BlockVariant::Orange,
+            PieceType::S => BlockVariant::Green,
+            PieceType::Z => BlockVariant::Red,
+            PieceType::O => BlockVariant::Yellow,
+            PieceType::I => BlockVariant::Cyan,
+        },
+        PieceTypeColor::NotPlayable => BlockVariant::Gray,
+    })
+}
+
+fn render_frame(board_size: BoardSize, size: f32) {
+    let (rows, cols) = (board_size.visible_rows, board_size.cols);
     let offset = size / 2.;
     (0..=rows).for_each(|row| {
         let y = row as f32 * size - offset;
@@ -274,15 +460,11 @@ fn render_frame(rows: usize, cols: usize, size: f32) {
     });
 }
 
-lazy_static! {
-    static ref HOLD_MAT: Mat4 = Mat4::from_translation(Vec3 {
-        x: 1. * BLOCK_SIZE,
-        y: 19.5 * BLOCK_SIZE,
+/// Hold and next previews sit above the visible rows.
+fn preview_mat(x: f32, board_size: BoardSize) -> Mat4 {
+    Mat4::from_translation(Vec3 {
+        x: x * BLOCK_SIZE,
+        y: (board_size.visible_rows as f32 - 0.5) * BLOCK_SIZE,
         z: 2. * BLOCK_SIZE,
-    });
-    static ref NEXT_MAT: Mat4 = Mat4::from_translation(Vec3 {
-        x: 5.5 * BLOCK_SIZE,
-        y: 19.5 * BLOCK_SIZE,
-        z: 2. * BLOCK_SIZE,
-    });
+    })
 }
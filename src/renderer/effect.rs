@@ -0,0 +1,91 @@
+use macroquad::prelude::*;
+
+use super::{
+    block::BlockVariant,
+    texture_atlas::TextureAltas,
+    utils::{pop_model_matrix, push_model_matrix},
+};
+
+/// Break sprites are three blocks wide
+const BREAK_SCALE: f32 = 3.;
+/// Erase sprites are one block wide
+const ERASE_SCALE: f32 = 1.;
+
+/// Draws the line clear animations.
+pub struct EffectRenderer {
+    // frames of each block color, gray first
+    break_frames: Vec<Vec<Texture2D>>,
+    // frames by number of cleared rows, one row first
+    erase_frames: Vec<Vec<Texture2D>>,
+}
+
+impl EffectRenderer {
+    /// `breaks` holds the two sheets of each block color, `erases` one sheet per cleared row count.
+    pub fn new(breaks: Vec<(Image, Image)>, erases: Vec<Image>) -> Self {
+        Self {
+            break_frames: breaks
+                .into_iter()
+                .map(|(first, second)| {
+                    let mut frames = TextureAltas::new(first, 5, 6, 96, 96, 0, 0).altas;
+                    frames.extend(TextureAltas::new(second, 1, 6, 96, 96, 0, 0).altas);
+                    frames
+                })
+                .collect(),
+            erase_frames: erases
+                .into_iter()
+                .map(|image| TextureAltas::new(image, 6, 10, 32, 32, 0, 0).altas)
+                .collect(),
+        }
+    }
+
+    /// Draws a block of `variant` breaking apart, `progress` going from 0 to 1.
+    pub fn draw_break(&self, variant: BlockVariant, position: Vec3, size: f32, progress: f32) {
+        let color = match variant {
+            BlockVariant::Gray | BlockVariant::White => 0,
+            BlockVariant::Red => 1,
+            BlockVariant::Orange => 2,
+            BlockVariant::Yellow => 3,
+            BlockVariant::Green => 4,
+            BlockVariant::Cyan => 5,
+            BlockVariant::Blue => 6,
+            BlockVariant::Purple => 7,
+        };
+
+        draw_frame(
+            &self.break_frames[color],
+            position,
+            size * BREAK_SCALE,
+            progress,
+        );
+    }
+
+    /// Draws the flash of a cell in a clear of `rows` rows, `progress` going from 0 to 1.
+    pub fn draw_erase(&self, rows: usize, position: Vec3, size: f32, progress: f32) {
+        let index = rows.clamp(1, self.erase_frames.len()) - 1;
+        draw_frame(
+            &self.erase_frames[index],
+            position,
+            size * ERASE_SCALE,
+            progress,
+        );
+    }
+}
+
+/// Draws the frame at `progress` of an animation, centered on `position`.
+fn draw_frame(frames: &[Texture2D], position: Vec3, size: f32, progress: f32) {
+    let index = ((progress * frames.len() as f32) as usize).min(frames.len() - 1);
+
+    push_model_matrix(Mat4::from_translation(position));
+    draw_texture_ex(
+        frames[index],
+        -size / 2.,
+        -size / 2.,
+        Color::from_rgba(255, 255, 255, 255),
+        DrawTextureParams {
+            dest_size: Some(Vec2 { x: size, y: size }),
+            flip_y: true,
+            ..Default::default()
+        },
+    );
+    pop_model_matrix();
+}
@@ -1,13 +1,21 @@
 use crate::asset::Assets;
 use crate::constants::*;
+use crate::demo::Demo;
 use crate::game_data::{GameData, GameState};
+use crate::online::Online;
+use crate::versus::{Player, Versus};
 use macroquad::prelude::*;
+use rust_tetris_core::{
+    board::{Board, BoardSize},
+    game::{Game, GameEvent},
+};
 
-use self::board::render_board;
-use self::score::render_score;
+use self::board::{piece_offset, render_board, render_field, render_finesse_flash, FinesseFlash};
+use self::score::{render_player_label, render_score, render_stats, Callout};
 
 pub mod block;
 pub mod board;
+pub mod effect;
 pub mod material;
 pub mod score;
 pub mod text;
@@ -17,16 +25,40 @@ pub mod utils;
 pub struct Renderer {
     pub block_renderer: block::BlockRenderer,
     pub text_renderer: text::TextRenderer,
+    pub effect_renderer: effect::EffectRenderer,
+    callout: Option<Callout>,
+    finesse_flash: Option<FinesseFlash>,
 }
 
 impl From<&Assets> for Renderer {
     fn from(assets: &Assets) -> Self {
         let block_renderer = block::BlockRenderer::new(assets.block_img.clone());
         let text_renderer = text::TextRenderer::new(assets.text_img.clone());
+        let effect_renderer = effect::EffectRenderer::new(
+            vec![
+                (assets.break0_0.clone(), assets.break0_1.clone()),
+                (assets.break1_0.clone(), assets.break1_1.clone()),
+                (assets.break2_0.clone(), assets.break2_1.clone()),
+                (assets.break3_0.clone(), assets.break3_1.clone()),
+                (assets.break4_0.clone(), assets.break4_1.clone()),
+                (assets.break5_0.clone(), assets.break5_1.clone()),
+                (assets.break6_0.clone(), assets.break6_1.clone()),
+                (assets.break7_0.clone(), assets.break7_1.clone()),
+            ],
+            vec![
+                assets.perase1.clone(),
+                assets.perase2.clone(),
+                assets.perase3.clone(),
+                assets.perase4.clone(),
+            ],
+        );
 
         Self {
             block_renderer,
             text_renderer,
+            effect_renderer,
+            callout: None,
+            finesse_flash: None,
         }
     }
 }
@@ -34,23 +66,163 @@ impl From<&Assets> for Renderer {
 impl Renderer {
     pub fn render(&self, game_data: &GameData) {
         clear_background(Color::from_rgba(10, 10, 10, 255));
-        self.setup_camera();
 
+        if let Some(versus) = &game_data.versus {
+            self.render_versus(versus, &game_data.state);
+            return;
+        }
+        if let Some(online) = &game_data.online {
+            self.render_online(online, &game_data.state);
+            return;
+        }
+
+        // the board being played or watched, else the configured one
+        let board_size = game_data
+            .game
+            .as_ref()
+            .map_or(game_data.config.board_size, |game| game.config.board_size);
+        self.setup_camera(board_size);
+
+        let game = shown_game(game_data.game.as_ref(), &game_data.state);
+        let offset = match game {
+            Some(game) if is_running(&game_data.state) => {
+                piece_offset(game, game_data.prev_piece_pos, game_data.step.alpha())
+            }
+            _ => Vec2::ZERO,
+        };
         render_board(
-            game_data,
-            BOARD_POS,
+            game,
+            offset,
+            board_size,
+            board_pos(board_size),
             &self.block_renderer,
             &self.text_renderer,
+            &self.effect_renderer,
         );
+        if let Some(flash) = self.finesse_flash.as_ref().filter(|_| game.is_some()) {
+            render_finesse_flash(flash, board_size, board_pos(board_size));
+        }
 
         if game_data.state != GameState::Menu {
-            render_score(game_data, SCORE_POS, &self.text_renderer);
+            render_score(
+                game_data,
+                self.callout.as_ref(),
+                SCORE_POS,
+                &self.text_renderer,
+            );
+
+            // the full breakdown stays up once the game is over
+            let full = game_data.state == GameState::GameOver;
+            if full || game_data.show_stats {
+                render_stats(game_data, full, stats_pos(board_size), &self.text_renderer);
+            }
+        } else if let Some(demo) = &game_data.demo {
+            self.render_demo(demo);
+        }
+    }
+
+    /// Draws the bot game of the title screen next to the menu.
+    fn render_demo(&self, demo: &Demo) {
+        let player = &demo.player;
+        render_board(
+            Some(&player.game),
+            piece_offset(&player.game, player.prev_piece_pos, player.step.alpha()),
+            player.game.config.board_size,
+            DEMO_BOARD_POS,
+            &self.block_renderer,
+            &self.text_renderer,
+            &self.effect_renderer,
+        );
+    }
+
+    /// Draws the board of each player side by side, with their own previews.
+    fn render_versus(&self, versus: &Versus, state: &GameState) {
+        self.setup_camera(BoardSize::default());
+
+        for (i, (player, pos)) in versus.players.iter().zip(VERSUS_BOARD_POS).enumerate() {
+            let game = shown_game(Some(&player.game), state);
+            render_board(
+                game,
+                versus_piece_offset(player, state),
+                player.game.config.board_size,
+                pos,
+                &self.block_renderer,
+                &self.text_renderer,
+                &self.effect_renderer,
+            );
+            render_player_label(
+                &versus.player_name(i),
+                &player.game,
+                pos.xy(),
+                &self.text_renderer,
+            );
         }
     }
 
-    fn setup_camera(&self) {
+    /// Draws the local board on the left and the last field of the opponent on the right.
+    fn render_online(&self, online: &Online, state: &GameState) {
+        let [pos, opponent_pos] = VERSUS_BOARD_POS;
+        let board_size = BoardSize::default();
+        self.setup_camera(board_size);
+
+        let player = online.player.as_ref();
+        render_board(
+            player.map(|player| &player.game),
+            player.map_or(Vec2::ZERO, |player| versus_piece_offset(player, state)),
+            board_size,
+            pos,
+            &self.block_renderer,
+            &self.text_renderer,
+            &self.effect_renderer,
+        );
+        if let Some(player) = player {
+            render_player_label("1P", &player.game, pos.xy(), &self.text_renderer);
+        }
+
+        match &online.opponent {
+            Some(snapshot) => render_field(
+                &snapshot.board(),
+                snapshot.pending_garbage,
+                BoardSize {
+                    cols: snapshot.cols,
+                    visible_rows: snapshot.rows,
+                    buffer_rows: 0,
+                },
+                opponent_pos,
+                &self.block_renderer,
+            ),
+            None => render_field(
+                &Board::with_size(board_size),
+                0,
+                board_size,
+                opponent_pos,
+                &self.block_renderer,
+            ),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::LinesCleared {
+                score_type: Some(score_type),
+                ..
+            } => self.callout = Some(Callout::new(*score_type)),
+            GameEvent::FinesseFault { cells, .. } => {
+                self.finesse_flash = Some(FinesseFlash::new(*cells))
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the camera back for boards taller or wider than the standard one fits.
+    fn setup_camera(&self, board_size: BoardSize) {
+        let standard = BoardSize::default();
+        let zoom = (board_size.visible_rows as f32 / standard.visible_rows as f32)
+            .max(board_size.cols as f32 / (2 * standard.cols) as f32)
+            .max(1.);
+
         set_camera(&Camera3D {
-            position: CAMERA_POS,
+            position: CAMERA_POS * zoom,
             fovy: CAMERA_FOV,
             target: Vec3::ZERO,
             up: Vec3::Y,
@@ -58,3 +230,38 @@ impl Renderer {
         });
     }
 }
+
+/// Left of the top of the board.
+fn stats_pos(board_size: BoardSize) -> Vec2 {
+    board_pos(board_size).xy()
+        + STATS_OFFSET
+        + Vec2::Y * (board_size.visible_rows - 1) as f32 * BLOCK_SIZE
+}
+
+/// Keeps the right edge and the vertical center of the standard board.
+fn board_pos(board_size: BoardSize) -> Vec3 {
+    let standard = BoardSize::default();
+    BOARD_POS
+        + Vec3 {
+            x: (standard.cols - board_size.cols) as f32 * BLOCK_SIZE,
+            y: (standard.visible_rows - board_size.visible_rows) as f32 / 2. * BLOCK_SIZE,
+            z: 0.,
+        }
+}
+
+/// The game to draw, nothing to plan ahead with while paused.
+fn shown_game<'a>(game: Option<&'a Game>, state: &GameState) -> Option<&'a Game> {
+    game.filter(|_| *state != GameState::Paused)
+}
+
+/// Whether games are ticking, so that pieces move between ticks.
+fn is_running(state: &GameState) -> bool {
+    matches!(state, GameState::Playing | GameState::Replay)
+}
+
+fn versus_piece_offset(player: &Player, state: &GameState) -> Vec2 {
+    if !is_running(state) {
+        return Vec2::ZERO;
+    }
+    piece_offset(&player.game, player.prev_piece_pos, player.step.alpha())
+}
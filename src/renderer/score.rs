@@ -1,4 +1,7 @@
-use crate::{constants::FONT_SIZE, game_data::GameData};
+use crate::{
+    constants::{FONT_SIZE, STATS_FONT_SIZE},
+    game_data::GameData,
+};
 
 use super::{
     text::{self, TextRenderer},
@@ -6,40 +9,235 @@ use super::{
 };
 
 use macroquad::prelude::*;
+use rust_tetris_core::{enums::PieceType, game::Game, mode::GameMode, score::ScoreType};
+
+/// How long a clear callout stays on screen, in seconds
+const CALLOUT_DURATION: f64 = 1.5;
+
+/// Transient label announcing the last line clear.
+pub struct Callout {
+    score_type: ScoreType,
+    since: f64,
+}
+
+impl Callout {
+    pub fn new(score_type: ScoreType) -> Self {
+        Self {
+            score_type,
+            since: get_time(),
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        get_time() - self.since < CALLOUT_DURATION
+    }
+}
+
+pub fn render_score(
+    game_data: &GameData,
+    callout: Option<&Callout>,
+    pos: Vec2,
+    text_renderer: &TextRenderer,
+) {
+    let Some(game) = &game_data.game else { return };
 
-pub fn render_score(game_data: &GameData, pos: Vec2, text_renderer: &TextRenderer) {
     push_model_matrix(Mat4::from_translation(Vec3 {
         x: pos.x,
         y: pos.y,
         z: 0.,
     }));
 
-    text_renderer.draw_text("LINES", Vec2::ZERO, FONT_SIZE, text::Color::Navyblue);
+    let score = &game.score_updater;
+
+    // cheese races count down the garbage rows left to dig
+    let (label, lines) = match (game.config.mode, game.config.mode.line_goal()) {
+        (GameMode::CheeseRace { .. }, Some(goal)) => {
+            ("LEFT", goal.saturating_sub(game.goal_lines()).to_string())
+        }
+        (_, Some(goal)) => ("LINES", format!("{}/{}", game.lines, goal)),
+        (_, None) => ("LINES", game.lines.to_string()),
+    };
+
+    draw_entry(text_renderer, "SCORE", &score.get_score().to_string(), 0.);
+    draw_entry(text_renderer, label, &lines, 2.5);
+    draw_entry(text_renderer, "TIME", &time_to_string(game.time), 5.);
+
+    let mut line = 7.5;
+    if game.config.mode.level(game.lines).is_some() {
+        draw_counter(text_renderer, "LEVEL", game.level(), line);
+        line += 1.;
+    }
+
+    draw_counter(
+        text_renderer,
+        "COMBO",
+        score.get_combo().value().unwrap_or(0),
+        line,
+    );
+    draw_counter(text_renderer, "B2B", score.get_b2b(), line + 1.);
+    draw_counter(
+        text_renderer,
+        "FAULTS",
+        game_data.stats.finesse_faults,
+        line + 2.,
+    );
+
+    if let Some(callout) = callout.filter(|c| c.is_visible()) {
+        text_renderer.draw_text(
+            score_type_label(callout.score_type),
+            Vec2::Y * FONT_SIZE * -(line + 3.5),
+            FONT_SIZE,
+            text::Color::Yellow,
+        );
+    }
+
+    pop_model_matrix();
+}
+
+/// Draws the statistics of the game, `full` adds the streaks and T-spins
+/// of the breakdown shown once it is over.
+pub fn render_stats(game_data: &GameData, full: bool, pos: Vec2, text_renderer: &TextRenderer) {
+    let Some(game) = &game_data.game else { return };
+    let stats = &game_data.stats;
+
+    push_model_matrix(Mat4::from_translation(Vec3 {
+        x: pos.x,
+        y: pos.y,
+        z: 0.,
+    }));
+
+    let mut rows = vec![
+        ("PIECES", stats.pieces.to_string()),
+        ("PPS", format!("{:.2}", stats.pps(game.time))),
+        ("APM", format!("{:.2}", stats.apm(game.time))),
+        ("KPP", format!("{:.2}", stats.kpp())),
+        ("LPM", format!("{:.2}", stats.lpm(game.time))),
+        ("HOLDS", stats.holds.to_string()),
+        ("", String::new()),
+    ];
+    let piece_labels = PieceType::ALL.map(|piece_type| format!("{:?}", piece_type));
+    for (label, piece_type) in piece_labels.iter().zip(PieceType::ALL) {
+        rows.push((label, stats.piece_count(piece_type).to_string()));
+    }
+
+    if full {
+        rows.push(("", String::new()));
+        rows.push(("ATTACK", stats.attack.to_string()));
+        rows.push(("COMBO", stats.max_combo.to_string()));
+        rows.push(("B2B", stats.max_b2b.to_string()));
+        for t_spin in ScoreType::T_SPINS {
+            let count = stats.t_spin_count(t_spin);
+            if count > 0 {
+                rows.push((t_spin_label(t_spin), count.to_string()));
+            }
+        }
+    }
+
+    for (line, (label, value)) in rows.iter().enumerate() {
+        let y = STATS_FONT_SIZE * -(line as f32);
+        text_renderer.draw_text(label, Vec2::Y * y, STATS_FONT_SIZE, text::Color::Navyblue);
+        text_renderer.draw_text(
+            value,
+            Vec2 {
+                x: STATS_FONT_SIZE * 7.,
+                y,
+            },
+            STATS_FONT_SIZE,
+            text::Color::White,
+        );
+    }
+
+    pop_model_matrix();
+}
+
+/// Draws the side of a versus player and the garbage they sent below their board.
+pub fn render_player_label(name: &str, game: &Game, pos: Vec2, text_renderer: &TextRenderer) {
+    push_model_matrix(Mat4::from_translation(Vec3 {
+        x: pos.x,
+        y: pos.y - FONT_SIZE * 1.5,
+        z: 0.,
+    }));
+
+    text_renderer.draw_text(name, Vec2::ZERO, FONT_SIZE, text::Color::Yellow);
     text_renderer.draw_text(
-        &game_data.lines.to_string(),
-        Vec2::Y * -FONT_SIZE,
+        &format!("SENT {}", game.garbage_sent()),
+        Vec2::X * FONT_SIZE * 3.,
         FONT_SIZE,
         text::Color::White,
     );
 
+    pop_model_matrix();
+}
+
+/// Draws a label with its value on the line below.
+/// `line` is the vertical offset in lines, counting downwards.
+fn draw_entry(text_renderer: &TextRenderer, label: &str, value: &str, line: f32) {
     text_renderer.draw_text(
-        "TIME",
-        Vec2::Y * FONT_SIZE * -3.,
+        label,
+        Vec2::Y * FONT_SIZE * -line,
         FONT_SIZE,
         text::Color::Navyblue,
     );
     text_renderer.draw_text(
-        &time_to_string(game_data.time),
-        Vec2::Y * FONT_SIZE * -4.,
+        value,
+        Vec2::Y * FONT_SIZE * -(line + 1.),
         FONT_SIZE,
         text::Color::White,
     );
+}
 
-    pop_model_matrix();
+/// Draws a label and a counter on the same line.
+fn draw_counter(text_renderer: &TextRenderer, label: &str, value: u32, line: f32) {
+    text_renderer.draw_text(
+        &format!("{:<6}", label),
+        Vec2::Y * FONT_SIZE * -line,
+        FONT_SIZE,
+        text::Color::Navyblue,
+    );
+    text_renderer.draw_text(
+        &value.to_string(),
+        Vec2 {
+            x: FONT_SIZE * 6.,
+            y: FONT_SIZE * -line,
+        },
+        FONT_SIZE,
+        text::Color::White,
+    );
+}
+
+fn score_type_label(score_type: ScoreType) -> &'static str {
+    match score_type {
+        ScoreType::Single => "SINGLE",
+        ScoreType::Double => "DOUBLE",
+        ScoreType::Triple => "TRIPLE",
+        ScoreType::Tetris => "TETRIS",
+        ScoreType::TSpin => "T-SPIN",
+        ScoreType::TSpinMini => "T-SPIN MINI",
+        ScoreType::TSpinMiniSingle => "T-SPIN MINI SINGLE",
+        ScoreType::TSpinMiniDouble => "T-SPIN MINI DOUBLE",
+        ScoreType::TSpinSingle => "T-SPIN SINGLE",
+        ScoreType::TSpinDouble => "T-SPIN DOUBLE",
+        ScoreType::TSpinTriple => "T-SPIN TRIPLE",
+        ScoreType::AllClear => "ALL CLEAR",
+    }
+}
+
+/// Short name of a T-spin, for the statistics panel.
+fn t_spin_label(score_type: ScoreType) -> &'static str {
+    match score_type {
+        ScoreType::TSpin => "TS",
+        ScoreType::TSpinSingle => "TSS",
+        ScoreType::TSpinDouble => "TSD",
+        ScoreType::TSpinTriple => "TST",
+        ScoreType::TSpinMini => "TSM",
+        ScoreType::TSpinMiniSingle => "TSMS",
+        ScoreType::TSpinMiniDouble => "TSMD",
+        _ => "",
+    }
 }
 
 // mm:ss:ms
-fn time_to_string(time: f32) -> String {
+pub fn time_to_string(time: f32) -> String {
     let minutes = time / 60.;
     let seconds = time as usize % 60;
     let milliseconds = time.fract() * 1000.;
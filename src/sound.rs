@@ -1,6 +1,7 @@
 use std::cell::Cell;
 
 use macroquad::audio;
+use rust_tetris_core::game::GameEvent;
 
 use crate::asset::Assets;
 
@@ -13,6 +14,9 @@ pub struct SoundAssets {
     pub mino_clear: SoundPtr,
     pub mino_hold: SoundPtr,
     pub mino_holdfail: SoundPtr,
+    pub pause: SoundPtr,
+    pub ready: SoundPtr,
+    pub go: SoundPtr,
 }
 
 impl From<&Assets> for SoundAssets {
@@ -32,6 +36,23 @@ impl From<&Assets> for SoundAssets {
             mino_clear: Box::new(SingleSound::from(assets.mino_clear)),
             mino_hold: Box::new(SingleSound::from(assets.mino_hold)),
             mino_holdfail: Box::new(SingleSound::from(assets.mino_holdfail)),
+            pause: Box::new(SingleSound::from(assets.pause)),
+            ready: Box::new(SingleSound::from(assets.ready)),
+            go: Box::new(SingleSound::from(assets.go)),
+        }
+    }
+}
+
+impl SoundAssets {
+    pub fn handle_event(&self, event: &GameEvent) {
+        match event {
+            GameEvent::PieceSpawned(_) => self.mino_spawn.play(),
+            GameEvent::TouchedGround => self.mino_touch_ground.play(),
+            GameEvent::Locked(_) => self.mino_lock.play(),
+            GameEvent::LinesCleared { rows, .. } if *rows > 0 => self.mino_clear.play(),
+            GameEvent::Hold => self.mino_hold.play(),
+            GameEvent::HoldFailed => self.mino_holdfail.play(),
+            _ => {}
         }
     }
 }
@@ -0,0 +1,142 @@
+use rust_tetris_core::{
+    bot::Bot,
+    game::{Game, GameConfig, GameEvent},
+    garbage::GarbagePattern,
+    input::Input,
+    stats::Statistics,
+    step::FixedStep,
+};
+
+pub const PLAYERS: usize = 2;
+/// The side the bot plays, when there is one
+pub const BOT_PLAYER: usize = 1;
+
+/// Hole change chance of the garbage players send each other
+pub const VERSUS_GARBAGE: GarbagePattern = GarbagePattern::Cheese(30);
+
+/// One side of a versus match, with its own game and tick splitting.
+pub struct Player {
+    pub game: Game,
+    pub stats: Statistics,
+    pub step: FixedStep,
+    /// Position of the current piece at the previous tick, to render between ticks
+    pub prev_piece_pos: Option<(isize, isize)>,
+}
+
+impl Player {
+    pub fn new(config: GameConfig, tick_rate: u32, seed: u64) -> Self {
+        Self {
+            game: Game::new(config, Some(seed)),
+            stats: Statistics::default(),
+            step: FixedStep::new(tick_rate),
+            prev_piece_pos: None,
+        }
+    }
+
+    /// Runs one tick of the game, returns the garbage rows it sent.
+    pub fn tick(&mut self, input: &Input, on_event: &mut impl FnMut(&GameEvent)) -> u32 {
+        self.stats.handle_input(input);
+        self.prev_piece_pos = self.game.curr_piece.as_ref().map(|p| (p.row(), p.col()));
+        self.game.tick(self.step.tick_frames(), input);
+
+        let mut sent = 0;
+        for event in self.game.drain_events() {
+            match event {
+                GameEvent::PieceSpawned(_) | GameEvent::Hold => self.prev_piece_pos = None,
+                GameEvent::GarbageSent { rows } => sent += rows,
+                _ => {}
+            }
+            self.stats.handle_event(&event);
+            on_event(&event);
+        }
+
+        sent
+    }
+}
+
+/// Games played side by side, the garbage sent by one player is queued on the other.
+pub struct Versus {
+    pub players: [Player; PLAYERS],
+    /// Plays the second side instead of its keys
+    pub bot: Option<Bot>,
+}
+
+impl Versus {
+    /// Both players are dealt the same pieces and garbage holes.
+    pub fn new(config: GameConfig, tick_rate: u32, seed: u64) -> Self {
+        Self {
+            players: [(); PLAYERS].map(|_| Player::new(config.clone(), tick_rate, seed)),
+            bot: None,
+        }
+    }
+
+    /// Adds `frames` of elapsed time with the input of each player, then runs
+    /// the due ticks of both games in lockstep until the match is over.
+    pub fn advance(
+        &mut self,
+        frames: f32,
+        inputs: [Input; PLAYERS],
+        mut on_event: impl FnMut(usize, &GameEvent),
+    ) {
+        for (player, input) in self.players.iter_mut().zip(inputs) {
+            player.step.advance(frames, input);
+        }
+
+        while !self.is_over() {
+            let mut ticked = false;
+            for i in 0..PLAYERS {
+                let Some(input) = self.players[i].step.next_tick() else {
+                    continue;
+                };
+                ticked = true;
+
+                let player = &self.players[i];
+                let input = match &mut self.bot {
+                    Some(bot) if i == BOT_PLAYER => {
+                        bot.next_input(&player.game, player.step.tick_frames())
+                    }
+                    _ => input,
+                };
+
+                let sent = self.players[i].tick(&input, &mut |event| on_event(i, event));
+                self.players[(i + 1) % PLAYERS].game.queue_garbage(sent);
+            }
+
+            if !ticked {
+                break;
+            }
+        }
+    }
+
+    /// Drops the elapsed time not ticked yet, after a pause.
+    pub fn reset_steps(&mut self) {
+        for player in self.players.iter_mut() {
+            player.step.reset();
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.players.iter().any(|p| p.game.is_over())
+    }
+
+    pub fn player_name(&self, index: usize) -> String {
+        if index == BOT_PLAYER && self.bot.is_some() {
+            "BOT".to_string()
+        } else {
+            format!("{}P", index + 1)
+        }
+    }
+
+    /// The last player standing, `None` while playing or when both topped out.
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_over() {
+            return None;
+        }
+
+        let mut standing = (0..PLAYERS).filter(|&i| !self.players[i].game.is_over());
+        match (standing.next(), standing.next()) {
+            (Some(winner), None) => Some(winner),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+// Tells the game the page lost the focus or got hidden, to pause it.
+var focus_lost = false;
+
+window.addEventListener("blur", function () {
+    focus_lost = true;
+});
+document.addEventListener("visibilitychange", function () {
+    if (document.hidden) {
+        focus_lost = true;
+    }
+});
+
+focus_register_js_plugin = function (importObject) {
+    importObject.env.take_focus_lost = function () {
+        var lost = focus_lost;
+        focus_lost = false;
+        return +lost;
+    }
+}
+
+miniquad_add_plugin({
+    register_plugin: focus_register_js_plugin,
+    name: "focus",
+    version: "0.1.0"
+});